@@ -1,5 +1,8 @@
 use std::fmt::{self, Display, Formatter};
+use std::num::NonZeroUsize;
+use std::ops::RangeInclusive;
 use std::path::PathBuf;
+use std::str::FromStr;
 
 use clap::builder::ValueParser;
 use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
@@ -37,11 +40,17 @@ pub enum Command {
 
     /// Watches an input file and recompiles on changes
     #[command(visible_alias = "w")]
-    Watch(CompileCommand),
+    Watch(WatchCommand),
 
     /// Processes an input file to extract provided metadata
     Query(QueryCommand),
 
+    /// Keeps a compiler running and answers JSON-RPC requests
+    Serve(ServeCommand),
+
+    /// Starts a language server on stdin and stdout
+    Lsp(LspCommand),
+
     /// Lists all discovered fonts in system and custom font paths
     Fonts(FontsCommand),
 
@@ -57,7 +66,7 @@ pub struct CompileCommand {
     #[clap(flatten)]
     pub common: SharedArgs,
 
-    /// Path to output file (PDF, PNG, or SVG)
+    /// Path to output file (PDF, PNG, JPEG, WebP, SVG, or HTML)
     pub output: Option<PathBuf>,
 
     /// The format of the output file, inferred from the extension by default
@@ -68,15 +77,75 @@ pub struct CompileCommand {
     #[arg(long = "open")]
     pub open: Option<Option<String>>,
 
-    /// The PPI (pixels per inch) to use for PNG export
+    /// Which pages to export (e.g. '1-3,7,10-'). When unspecified, all pages
+    /// are exported
+    ///
+    /// Pages are given by their one-based physical page number in the
+    /// document, independently of the page counter. A range without start or
+    /// end is open on that side.
+    #[arg(long = "pages", value_name = "PAGES", value_delimiter = ',')]
+    pub pages: Option<Vec<PageRangeArgument>>,
+
+    /// An archival PDF standard the output must conform to
+    #[arg(long = "pdf-standard", value_name = "STANDARD")]
+    pub pdf_standard: Option<PdfStandard>,
+
+    /// The PPI (pixels per inch) to use for PNG, JPEG, and WebP export
     #[arg(long = "ppi", default_value_t = 144.0)]
     pub ppi: f32,
 
+    /// The background of PNG, JPEG, and WebP images, either 'transparent' or
+    /// a hex color like '#f0f0f0', defaults to white
+    #[arg(long = "background", value_name = "COLOR")]
+    pub background: Option<BackgroundArgument>,
+
+    /// The quality of JPEG images, from 1 (smallest) to 100 (best), defaults
+    /// to 90
+    #[arg(
+        long = "jpeg-quality",
+        value_name = "QUALITY",
+        value_parser = clap::value_parser!(u8).range(1..=100),
+    )]
+    pub jpeg_quality: Option<u8>,
+
+    /// Merges all exported pages into a single PNG or SVG file instead of
+    /// writing one file per page
+    #[arg(long = "merge")]
+    pub merge: bool,
+
+    /// The gap around and between merged pages (e.g. '10pt' or '5mm'),
+    /// defaults to 10pt
+    #[arg(long = "gap", value_name = "LENGTH", requires = "merge")]
+    pub gap: Option<LengthArgument>,
+
+    /// The number of merged pages to place next to each other, defaults to
+    /// one page per row
+    #[arg(long = "merge-columns", value_name = "COLUMNS", requires = "merge")]
+    pub merge_columns: Option<NonZeroUsize>,
+
     /// Produces a flamegraph of the compilation process
     #[arg(long = "flamegraph", value_name = "OUTPUT_SVG")]
     pub flamegraph: Option<Option<PathBuf>>,
 }
 
+/// Watches an input file and recompiles on changes
+#[derive(Debug, Clone, Parser)]
+pub struct WatchCommand {
+    /// Arguments for compilation
+    #[clap(flatten)]
+    pub compile: CompileCommand,
+
+    /// Serves a live-reloading preview of the selected pages on a local HTTP
+    /// server. Clicking on a page shows the source location it stems from in
+    /// the preview's status bar, but does not open it in an editor
+    #[arg(long = "serve")]
+    pub serve: bool,
+
+    /// The port to serve the preview on
+    #[arg(long = "port", default_value_t = 3000, requires = "serve")]
+    pub port: u16,
+}
+
 /// Processes an input file to extract provided metadata
 #[derive(Debug, Clone, Parser)]
 pub struct QueryCommand {
@@ -100,6 +169,51 @@ pub struct QueryCommand {
     pub format: SerializationFormat,
 }
 
+/// Keeps a compiler running and answers JSON-RPC requests
+#[derive(Debug, Clone, Parser)]
+pub struct ServeCommand {
+    /// Shared arguments
+    #[clap(flatten)]
+    pub common: SharedArgs,
+
+    /// Communicates through newline-delimited JSON-RPC messages on stdin and
+    /// stdout
+    #[clap(long = "stdio", required = true)]
+    pub stdio: bool,
+}
+
+/// Starts a language server on stdin and stdout
+#[derive(Debug, Clone, Parser)]
+pub struct LspCommand {
+    /// Configures the project root (for absolute paths), defaults to the
+    /// workspace folder announced by the client
+    #[clap(long = "root", env = "TYPST_ROOT", value_name = "DIR")]
+    pub root: Option<PathBuf>,
+
+    /// Add a string key-value pair visible through `sys.inputs`
+    #[clap(
+        long = "input",
+        value_name = "key=value",
+        action = ArgAction::Append,
+        value_parser = ValueParser::new(parse_input_pair),
+    )]
+    pub inputs: Vec<(String, String)>,
+
+    /// Adds additional directories to search for fonts
+    #[clap(
+        long = "font-path",
+        env = "TYPST_FONT_PATHS",
+        value_name = "DIR",
+        value_delimiter = ENV_PATH_SEP,
+    )]
+    pub font_paths: Vec<PathBuf>,
+
+    /// Communicates through stdin and stdout, which is the only supported
+    /// transport
+    #[clap(long = "stdio")]
+    pub stdio: bool,
+}
+
 // Output file format for query command
 #[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
 pub enum SerializationFormat {
@@ -107,7 +221,7 @@ pub enum SerializationFormat {
     Yaml,
 }
 
-/// Common arguments of compile, watch, and query.
+/// Common arguments of compile, watch, query, and serve.
 #[derive(Debug, Clone, Args, Default)]
 pub struct SharedArgs {
     /// Path to input Typst file
@@ -160,6 +274,129 @@ fn parse_input_pair(raw: &str) -> Result<(String, String), String> {
     Ok((key, val))
 }
 
+/// A range of pages to export, given as a single page number (`7`), a closed
+/// range (`1-3`), or a range that is open on one side (`10-` or `-3`).
+#[derive(Debug, Clone)]
+pub struct PageRangeArgument(RangeInclusive<Option<NonZeroUsize>>);
+
+impl PageRangeArgument {
+    /// The range of page numbers.
+    pub fn to_range(&self) -> RangeInclusive<Option<NonZeroUsize>> {
+        self.0.clone()
+    }
+}
+
+impl FromStr for PageRangeArgument {
+    type Err = &'static str;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.split('-').map(str::trim).collect::<Vec<_>>().as_slice() {
+            [] | [""] => Err("page range must not be empty"),
+            [page] => {
+                let page = parse_page_number(page)?;
+                Ok(Self(Some(page)..=Some(page)))
+            }
+            ["", ""] => Err("page range must have a start or an end"),
+            [start, ""] => Ok(Self(Some(parse_page_number(start)?)..=None)),
+            ["", end] => Ok(Self(None..=Some(parse_page_number(end)?))),
+            [start, end] => {
+                let start = parse_page_number(start)?;
+                let end = parse_page_number(end)?;
+                if start > end {
+                    return Err("page range must not end before it starts");
+                }
+                Ok(Self(Some(start)..=Some(end)))
+            }
+            _ => Err("page range must contain at most one hyphen"),
+        }
+    }
+}
+
+/// Parses a one-based page number.
+fn parse_page_number(value: &str) -> Result<NonZeroUsize, &'static str> {
+    match value.parse::<usize>() {
+        Ok(0) => Err("page numbers start at one"),
+        Ok(n) => Ok(NonZeroUsize::new(n).unwrap()),
+        Err(_) => Err("not a valid page number"),
+    }
+}
+
+/// A non-negative length, given as a number with a unit (`pt`, `mm`, `cm`, or
+/// `in`).
+#[derive(Debug, Copy, Clone)]
+pub struct LengthArgument(f64);
+
+impl LengthArgument {
+    /// The length in points.
+    pub fn to_pt(self) -> f64 {
+        self.0
+    }
+}
+
+impl FromStr for LengthArgument {
+    type Err = &'static str;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let split = value
+            .find(|c: char| c.is_ascii_alphabetic())
+            .ok_or("length must have a unit (pt, mm, cm, or in)")?;
+        let (number, unit) = value.split_at(split);
+        let number = number
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|number| number.is_finite())
+            .ok_or("not a valid length")?;
+        let factor = match unit {
+            "pt" => 1.0,
+            "mm" => 72.0 / 25.4,
+            "cm" => 72.0 / 2.54,
+            "in" => 72.0,
+            _ => return Err("unknown unit (expected pt, mm, cm, or in)"),
+        };
+        if number < 0.0 {
+            return Err("length must not be negative");
+        }
+        Ok(Self(number * factor))
+    }
+}
+
+/// The background of exported images, given as `transparent` or as a hex
+/// color like `#f0f0f0` or `#ff000080`.
+#[derive(Debug, Copy, Clone)]
+pub struct BackgroundArgument([u8; 4]);
+
+impl BackgroundArgument {
+    /// The red, green, blue, and alpha components of the color.
+    pub fn to_rgba(self) -> [u8; 4] {
+        self.0
+    }
+}
+
+impl FromStr for BackgroundArgument {
+    type Err = &'static str;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("transparent") {
+            return Ok(Self([0; 4]));
+        }
+
+        let hex = value.strip_prefix('#').unwrap_or(value);
+        if !matches!(hex.len(), 6 | 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err("expected `transparent` or a hex color like `#f0f0f0`");
+        }
+
+        let mut rgba = [u8::MAX; 4];
+        for (i, component) in rgba.iter_mut().take(hex.len() / 2).enumerate() {
+            *component = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+        }
+
+        Ok(Self(rgba))
+    }
+}
+
 /// Lists all discovered fonts in system and custom font paths
 #[derive(Debug, Clone, Parser, Default)]
 pub struct FontsCommand {
@@ -215,7 +452,21 @@ pub struct UpdateCommand {
 pub enum OutputFormat {
     Pdf,
     Png,
+    Jpeg,
+    Webp,
     Svg,
+    Html,
+}
+
+/// An archival PDF standard that exported PDFs can conform to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum PdfStandard {
+    /// PDF/A-2b
+    #[value(name = "a-2b")]
+    A2b,
+    /// PDF/A-3b
+    #[value(name = "a-3b")]
+    A3b,
 }
 
 impl Display for OutputFormat {
@@ -226,3 +477,66 @@ impl Display for OutputFormat {
             .fmt(f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArguments, clap::Error> {
+        CliArguments::try_parse_from(["typst", "compile", "main.typ"].iter().chain(args))
+    }
+
+    fn pages(args: &[&str]) -> Vec<(Option<usize>, Option<usize>)> {
+        let Command::Compile(command) = parse(args).unwrap().command else {
+            unreachable!()
+        };
+        command
+            .pages
+            .unwrap_or_default()
+            .iter()
+            .map(|range| {
+                let range = range.to_range();
+                (range.start().map(NonZeroUsize::get), range.end().map(NonZeroUsize::get))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_page_ranges() {
+        assert_eq!(
+            pages(&["--pages", "1-3,7,10-"]),
+            [(Some(1), Some(3)), (Some(7), Some(7)), (Some(10), None)]
+        );
+        assert_eq!(pages(&["--pages", " -2 "]), [(None, Some(2))]);
+        assert_eq!(pages(&[]), []);
+    }
+
+    #[test]
+    fn test_parse_page_ranges_invalid() {
+        let error = |value: &str| PageRangeArgument::from_str(value).unwrap_err();
+        assert_eq!(error("3-1"), "page range must not end before it starts");
+        assert_eq!(error("0"), "page numbers start at one");
+        assert_eq!(error("0-2"), "page numbers start at one");
+        assert_eq!(error(""), "page range must not be empty");
+        assert_eq!(error("-"), "page range must have a start or an end");
+        assert_eq!(error("1-2-3"), "page range must contain at most one hyphen");
+        assert_eq!(error("one"), "not a valid page number");
+        assert!(parse(&["--pages", "3-1"])
+            .unwrap_err()
+            .to_string()
+            .contains("page range must not end before it starts"));
+        assert!(parse(&["--pages", ""]).is_err());
+    }
+
+    #[test]
+    fn test_parse_merge_columns() {
+        let Command::Compile(command) =
+            parse(&["--merge", "--merge-columns", "3"]).unwrap().command
+        else {
+            unreachable!()
+        };
+        assert_eq!(command.merge_columns, NonZeroUsize::new(3));
+        assert!(parse(&["--merge", "--merge-columns", "0"]).is_err());
+        assert!(parse(&["--merge-columns", "2"]).is_err());
+    }
+}
@@ -1,23 +1,34 @@
 use std::fs;
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 
 use chrono::{Datelike, Timelike};
 use codespan_reporting::diagnostic::{Diagnostic, Label};
 use codespan_reporting::term::{self, termcolor};
 use ecow::{eco_format, EcoString};
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::{ColorType, ImageEncoder};
 use parking_lot::RwLock;
-use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use termcolor::{ColorChoice, StandardStream};
-use typst::diag::{bail, At, Severity, SourceDiagnostic, StrResult};
+use tiny_skia as sk;
+use typst::diag::{bail, At, Severity, SourceDiagnostic, SourceResult, StrResult};
 use typst::eval::Tracer;
 use typst::foundations::Datetime;
-use typst::layout::Frame;
+use typst::layout::{Abs, Frame, PageRanges};
 use typst::model::Document;
 use typst::syntax::{FileId, Source, Span};
+use typst::util::NonZeroExt;
 use typst::visualize::Color;
 use typst::{World, WorldExt};
+use typst_pdf::PdfOptions;
 
-use crate::args::{CompileCommand, DiagnosticFormat, OutputFormat};
+use crate::args::{
+    CompileCommand, DiagnosticFormat, LengthArgument, OutputFormat, PageRangeArgument,
+    PdfStandard,
+};
+use crate::preview::Preview;
 use crate::watch::Status;
 use crate::world::SystemWorld;
 use crate::{color_stream, set_failed};
@@ -33,7 +44,10 @@ impl CompileCommand {
                 match self.output_format().unwrap_or(OutputFormat::Pdf) {
                     OutputFormat::Pdf => "pdf",
                     OutputFormat::Png => "png",
+                    OutputFormat::Jpeg => "jpg",
+                    OutputFormat::Webp => "webp",
                     OutputFormat::Svg => "svg",
+                    OutputFormat::Html => "html",
                 },
             )
         })
@@ -49,36 +63,51 @@ impl CompileCommand {
             match output.extension() {
                 Some(ext) if ext.eq_ignore_ascii_case("pdf") => OutputFormat::Pdf,
                 Some(ext) if ext.eq_ignore_ascii_case("png") => OutputFormat::Png,
+                Some(ext) if ext.eq_ignore_ascii_case("jpg") => OutputFormat::Jpeg,
+                Some(ext) if ext.eq_ignore_ascii_case("jpeg") => OutputFormat::Jpeg,
+                Some(ext) if ext.eq_ignore_ascii_case("webp") => OutputFormat::Webp,
                 Some(ext) if ext.eq_ignore_ascii_case("svg") => OutputFormat::Svg,
+                Some(ext) if ext.eq_ignore_ascii_case("html") => OutputFormat::Html,
                 _ => bail!("could not infer output format for path {}.\nconsider providing the format manually with `--format/-f`", output.display()),
             }
         } else {
             OutputFormat::Pdf
         })
     }
+
+    /// The ranges of pages to export, if only some of them should be.
+    pub fn exported_page_ranges(&self) -> Option<PageRanges> {
+        self.pages.as_ref().map(|pages| {
+            PageRanges::new(pages.iter().map(PageRangeArgument::to_range).collect())
+        })
+    }
 }
 
 /// Execute a compilation command.
 pub fn compile(mut command: CompileCommand) -> StrResult<()> {
     let mut world = SystemWorld::new(&command.common)?;
-    compile_once(&mut world, &mut command, false)?;
+    compile_once(&mut world, &mut command, false, None)?;
     Ok(())
 }
 
 /// Compile a single time.
 ///
 /// Returns whether it compiled without errors.
+///
+/// When watching, a `preview` of the pages may be updated along with the
+/// export.
 #[tracing::instrument(skip_all)]
 pub fn compile_once(
     world: &mut SystemWorld,
     command: &mut CompileCommand,
     watching: bool,
+    preview: Option<&Preview>,
 ) -> StrResult<()> {
     tracing::info!("Starting compilation");
 
     let start = std::time::Instant::now();
     if watching {
-        Status::Compiling.print(command).unwrap();
+        Status::Compiling.print(command, preview).unwrap();
     }
 
     // Check if main file can be read and opened.
@@ -87,7 +116,11 @@ pub fn compile_once(
         tracing::info!("Failed to open and decode main file");
 
         if watching {
-            Status::Error.print(command).unwrap();
+            Status::Error.print(command, preview).unwrap();
+        }
+
+        if let Some(preview) = preview {
+            preview.fail();
         }
 
         print_diagnostics(world, &errors, &[], command.common.diagnostic_format)
@@ -97,21 +130,26 @@ pub fn compile_once(
     }
 
     let mut tracer = Tracer::new();
-    let result = typst::compile(world, &mut tracer);
+    let result = typst::compile(world, &mut tracer).and_then(|document| {
+        export(world, &document, command, watching)?;
+        if let Some(preview) = preview {
+            preview.update(&document);
+        }
+        Ok(())
+    });
     let warnings = tracer.warnings();
 
     match result {
         // Export the PDF / PNG.
-        Ok(document) => {
-            export(world, &document, command, watching)?;
+        Ok(()) => {
             let duration = start.elapsed();
 
             tracing::info!("Compilation succeeded in {duration:?}");
             if watching {
                 if warnings.is_empty() {
-                    Status::Success(duration).print(command).unwrap();
+                    Status::Success(duration).print(command, preview).unwrap();
                 } else {
-                    Status::PartialSuccess(duration).print(command).unwrap();
+                    Status::PartialSuccess(duration).print(command, preview).unwrap();
                 }
             }
 
@@ -129,7 +167,11 @@ pub fn compile_once(
             tracing::info!("Compilation failed");
 
             if watching {
-                Status::Error.print(command).unwrap();
+                Status::Error.print(command, preview).unwrap();
+            }
+
+            if let Some(preview) = preview {
+                preview.fail();
             }
 
             print_diagnostics(
@@ -146,20 +188,36 @@ pub fn compile_once(
 }
 
 /// Export into the target format.
-fn export(
+pub fn export(
     world: &mut SystemWorld,
     document: &Document,
     command: &CompileCommand,
     watching: bool,
-) -> StrResult<()> {
-    match command.output_format()? {
+) -> SourceResult<()> {
+    let format = command.output_format().at(Span::detached())?;
+    if command.merge && matches!(format, OutputFormat::Pdf | OutputFormat::Html) {
+        bail!(Span::detached(), "pages can only be merged when exporting to images");
+    }
+
+    match format {
         OutputFormat::Png => {
             export_image(world, document, command, watching, ImageExportFormat::Png)
+                .at(Span::detached())
+        }
+        OutputFormat::Jpeg => {
+            export_image(world, document, command, watching, ImageExportFormat::Jpeg)
+                .at(Span::detached())
+        }
+        OutputFormat::Webp => {
+            export_image(world, document, command, watching, ImageExportFormat::Webp)
+                .at(Span::detached())
         }
         OutputFormat::Svg => {
             export_image(world, document, command, watching, ImageExportFormat::Svg)
+                .at(Span::detached())
         }
         OutputFormat::Pdf => export_pdf(document, command, world),
+        OutputFormat::Html => export_html(document, command, world),
     }
 }
 
@@ -168,12 +226,40 @@ fn export_pdf(
     document: &Document,
     command: &CompileCommand,
     world: &SystemWorld,
-) -> StrResult<()> {
+) -> SourceResult<()> {
     let ident = world.input().to_string_lossy();
-    let buffer = typst_pdf::pdf(document, Some(&ident), now());
+    let options = PdfOptions {
+        ident: Some(&ident),
+        timestamp: now(),
+        standard: command.pdf_standard.map(|standard| match standard {
+            PdfStandard::A2b => typst_pdf::PdfStandard::A2b,
+            PdfStandard::A3b => typst_pdf::PdfStandard::A3b,
+        }),
+        page_ranges: command.exported_page_ranges(),
+    };
+    let buffer = typst_pdf::pdf(document, &options)?;
     let output = command.output();
     fs::write(output, buffer)
-        .map_err(|err| eco_format!("failed to write PDF file ({err})"))?;
+        .map_err(|err| eco_format!("failed to write PDF file ({err})"))
+        .at(Span::detached())?;
+    Ok(())
+}
+
+/// Export to an HTML file.
+fn export_html(
+    document: &Document,
+    command: &CompileCommand,
+    world: &SystemWorld,
+) -> SourceResult<()> {
+    if command.pages.is_some() {
+        bail!(Span::detached(), "cannot export a selection of pages to HTML");
+    }
+
+    let html = typst_html::html(world, document)?;
+    let output = command.output();
+    fs::write(output, html)
+        .map_err(|err| eco_format!("failed to write HTML file ({err})"))
+        .at(Span::detached())?;
     Ok(())
 }
 
@@ -191,12 +277,27 @@ fn now() -> Option<Datetime> {
 }
 
 /// An image format to export in.
+#[derive(Copy, Clone)]
 enum ImageExportFormat {
     Png,
+    Jpeg,
+    Webp,
     Svg,
 }
 
-/// Export to one or multiple PNGs.
+impl ImageExportFormat {
+    /// The name of the format in messages.
+    fn name(self) -> &'static str {
+        match self {
+            Self::Png => "PNG",
+            Self::Jpeg => "JPEG",
+            Self::Webp => "WebP",
+            Self::Svg => "SVG",
+        }
+    }
+}
+
+/// Export to one or multiple images.
 fn export_image(
     world: &mut SystemWorld,
     document: &Document,
@@ -204,12 +305,44 @@ fn export_image(
     watching: bool,
     fmt: ImageExportFormat,
 ) -> StrResult<()> {
+    let opaque = command.background.map_or(true, |bg| bg.to_rgba()[3] == u8::MAX);
+    if matches!(fmt, ImageExportFormat::Jpeg) && !opaque {
+        bail!(
+            "JPEG images cannot have a transparent background\n\
+             consider exporting to PNG or WebP instead"
+        );
+    }
+
     // Determine whether we have a `{n}` numbering.
     let output = command.output();
     let string = output.to_str().unwrap_or_default();
     let numbered = string.contains("{n}");
-    if !numbered && document.pages.len() > 1 {
-        bail!("cannot export multiple images without `{{n}}` in output path");
+
+    // Select the pages to export, keeping their original page numbers.
+    let ranges = command.exported_page_ranges();
+    let pages: Vec<(usize, &Frame)> = document
+        .pages
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| ranges.as_ref().map_or(true, |r| r.includes_page_index(i)))
+        .collect();
+
+    if pages.is_empty() {
+        bail!("page selection contains no pages of the document");
+    }
+
+    if command.merge {
+        if numbered {
+            bail!("cannot merge pages with `{{n}}` in output path");
+        }
+        return export_merged_image(world, document, command, watching, fmt, &pages);
+    }
+
+    if !numbered && pages.len() > 1 {
+        bail!(
+            "cannot export multiple images without `{{n}}` in output path\n\
+             consider merging them into a single image with `--merge`"
+        );
     }
 
     // Find a number width that accommodates all pages. For instance, the
@@ -220,11 +353,9 @@ fn export_image(
     let cache = world.export_cache();
 
     // The results are collected in a `Vec<()>` which does not allocate.
-    document
-        .pages
+    pages
         .par_iter()
-        .enumerate()
-        .map(|(i, frame)| {
+        .map(|&(i, frame)| {
             let storage;
             let path = if numbered {
                 storage = string.replace("{n}", &format!("{:0width$}", i + 1));
@@ -240,20 +371,21 @@ fn export_image(
                 return Ok(());
             }
 
-            match fmt {
-                ImageExportFormat::Png => {
-                    let pixmap =
-                        typst_render::render(frame, command.ppi / 72.0, Color::WHITE);
-                    pixmap
-                        .save_png(path)
-                        .map_err(|err| eco_format!("failed to write PNG file ({err})"))?;
+            let buffer = match fmt {
+                ImageExportFormat::Svg => typst_svg::svg(frame).into_bytes(),
+                _ => {
+                    let pixmap = typst_render::render(
+                        frame,
+                        command.ppi / 72.0,
+                        background(command),
+                    );
+                    encode_raster(&pixmap, fmt, command)?
                 }
-                ImageExportFormat::Svg => {
-                    let svg = typst_svg::svg(frame);
-                    fs::write(path, svg.as_bytes())
-                        .map_err(|err| eco_format!("failed to write SVG file ({err})"))?;
-                }
-            }
+            };
+
+            fs::write(path, buffer).map_err(|err| {
+                eco_format!("failed to write {} file ({err})", fmt.name())
+            })?;
 
             Ok(())
         })
@@ -262,6 +394,116 @@ fn export_image(
     Ok(())
 }
 
+/// Export the selected pages into a single PNG or SVG, laid out in a grid.
+fn export_merged_image(
+    world: &mut SystemWorld,
+    document: &Document,
+    command: &CompileCommand,
+    watching: bool,
+    fmt: ImageExportFormat,
+    pages: &[(usize, &Frame)],
+) -> StrResult<()> {
+    if pages.is_empty() {
+        bail!("page selection contains no pages of the document");
+    }
+
+    let output = command.output();
+    let gap = Abs::pt(command.gap.map_or(10.0, LengthArgument::to_pt));
+    let columns = command.merge_columns.unwrap_or(NonZeroUsize::ONE);
+
+    // If we are watching and none of the pages changed, skip the export. All
+    // pages are checked so that the cache is updated for each of them.
+    let cache = world.export_cache();
+    let changed = pages.iter().filter(|&&(i, frame)| !cache.is_cached(i, frame)).count();
+    if watching && changed == 0 && output.exists() {
+        return Ok(());
+    }
+
+    let buffer = match fmt {
+        ImageExportFormat::Svg => {
+            let ranges = command.exported_page_ranges();
+            typst_svg::svg_merged(document, gap, columns, ranges.as_ref()).into_bytes()
+        }
+        _ => {
+            let frames: Vec<Frame> =
+                pages.iter().map(|&(_, frame)| frame.clone()).collect();
+            let pixmap = typst_render::render_merged(
+                &frames,
+                command.ppi / 72.0,
+                background(command),
+                gap,
+                background(command),
+                columns,
+            )
+            .ok_or("merged image is too large to render")?;
+            encode_raster(&pixmap, fmt, command)?
+        }
+    };
+
+    fs::write(&output, buffer)
+        .map_err(|err| eco_format!("failed to write {} file ({err})", fmt.name()))?;
+
+    Ok(())
+}
+
+/// The background to render raster images on.
+fn background(command: &CompileCommand) -> Color {
+    match command.background {
+        Some(background) => {
+            let [r, g, b, a] = background.to_rgba();
+            Color::from_u8(r, g, b, a)
+        }
+        None => Color::WHITE,
+    }
+}
+
+/// Encode a rendered image as a PNG, JPEG, or WebP file.
+fn encode_raster(
+    pixmap: &sk::Pixmap,
+    fmt: ImageExportFormat,
+    command: &CompileCommand,
+) -> StrResult<Vec<u8>> {
+    // The pixmap stores premultiplied colors, but the encoders expect
+    // straight ones.
+    let pixels = || pixmap.pixels().iter().map(|pixel| pixel.demultiply());
+    let (width, height) = (pixmap.width(), pixmap.height());
+
+    let mut buffer = vec![];
+    let result = match fmt {
+        ImageExportFormat::Png => {
+            return pixmap
+                .encode_png()
+                .map_err(|err| eco_format!("failed to encode PNG file ({err})"));
+        }
+        ImageExportFormat::Jpeg => {
+            let data: Vec<u8> =
+                pixels().flat_map(|c| [c.red(), c.green(), c.blue()]).collect();
+            let quality = command.jpeg_quality.unwrap_or(90);
+            JpegEncoder::new_with_quality(&mut buffer, quality).write_image(
+                &data,
+                width,
+                height,
+                ColorType::Rgb8,
+            )
+        }
+        ImageExportFormat::Webp => {
+            let data: Vec<u8> = pixels()
+                .flat_map(|c| [c.red(), c.green(), c.blue(), c.alpha()])
+                .collect();
+            WebPEncoder::new_lossless(&mut buffer).write_image(
+                &data,
+                width,
+                height,
+                ColorType::Rgba8,
+            )
+        }
+        ImageExportFormat::Svg => unreachable!(),
+    };
+
+    result.map_err(|err| eco_format!("failed to encode {} file ({err})", fmt.name()))?;
+    Ok(buffer)
+}
+
 /// Caches exported files so that we can avoid re-exporting them if they haven't
 /// changed.
 ///
@@ -287,7 +529,11 @@ impl ExportCache {
 
         let mut cache = self.cache.upgradable_read();
         if i >= cache.len() {
-            cache.with_upgraded(|cache| cache.push(hash));
+            // Pages may be exported out of order or only partially.
+            cache.with_upgraded(|cache| {
+                cache.resize(i + 1, 0);
+                cache[i] = hash;
+            });
             return false;
         }
 
@@ -0,0 +1,884 @@
+//! A language server built on `typst-ide`.
+//!
+//! The server speaks the Language Server Protocol on stdin and stdout. Open
+//! documents are kept in memory and updated incrementally through
+//! [`Source::edit`], so that each change only reparses what is necessary.
+//! After every change, the changed document is compiled as the main file and
+//! the resulting diagnostics are published.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use comemo::Prehashed;
+use ecow::eco_format;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _,
+    PublishDiagnostics,
+};
+use lsp_types::request::{
+    Completion, DocumentSymbolRequest, GotoDefinition, HoverRequest, Request as _,
+};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams,
+    CompletionResponse, CompletionTextEdit, CompletionTriggerKind, DiagnosticSeverity,
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DocumentSymbolParams, DocumentSymbolResponse, GotoDefinitionParams,
+    GotoDefinitionResponse, Hover, HoverContents, HoverParams, HoverProviderCapability,
+    InitializeParams, InsertTextFormat, Location, MarkupContent, MarkupKind, OneOf,
+    Position, PublishDiagnosticsParams, Range, ServerCapabilities,
+    TextDocumentPositionParams, TextDocumentSyncCapability, TextDocumentSyncKind,
+    TextEdit, Url,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use typst::diag::{FileResult, Severity, SourceDiagnostic, StrResult};
+use typst::eval::Tracer;
+use typst::foundations::{Bytes, Datetime};
+use typst::model::Document;
+use typst::syntax::{FileId, Source};
+use typst::text::{Font, FontBook};
+use typst::{Library, World, WorldExt};
+use typst_ide::{CompletionKind, SymbolKind, Tooltip};
+
+use crate::args::{LspCommand, SharedArgs};
+use crate::world::SystemWorld;
+
+/// Execute an lsp command.
+pub fn lsp(command: &LspCommand) -> StrResult<()> {
+    let (connection, io_threads) = Connection::stdio();
+    run(command, &connection)?;
+    drop(connection);
+    io_threads
+        .join()
+        .map_err(|err| eco_format!("failed to stop language server ({err})"))
+}
+
+/// Initialize the server and handle messages until the client shuts it down.
+fn run(command: &LspCommand, connection: &Connection) -> StrResult<()> {
+    let capabilities = serde_json::to_value(capabilities())
+        .map_err(|err| eco_format!("failed to serialize capabilities ({err})"))?;
+    let params = connection
+        .initialize(capabilities)
+        .map_err(|err| eco_format!("failed to initialize language server ({err})"))?;
+    let params: InitializeParams = serde_json::from_value(params)
+        .map_err(|err| eco_format!("invalid initialize params ({err})"))?;
+
+    let root = command.root.clone().or_else(|| workspace_root(&params));
+    let mut server = Server::new(command, root)?;
+    tracing::info!("Starting language server in {}", server.world.root().display());
+
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                let shutdown = connection
+                    .handle_shutdown(&request)
+                    .map_err(|err| eco_format!("failed to shut down ({err})"))?;
+                if shutdown {
+                    break;
+                }
+                let response = server.request(request);
+                send(connection, response.into())?;
+            }
+            Message::Notification(notification) => {
+                for notification in server.notification(notification) {
+                    send(connection, notification.into())?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// The features supported by the server.
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(
+            TextDocumentSyncKind::INCREMENTAL,
+        )),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(
+                ["#", ".", "@", "<", "(", ",", ":", " "].map(String::from).to_vec(),
+            ),
+            ..CompletionOptions::default()
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        ..ServerCapabilities::default()
+    }
+}
+
+/// The root of the workspace announced by the client, if it is on disk.
+#[allow(deprecated)]
+fn workspace_root(params: &InitializeParams) -> Option<PathBuf> {
+    params
+        .workspace_folders
+        .iter()
+        .flatten()
+        .map(|folder| &folder.uri)
+        .chain(&params.root_uri)
+        .find_map(|uri| uri.to_file_path().ok())
+}
+
+/// Send a message to the client.
+fn send(connection: &Connection, message: Message) -> StrResult<()> {
+    connection
+        .sender
+        .send(message)
+        .map_err(|err| eco_format!("failed to send message ({err})"))
+}
+
+/// The state of the language server.
+struct Server {
+    /// Provides access to fonts, the library, and files that are not open.
+    world: SystemWorld,
+    /// The documents that are open in the client.
+    documents: HashMap<FileId, OpenDocument>,
+}
+
+/// A document that is open in the client.
+struct OpenDocument {
+    /// The document's URI in the client.
+    uri: Url,
+    /// The document's current contents.
+    source: Source,
+    /// The result of the last successful compilation with this document as
+    /// the main file.
+    document: Option<Document>,
+    /// The URIs for which the last compilation published diagnostics.
+    published: HashSet<Url>,
+}
+
+impl Server {
+    /// Create a server for a project root, which defaults to the current
+    /// working directory.
+    fn new(command: &LspCommand, root: Option<PathBuf>) -> StrResult<Self> {
+        let root = match root {
+            Some(root) => root,
+            None => std::env::current_dir()
+                .map_err(|err| eco_format!("failed to determine project root ({err})"))?,
+        };
+
+        // The main file is determined per compilation by the open document, so
+        // the root itself serves as a placeholder input.
+        let args = SharedArgs {
+            input: root.clone(),
+            root: Some(root),
+            inputs: command.inputs.clone(),
+            font_paths: command.font_paths.clone(),
+            ..SharedArgs::default()
+        };
+
+        Ok(Self {
+            world: SystemWorld::new(&args)?,
+            documents: HashMap::new(),
+        })
+    }
+
+    /// Handle a request and produce the response.
+    fn request(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            Completion::METHOD => respond(request, |params| self.completion(params)),
+            HoverRequest::METHOD => respond(request, |params| self.hover(params)),
+            GotoDefinition::METHOD => respond(request, |params| self.definition(params)),
+            DocumentSymbolRequest::METHOD => {
+                respond(request, |params| self.document_symbols(params))
+            }
+            method => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("unknown method `{method}`"),
+            ),
+        }
+    }
+
+    /// Handle a notification and produce notifications in return.
+    fn notification(&mut self, notification: Notification) -> Vec<Notification> {
+        let result = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                params(notification.params).and_then(|params| self.did_open(params))
+            }
+            DidChangeTextDocument::METHOD => {
+                params(notification.params).and_then(|params| self.did_change(params))
+            }
+            DidCloseTextDocument::METHOD => {
+                params(notification.params).and_then(|params| self.did_close(params))
+            }
+            _ => Ok(vec![]),
+        };
+
+        result.unwrap_or_else(|err| {
+            tracing::warn!("Failed to handle {} ({err})", notification.method);
+            vec![]
+        })
+    }
+
+    /// Start tracking a document and publish its diagnostics.
+    fn did_open(
+        &mut self,
+        params: DidOpenTextDocumentParams,
+    ) -> StrResult<Vec<Notification>> {
+        let uri = params.text_document.uri;
+        let id = self.id_for_uri(&uri)?;
+        let source = Source::new(id, params.text_document.text);
+        self.documents.insert(
+            id,
+            OpenDocument {
+                uri,
+                source,
+                document: None,
+                published: HashSet::new(),
+            },
+        );
+        Ok(self.compile(id))
+    }
+
+    /// Apply changes to a document and publish its new diagnostics.
+    fn did_change(
+        &mut self,
+        params: DidChangeTextDocumentParams,
+    ) -> StrResult<Vec<Notification>> {
+        let id = self.id_for_uri(&params.text_document.uri)?;
+        let open = self.documents.get_mut(&id).ok_or("document is not open")?;
+        for change in params.content_changes {
+            match change.range {
+                Some(range) => {
+                    let range = to_range(&open.source, range);
+                    open.source.edit(range, &change.text);
+                }
+                None => {
+                    open.source.replace(&change.text);
+                }
+            }
+        }
+        Ok(self.compile(id))
+    }
+
+    /// Stop tracking a document and clear its diagnostics.
+    fn did_close(
+        &mut self,
+        params: DidCloseTextDocumentParams,
+    ) -> StrResult<Vec<Notification>> {
+        let id = self.id_for_uri(&params.text_document.uri)?;
+        let open = self.documents.remove(&id).ok_or("document is not open")?;
+        Ok(open.published.into_iter().map(|uri| publish(uri, vec![])).collect())
+    }
+
+    /// Compile with an open document as the main file and produce the
+    /// notifications that publish the diagnostics.
+    fn compile(&mut self, main: FileId) -> Vec<Notification> {
+        self.world.reset();
+
+        let world = LspWorld {
+            system: &self.world,
+            documents: &self.documents,
+            main,
+        };
+
+        let mut tracer = Tracer::new();
+        let result = typst::compile(&world, &mut tracer);
+        comemo::evict(10);
+
+        let mut diagnostics = tracer.warnings().to_vec();
+        let document = match result {
+            Ok(document) => Some(document),
+            Err(errors) => {
+                diagnostics.extend(errors);
+                None
+            }
+        };
+
+        // Group the diagnostics by the file they belong to.
+        let mut grouped: HashMap<Url, Vec<lsp_types::Diagnostic>> = HashMap::new();
+        for diagnostic in &diagnostics {
+            let id = diagnostic.span.id().unwrap_or(main);
+            let (Some(uri), Ok(source)) = (self.uri_for_id(id), world.source(id)) else {
+                continue;
+            };
+            grouped
+                .entry(uri)
+                .or_default()
+                .push(to_diagnostic(&world, &source, diagnostic));
+        }
+
+        let open = self.documents.get_mut(&main).unwrap();
+        if document.is_some() {
+            open.document = document;
+        }
+
+        // Clear the diagnostics of files that don't have any anymore and
+        // make sure that the main file's diagnostics are always refreshed.
+        let mut stale = std::mem::take(&mut open.published);
+        stale.insert(open.uri.clone());
+        open.published = grouped.keys().cloned().collect();
+
+        let mut notifications: Vec<_> = stale
+            .into_iter()
+            .filter(|uri| !grouped.contains_key(uri))
+            .map(|uri| publish(uri, vec![]))
+            .collect();
+        notifications.extend(grouped.into_iter().map(|(uri, items)| publish(uri, items)));
+        notifications
+    }
+
+    /// Autocomplete at a position.
+    fn completion(
+        &self,
+        params: CompletionParams,
+    ) -> StrResult<Option<CompletionResponse>> {
+        let (open, cursor) = self.locate(&params.text_document_position)?;
+        let explicit = params.context.map_or(true, |context| {
+            context.trigger_kind == CompletionTriggerKind::INVOKED
+        });
+
+        let world = self.world_for(open.source.id());
+        let Some((from, completions)) = typst_ide::autocomplete(
+            &world,
+            open.document.as_ref(),
+            &open.source,
+            cursor,
+            explicit,
+        ) else {
+            return Ok(None);
+        };
+
+        let range = to_lsp_range(&open.source, from..cursor);
+        let items = completions
+            .into_iter()
+            .map(|completion| {
+                let apply = completion.apply.as_ref().unwrap_or(&completion.label);
+                CompletionItem {
+                    label: completion.label.to_string(),
+                    kind: Some(match completion.kind {
+                        CompletionKind::Syntax => CompletionItemKind::SNIPPET,
+                        CompletionKind::Func => CompletionItemKind::FUNCTION,
+                        CompletionKind::Type => CompletionItemKind::CLASS,
+                        CompletionKind::Param => CompletionItemKind::VARIABLE,
+                        CompletionKind::Constant => CompletionItemKind::CONSTANT,
+                        CompletionKind::Symbol(_) => CompletionItemKind::TEXT,
+                    }),
+                    detail: completion.detail.as_ref().map(ToString::to_string),
+                    insert_text_format: Some(InsertTextFormat::SNIPPET),
+                    text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                        range,
+                        new_text: to_snippet(apply),
+                    })),
+                    ..CompletionItem::default()
+                }
+            })
+            .collect();
+
+        Ok(Some(CompletionResponse::Array(items)))
+    }
+
+    /// Describe the item at a position.
+    fn hover(&self, params: HoverParams) -> StrResult<Option<Hover>> {
+        let (open, cursor) = self.locate(&params.text_document_position_params)?;
+        let world = self.world_for(open.source.id());
+        let tooltip =
+            typst_ide::tooltip(&world, open.document.as_ref(), &open.source, cursor);
+
+        Ok(tooltip.map(|tooltip| Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: match tooltip {
+                    Tooltip::Text(text) => text.into(),
+                    Tooltip::Code(code) => format!("```typc\n{code}\n```"),
+                },
+            }),
+            range: None,
+        }))
+    }
+
+    /// Find the definition of the item at a position.
+    fn definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> StrResult<Option<GotoDefinitionResponse>> {
+        let (open, cursor) = self.locate(&params.text_document_position_params)?;
+        let world = self.world_for(open.source.id());
+        let Some(definition) =
+            typst_ide::definition(&world, open.document.as_ref(), &open.source, cursor)
+        else {
+            return Ok(None);
+        };
+
+        let (Some(uri), Ok(source)) =
+            (self.uri_for_id(definition.id), world.source(definition.id))
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(GotoDefinitionResponse::Scalar(Location {
+            uri,
+            range: to_lsp_range(&source, definition.range),
+        })))
+    }
+
+    /// List the symbols in a document.
+    fn document_symbols(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> StrResult<Option<DocumentSymbolResponse>> {
+        let id = self.id_for_uri(&params.text_document.uri)?;
+        let open = self.documents.get(&id).ok_or("document is not open")?;
+        let symbols = typst_ide::document_symbols(&open.source)
+            .into_iter()
+            .map(|symbol| to_symbol(&open.source, symbol))
+            .collect();
+        Ok(Some(DocumentSymbolResponse::Nested(symbols)))
+    }
+
+    /// Find the open document and the byte offset a position refers to.
+    fn locate(
+        &self,
+        position: &TextDocumentPositionParams,
+    ) -> StrResult<(&OpenDocument, usize)> {
+        let id = self.id_for_uri(&position.text_document.uri)?;
+        let open = self.documents.get(&id).ok_or("document is not open")?;
+        Ok((open, to_offset(&open.source, position.position)))
+    }
+
+    /// A world with an open document as the main file.
+    fn world_for(&self, main: FileId) -> LspWorld<'_> {
+        LspWorld {
+            system: &self.world,
+            documents: &self.documents,
+            main,
+        }
+    }
+
+    /// Resolve a URI to the id of a file within the project root.
+    fn id_for_uri(&self, uri: &Url) -> StrResult<FileId> {
+        let path = uri
+            .to_file_path()
+            .map_err(|_| eco_format!("unsupported document URI `{uri}`"))?;
+        self.world.id_for_path(&path)
+    }
+
+    /// The URI of a file.
+    fn uri_for_id(&self, id: FileId) -> Option<Url> {
+        if let Some(open) = self.documents.get(&id) {
+            return Some(open.uri.clone());
+        }
+        Url::from_file_path(self.world.path_for_id(id).ok()?).ok()
+    }
+}
+
+/// Deserialize the parameters of a request, handle it and produce a response.
+fn respond<P, R>(request: Request, f: impl FnOnce(P) -> StrResult<R>) -> Response
+where
+    P: DeserializeOwned,
+    R: Serialize,
+{
+    match params(request.params).and_then(f) {
+        Ok(result) => Response::new_ok(request.id, result),
+        Err(err) => {
+            Response::new_err(request.id, ErrorCode::InvalidParams as i32, err.into())
+        }
+    }
+}
+
+/// Deserialize the parameters of a message.
+fn params<P: DeserializeOwned>(params: serde_json::Value) -> StrResult<P> {
+    serde_json::from_value(params).map_err(|err| eco_format!("invalid params ({err})"))
+}
+
+/// Create a notification that publishes the diagnostics of a file.
+fn publish(uri: Url, diagnostics: Vec<lsp_types::Diagnostic>) -> Notification {
+    Notification::new(
+        PublishDiagnostics::METHOD.into(),
+        PublishDiagnosticsParams { uri, diagnostics, version: None },
+    )
+}
+
+/// A world whose open documents take precedence over the files on disk.
+struct LspWorld<'a> {
+    /// The underlying system world.
+    system: &'a SystemWorld,
+    /// The documents that are open in the client.
+    documents: &'a HashMap<FileId, OpenDocument>,
+    /// The open document that serves as the main file.
+    main: FileId,
+}
+
+impl World for LspWorld<'_> {
+    fn library(&self) -> &Prehashed<Library> {
+        self.system.library()
+    }
+
+    fn book(&self) -> &Prehashed<FontBook> {
+        self.system.book()
+    }
+
+    fn main(&self) -> Source {
+        self.source(self.main).unwrap()
+    }
+
+    fn source(&self, id: FileId) -> FileResult<Source> {
+        match self.documents.get(&id) {
+            Some(open) => Ok(open.source.clone()),
+            None => self.system.source(id),
+        }
+    }
+
+    fn file(&self, id: FileId) -> FileResult<Bytes> {
+        match self.documents.get(&id) {
+            Some(open) => Ok(open.source.text().as_bytes().to_vec().into()),
+            None => self.system.file(id),
+        }
+    }
+
+    fn font(&self, index: usize) -> Option<Font> {
+        self.system.font(index)
+    }
+
+    fn today(&self, offset: Option<i64>) -> Option<Datetime> {
+        self.system.today(offset)
+    }
+}
+
+/// Convert a diagnostic to its LSP counterpart.
+fn to_diagnostic(
+    world: &LspWorld,
+    source: &Source,
+    diagnostic: &SourceDiagnostic,
+) -> lsp_types::Diagnostic {
+    let range = match diagnostic.span.id() {
+        Some(_) => world.range(diagnostic.span).unwrap_or(0..0),
+        None => 0..0,
+    };
+
+    let mut message = diagnostic.message.to_string();
+    for hint in &diagnostic.hints {
+        message.push_str("\nhint: ");
+        message.push_str(hint);
+    }
+
+    lsp_types::Diagnostic {
+        range: to_lsp_range(source, range),
+        severity: Some(match diagnostic.severity {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+        }),
+        source: Some("typst".into()),
+        message,
+        ..lsp_types::Diagnostic::default()
+    }
+}
+
+/// Convert a document symbol to its LSP counterpart.
+fn to_symbol(
+    source: &Source,
+    symbol: typst_ide::DocumentSymbol,
+) -> lsp_types::DocumentSymbol {
+    #[allow(deprecated)]
+    lsp_types::DocumentSymbol {
+        name: symbol.name.into(),
+        detail: None,
+        kind: match symbol.kind {
+            SymbolKind::Heading => lsp_types::SymbolKind::NAMESPACE,
+            SymbolKind::Variable => lsp_types::SymbolKind::VARIABLE,
+            SymbolKind::Function => lsp_types::SymbolKind::FUNCTION,
+            SymbolKind::Label => lsp_types::SymbolKind::CONSTANT,
+        },
+        tags: None,
+        deprecated: None,
+        range: to_lsp_range(source, symbol.range),
+        selection_range: to_lsp_range(source, symbol.selection_range),
+        children: Some(
+            symbol
+                .children
+                .into_iter()
+                .map(|child| to_symbol(source, child))
+                .collect(),
+        ),
+    }
+}
+
+/// Convert a Typst snippet (`${}` or `${name}` placeholders) into an LSP
+/// snippet (`$1` or `${1:name}` tab stops).
+fn to_snippet(apply: &str) -> String {
+    let mut snippet = String::new();
+    let mut stops = 0;
+    let mut rest = apply;
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("${") {
+            if let Some((name, after)) = after.split_once('}') {
+                stops += 1;
+                if name.is_empty() {
+                    snippet.push_str(&eco_format!("${stops}"));
+                } else {
+                    snippet.push_str(&eco_format!("${{{stops}:{name}}}"));
+                }
+                rest = after;
+                continue;
+            }
+        }
+
+        if matches!(c, '$' | '}' | '\\') {
+            snippet.push('\\');
+        }
+        snippet.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    snippet
+}
+
+/// Convert an LSP position to a byte offset.
+///
+/// LSP positions count columns in UTF-16 code units. Positions beyond the end
+/// of a line or of the file are clamped.
+fn to_offset(source: &Source, position: Position) -> usize {
+    let line = position.line as usize;
+    let (Some(start), Some(range)) =
+        (source.line_to_byte(line), source.line_to_range(line))
+    else {
+        return source.len_bytes();
+    };
+
+    let end = start + source.text()[range].trim_end_matches(['\r', '\n']).len();
+    source
+        .byte_to_utf16(start)
+        .and_then(|utf16| source.utf16_to_byte(utf16 + position.character as usize))
+        .map_or(end, |offset| offset.min(end))
+}
+
+/// Convert a byte offset to an LSP position.
+fn to_position(source: &Source, offset: usize) -> Position {
+    let offset = offset.min(source.len_bytes());
+    let line = source.byte_to_line(offset).unwrap_or(0);
+    let column = source
+        .line_to_byte(line)
+        .and_then(|start| {
+            Some(source.byte_to_utf16(offset)? - source.byte_to_utf16(start)?)
+        })
+        .unwrap_or(0);
+    Position::new(line as u32, column as u32)
+}
+
+/// Convert an LSP range to a byte range.
+fn to_range(source: &Source, range: Range) -> std::ops::Range<usize> {
+    let start = to_offset(source, range.start);
+    let end = to_offset(source, range.end);
+    start..end.max(start)
+}
+
+/// Convert a byte range to an LSP range.
+fn to_lsp_range(source: &Source, range: std::ops::Range<usize>) -> Range {
+    Range::new(to_position(source, range.start), to_position(source, range.end))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use clap::Parser;
+    use lsp_types::notification::{Exit, Initialized};
+    use lsp_types::request::{Initialize, Shutdown};
+    use lsp_types::{
+        CompletionContext, InitializedParams, PartialResultParams,
+        TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
+        VersionedTextDocumentIdentifier, WorkDoneProgressParams,
+    };
+
+    use super::*;
+
+    /// The client side of an in-memory connection to a server.
+    struct Client {
+        connection: Connection,
+        next_id: i32,
+    }
+
+    impl Client {
+        /// Receive the next message from the server.
+        fn receive(&self) -> Message {
+            self.connection
+                .receiver
+                .recv_timeout(Duration::from_secs(30))
+                .unwrap()
+        }
+
+        /// Send a request and wait for its result.
+        fn request<R: lsp_types::request::Request>(
+            &mut self,
+            params: R::Params,
+        ) -> R::Result {
+            self.next_id += 1;
+            let request = Request::new(self.next_id.into(), R::METHOD.into(), params);
+            self.connection.sender.send(request.into()).unwrap();
+            let Message::Response(response) = self.receive() else {
+                panic!("expected a response");
+            };
+            assert_eq!(response.id, self.next_id.into());
+            assert!(response.error.is_none(), "{:?}", response.error);
+            serde_json::from_value(response.result.unwrap()).unwrap()
+        }
+
+        /// Send a notification.
+        fn notify<N: lsp_types::notification::Notification>(&self, params: N::Params) {
+            let notification = Notification::new(N::METHOD.into(), params);
+            self.connection.sender.send(notification.into()).unwrap();
+        }
+
+        /// Wait for published diagnostics.
+        fn diagnostics(&self) -> PublishDiagnosticsParams {
+            let Message::Notification(notification) = self.receive() else {
+                panic!("expected a notification");
+            };
+            assert_eq!(notification.method, PublishDiagnostics::METHOD);
+            serde_json::from_value(notification.params).unwrap()
+        }
+    }
+
+    /// A position in the document.
+    fn at(uri: &Url, line: u32, character: u32) -> TextDocumentPositionParams {
+        TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            position: Position::new(line, character),
+        }
+    }
+
+    #[test]
+    fn test_lsp_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let uri = Url::from_file_path(root.join("main.typ")).unwrap();
+        let fonts = concat!(env!("CARGO_MANIFEST_DIR"), "/../../assets/fonts");
+        let command = LspCommand::try_parse_from([
+            "lsp".as_ref(),
+            "--stdio".as_ref(),
+            "--font-path".as_ref(),
+            fonts.as_ref(),
+            "--root".as_ref(),
+            root.as_os_str(),
+        ])
+        .unwrap();
+
+        let (server, connection) = Connection::memory();
+        let thread = std::thread::spawn(move || run(&command, &server));
+        let mut client = Client { connection, next_id: 0 };
+
+        let result = client.request::<Initialize>(InitializeParams::default());
+        assert_eq!(result.capabilities, capabilities());
+        client.notify::<Initialized>(InitializedParams {});
+
+        // Opening a document publishes its diagnostics.
+        client.notify::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "typst".into(),
+                version: 1,
+                text:
+                    "#let greet(name) = [Hello #name]\n= Intro\n#greet(\"World\") #nope\n"
+                        .into(),
+            },
+        });
+        let published = client.diagnostics();
+        assert_eq!(published.uri, uri);
+        assert_eq!(published.diagnostics.len(), 1);
+        assert_eq!(published.diagnostics[0].message, "unknown variable: nope");
+        assert_eq!(
+            published.diagnostics[0].range,
+            Range::new(Position::new(2, 17), Position::new(2, 21))
+        );
+        assert_eq!(published.diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+
+        // Removing the unknown variable clears them.
+        client.notify::<DidChangeTextDocument>(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: uri.clone(),
+                version: 2,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: Some(Range::new(Position::new(2, 15), Position::new(2, 21))),
+                range_length: None,
+                text: String::new(),
+            }],
+        });
+        let published = client.diagnostics();
+        assert_eq!(published.uri, uri);
+        assert!(published.diagnostics.is_empty());
+
+        let completions = client.request::<Completion>(CompletionParams {
+            text_document_position: at(&uri, 2, 4),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: Some(CompletionContext {
+                trigger_kind: CompletionTriggerKind::INVOKED,
+                trigger_character: None,
+            }),
+        });
+        let Some(CompletionResponse::Array(items)) = completions else {
+            panic!("expected completions");
+        };
+        let greet = items.iter().find(|item| item.label == "greet").unwrap();
+        assert_eq!(greet.kind, Some(CompletionItemKind::CONSTANT));
+        assert_eq!(
+            greet.text_edit,
+            Some(CompletionTextEdit::Edit(TextEdit {
+                range: Range::new(Position::new(2, 1), Position::new(2, 4)),
+                new_text: "greet".into(),
+            }))
+        );
+        let hover = client.request::<HoverRequest>(HoverParams {
+            text_document_position_params: at(&uri, 0, 28),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        });
+        assert_eq!(
+            hover.map(|hover| hover.contents),
+            Some(HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: "```typc\n\"World\"\n```".into(),
+            }))
+        );
+
+        let definition = client.request::<GotoDefinition>(GotoDefinitionParams {
+            text_document_position_params: at(&uri, 2, 3),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        });
+        assert_eq!(
+            definition,
+            Some(GotoDefinitionResponse::Scalar(Location {
+                uri: uri.clone(),
+                range: Range::new(Position::new(0, 5), Position::new(0, 10)),
+            }))
+        );
+
+        let symbols = client.request::<DocumentSymbolRequest>(DocumentSymbolParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        });
+        let Some(DocumentSymbolResponse::Nested(symbols)) = symbols else {
+            panic!("expected nested symbols");
+        };
+        let symbols: Vec<_> = symbols
+            .iter()
+            .map(|symbol| (symbol.name.as_str(), symbol.kind, symbol.selection_range))
+            .collect();
+        assert_eq!(
+            symbols,
+            [
+                (
+                    "greet",
+                    lsp_types::SymbolKind::FUNCTION,
+                    Range::new(Position::new(0, 5), Position::new(0, 10)),
+                ),
+                (
+                    "Intro",
+                    lsp_types::SymbolKind::NAMESPACE,
+                    Range::new(Position::new(1, 0), Position::new(1, 7)),
+                ),
+            ]
+        );
+
+        client.request::<Shutdown>(());
+        client.notify::<Exit>(());
+        thread.join().unwrap().unwrap();
+    }
+}
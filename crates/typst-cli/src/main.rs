@@ -2,8 +2,11 @@ mod args;
 mod compile;
 mod download;
 mod fonts;
+mod lsp;
 mod package;
+mod preview;
 mod query;
+mod serve;
 mod tracing;
 #[cfg(feature = "self-update")]
 mod update;
@@ -76,6 +79,8 @@ pub fn origin_main() -> ExitCode {
         Command::Compile(command) => crate::compile::compile(command.clone()),
         Command::Watch(command) => crate::watch::watch(command.clone()),
         Command::Query(command) => crate::query::query(command),
+        Command::Serve(command) => crate::serve::serve(command),
+        Command::Lsp(command) => crate::lsp::lsp(command),
         Command::Fonts(command) => crate::fonts::fonts(command),
         Command::Update(command) => crate::update::update(command),
     };
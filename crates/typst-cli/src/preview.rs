@@ -0,0 +1,474 @@
+//! A live-reloading preview of the document in the browser.
+//!
+//! The preview is served over HTTP on the local machine. The browser
+//! subscribes to updates through server-sent events and only fetches the pages
+//! that changed since it last saw them. Only the pages selected for export are
+//! shown. Clicks on a page are resolved to the source location they stem from,
+//! which is shown in the status bar.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::time::Duration;
+
+use ecow::eco_format;
+use parking_lot::{Condvar, Mutex};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde_json::json;
+use typst::diag::StrResult;
+use typst::layout::{Abs, Frame, PageRanges, Point};
+use typst::model::Document;
+use typst::visualize::Color;
+use typst::World;
+use typst_ide::Jump;
+
+use crate::args::OutputFormat;
+use crate::compile::ExportCache;
+use crate::watch::Event;
+use crate::world::SystemWorld;
+
+/// How long to wait for the watch loop to resolve a click.
+const JUMP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often to send a keep-alive comment on an idle event stream, which also
+/// detects closed connections.
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// A preview of the document that is served over HTTP.
+pub struct Preview {
+    /// The address the server listens on.
+    addr: SocketAddr,
+    /// Whether pages are rendered as PNG instead of SVG.
+    png: bool,
+    /// The pixels per inch to render PNG pages with.
+    ppi: f32,
+    /// The ranges of pages to show, if only some of them should be.
+    ranges: Option<PageRanges>,
+    /// The hashes of the rendered pages' frames.
+    cache: ExportCache,
+    /// The rendered pages and the document they stem from.
+    state: Mutex<State>,
+    /// Notified whenever the state changes.
+    changed: Condvar,
+}
+
+/// The current state of the preview.
+#[derive(Default)]
+struct State {
+    /// Increased with every update.
+    version: u64,
+    /// Whether the last compilation failed.
+    failed: bool,
+    /// The rendered pages.
+    pages: Vec<Page>,
+    /// The index of each rendered page in the document.
+    indices: Vec<usize>,
+    /// The document the pages were rendered from.
+    document: Option<Document>,
+}
+
+/// A rendered page.
+#[derive(Clone)]
+struct Page {
+    /// The version of the state in which the page last changed.
+    version: u64,
+    /// The page's width in points.
+    width: f64,
+    /// The page's height in points.
+    height: f64,
+    /// The encoded image.
+    data: Arc<[u8]>,
+}
+
+/// A click in the preview that the watch loop should resolve, as it owns the
+/// world.
+pub struct JumpRequest {
+    /// The index of the clicked page in the preview.
+    page: usize,
+    /// The clicked point on the page.
+    click: Point,
+    /// Receives the JSON description of the jump target, if any.
+    reply: Sender<Option<serde_json::Value>>,
+}
+
+impl Preview {
+    /// Start serving a preview on a local port.
+    ///
+    /// Pages are rendered as PNG when exporting to a raster format and as SVG
+    /// otherwise. Only the pages within `ranges` are shown.
+    /// Clicks are sent to the watch loop through `events`.
+    pub fn serve(
+        port: u16,
+        format: OutputFormat,
+        ppi: f32,
+        ranges: Option<PageRanges>,
+        events: Sender<Event>,
+    ) -> StrResult<Arc<Self>> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .map_err(|err| eco_format!("failed to start preview server ({err})"))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|err| eco_format!("failed to start preview server ({err})"))?;
+
+        let preview = Arc::new(Self {
+            addr,
+            png: matches!(
+                format,
+                OutputFormat::Png | OutputFormat::Jpeg | OutputFormat::Webp
+            ),
+            ppi,
+            ranges,
+            cache: ExportCache::new(),
+            state: Mutex::new(State::default()),
+            changed: Condvar::new(),
+        });
+
+        let server = Arc::clone(&preview);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let preview = Arc::clone(&server);
+                let events = events.clone();
+                std::thread::spawn(move || {
+                    if let Err(err) = preview.handle(stream, &events) {
+                        tracing::info!("Preview connection closed ({err})");
+                    }
+                });
+            }
+        });
+
+        tracing::info!("Serving preview at http://{addr}");
+        Ok(preview)
+    }
+
+    /// The URL at which the preview is served.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Show a newly compiled document, rendering only the pages that changed.
+    pub fn update(&self, document: &Document) {
+        let version = self.state.lock().version + 1;
+        let (indices, frames): (Vec<usize>, Vec<&Frame>) = document
+            .pages
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| {
+                self.ranges.as_ref().map_or(true, |r| r.includes_page_index(i))
+            })
+            .unzip();
+
+        let rendered: Vec<Option<Page>> = frames
+            .iter()
+            .enumerate()
+            .collect::<Vec<_>>()
+            .par_iter()
+            .map(|&(i, frame)| {
+                (!self.cache.is_cached(i, frame)).then(|| self.render(frame, version))
+            })
+            .collect();
+
+        let mut state = self.state.lock();
+        state.pages.truncate(rendered.len());
+        for (i, page) in rendered.into_iter().enumerate() {
+            match page {
+                Some(page) if i < state.pages.len() => state.pages[i] = page,
+                Some(page) => state.pages.push(page),
+                None => {}
+            }
+        }
+
+        // Pages that were removed must be rendered again if they reappear.
+        self.cache.cache.write().truncate(frames.len());
+
+        state.indices = indices;
+        state.version = version;
+        state.failed = false;
+        state.document = Some(document.clone());
+        self.changed.notify_all();
+    }
+
+    /// Indicate that the last compilation failed, keeping the previous pages.
+    pub fn fail(&self) {
+        let mut state = self.state.lock();
+        state.version += 1;
+        state.failed = true;
+        self.changed.notify_all();
+    }
+
+    /// Resolve a click to the place it stems from.
+    pub fn jump(&self, world: &SystemWorld, request: JumpRequest) {
+        let state = self.state.lock();
+        let target = state.document.as_ref().and_then(|document| {
+            let index = *state.indices.get(request.page)?;
+            let frame = document.pages.get(index)?;
+            let jump = typst_ide::jump_from_click(world, document, frame, request.click)?;
+            Some(match jump {
+                Jump::Source(id, offset) => {
+                    let source = world.source(id).ok()?;
+                    let path = world.path_for_id(id).ok()?;
+                    let line = source.byte_to_line(offset)?;
+                    let column = source.byte_to_column(offset)?;
+                    tracing::info!(
+                        "Jumping to {}:{}:{}",
+                        path.display(),
+                        line + 1,
+                        column + 1
+                    );
+                    json!({
+                        "path": path,
+                        "line": line + 1,
+                        "column": column + 1,
+                    })
+                }
+                Jump::Url(url) => json!({ "url": url }),
+                Jump::Position(position) => json!({
+                    "page": state
+                        .indices
+                        .iter()
+                        .position(|&i| i == position.page.get() - 1)?,
+                    "x": position.point.x.to_pt(),
+                    "y": position.point.y.to_pt(),
+                }),
+            })
+        });
+        request.reply.send(target).ok();
+    }
+
+    /// Render a page.
+    fn render(&self, frame: &Frame, version: u64) -> Page {
+        let data: Arc<[u8]> = if self.png {
+            let pixmap = typst_render::render(frame, self.ppi / 72.0, Color::WHITE);
+            pixmap.encode_png().unwrap_or_default().into()
+        } else {
+            typst_svg::svg(frame).into_bytes().into()
+        };
+
+        Page {
+            version,
+            width: frame.width().to_pt(),
+            height: frame.height().to_pt(),
+            data,
+        }
+    }
+
+    /// Answer an HTTP request.
+    fn handle(&self, stream: TcpStream, events: &Sender<Event>) -> io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+
+        // Skip the headers, we don't need them.
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 2 {
+            header.clear();
+        }
+
+        let mut parts = line.split_whitespace();
+        let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+            return respond(stream, "400 Bad Request", "text/plain", b"bad request");
+        };
+
+        if method != "GET" {
+            return respond(stream, "405 Method Not Allowed", "text/plain", b"");
+        }
+
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        match path {
+            "/" => {
+                respond(stream, "200 OK", "text/html; charset=utf-8", INDEX.as_bytes())
+            }
+            "/events" => self.stream_events(stream),
+            "/jump" => self.handle_jump(stream, query, events),
+            _ => {
+                let page = path
+                    .strip_prefix("/page/")
+                    .and_then(|index| index.parse::<usize>().ok())
+                    .and_then(|index| self.state.lock().pages.get(index).cloned());
+                match page {
+                    Some(page) => respond(
+                        stream,
+                        "200 OK",
+                        if self.png { "image/png" } else { "image/svg+xml" },
+                        &page.data,
+                    ),
+                    None => respond(stream, "404 Not Found", "text/plain", b"not found"),
+                }
+            }
+        }
+    }
+
+    /// Send an event to the browser whenever the preview changes.
+    fn stream_events(&self, mut stream: TcpStream) -> io::Result<()> {
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\n\
+             Content-Type: text/event-stream\r\n\
+             Cache-Control: no-cache\r\n\
+             Connection: keep-alive\r\n\r\n"
+        )?;
+        stream.flush()?;
+
+        let mut seen = None;
+        loop {
+            let mut state = self.state.lock();
+            if seen == Some(state.version) {
+                self.changed.wait_for(&mut state, KEEP_ALIVE);
+            }
+
+            if seen == Some(state.version) {
+                drop(state);
+                stream.write_all(b": keep-alive\n\n")?;
+            } else {
+                seen = Some(state.version);
+                let pages: Vec<_> = state
+                    .pages
+                    .iter()
+                    .map(|page| {
+                        json!({
+                            "version": page.version,
+                            "width": page.width,
+                            "height": page.height,
+                        })
+                    })
+                    .collect();
+                let event = json!({ "failed": state.failed, "pages": pages });
+                drop(state);
+                write!(stream, "data: {event}\n\n")?;
+            }
+            stream.flush()?;
+        }
+    }
+
+    /// Resolve a click through the watch loop.
+    fn handle_jump(
+        &self,
+        stream: TcpStream,
+        query: &str,
+        events: &Sender<Event>,
+    ) -> io::Result<()> {
+        let param = |name: &str| {
+            query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|&(key, _)| key == name)
+                .and_then(|(_, value)| value.parse::<f64>().ok())
+        };
+
+        let (Some(page), Some(x), Some(y)) = (param("page"), param("x"), param("y"))
+        else {
+            return respond(stream, "400 Bad Request", "text/plain", b"bad request");
+        };
+
+        let (reply, receiver) = mpsc::channel();
+        let request = JumpRequest {
+            page: page as usize,
+            click: Point::new(Abs::pt(x), Abs::pt(y)),
+            reply,
+        };
+
+        match events
+            .send(Event::Jump(request))
+            .ok()
+            .and_then(|_| receiver.recv_timeout(JUMP_TIMEOUT).ok())
+            .flatten()
+        {
+            Some(target) => respond(
+                stream,
+                "200 OK",
+                "application/json",
+                target.to_string().as_bytes(),
+            ),
+            None => respond(stream, "204 No Content", "text/plain", b""),
+        }
+    }
+}
+
+/// Write a complete HTTP response.
+fn respond(
+    mut stream: TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: {content_type}\r\n\
+         Content-Length: {}\r\n\
+         Cache-Control: no-cache\r\n\
+         Connection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
+
+/// The page that shows the preview.
+const INDEX: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Typst Preview</title>
+<style>
+  body { margin: 0; background: #e5e5e5; font-family: sans-serif; }
+  #pages { display: flex; flex-direction: column; align-items: center; gap: 16px; padding: 16px 16px 48px; }
+  #pages img { max-width: 100%; background: white; box-shadow: 0 1px 4px rgba(0, 0, 0, 0.3); cursor: pointer; }
+  #status { position: fixed; left: 0; right: 0; bottom: 0; padding: 4px 8px; background: #333; color: white; font-size: 13px; }
+  #status.failed { background: #a11; }
+</style>
+</head>
+<body>
+<div id="pages"></div>
+<div id="status">connecting ...</div>
+<script>
+const pages = document.getElementById("pages");
+const status = document.getElementById("status");
+
+function setStatus(text, failed) {
+  status.textContent = text;
+  status.className = failed ? "failed" : "";
+}
+
+const events = new EventSource("/events");
+events.onmessage = (message) => {
+  const update = JSON.parse(message.data);
+  update.pages.forEach((page, i) => {
+    let img = pages.children[i];
+    if (!img) {
+      img = document.createElement("img");
+      img.addEventListener("click", (event) => jump(i, event));
+      pages.appendChild(img);
+    }
+    img.dataset.width = page.width;
+    img.dataset.height = page.height;
+    img.style.width = page.width + "pt";
+    const src = `/page/${i}?v=${page.version}`;
+    if (img.getAttribute("src") !== src) img.setAttribute("src", src);
+  });
+  while (pages.children.length > update.pages.length) pages.lastChild.remove();
+  setStatus(update.failed ? "compiled with errors" : "compiled successfully", update.failed);
+};
+events.onerror = () => setStatus("disconnected", true);
+
+async function jump(i, event) {
+  const img = event.currentTarget;
+  const x = event.offsetX / img.clientWidth * img.dataset.width;
+  const y = event.offsetY / img.clientHeight * img.dataset.height;
+  const response = await fetch(`/jump?page=${i}&x=${x}&y=${y}`);
+  if (response.status !== 200) return;
+  const target = await response.json();
+  if (target.url !== undefined) {
+    window.open(target.url, "_blank");
+  } else if (target.page !== undefined) {
+    const page = pages.children[target.page];
+    const top = page.offsetTop + target.y / page.dataset.height * page.clientHeight;
+    window.scrollTo({ top: top - 16, behavior: "smooth" });
+  } else {
+    setStatus(`${target.path}:${target.line}:${target.column}`, false);
+  }
+}
+</script>
+</body>
+</html>
+"#;
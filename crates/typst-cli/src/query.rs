@@ -3,7 +3,7 @@ use ecow::{eco_format, EcoString};
 use serde::Serialize;
 use typst::diag::{bail, StrResult};
 use typst::eval::{eval_string, EvalMode, Tracer};
-use typst::foundations::{Content, IntoValue, LocatableSelector, Scope};
+use typst::foundations::{Content, IntoValue, LocatableSelector, Scope, Value};
 use typst::model::Document;
 use typst::syntax::Span;
 use typst::World;
@@ -53,7 +53,7 @@ pub fn query(command: &QueryCommand) -> StrResult<()> {
 }
 
 /// Retrieve the matches for the selector.
-fn retrieve(
+pub fn retrieve(
     world: &dyn World,
     command: &QueryCommand,
     document: &Document,
@@ -85,6 +85,15 @@ fn retrieve(
 
 /// Format the query result in the output format.
 fn format(elements: Vec<Content>, command: &QueryCommand) -> StrResult<String> {
+    let data = select(elements, command)?;
+    serialize(&data, command.format)
+}
+
+/// Extract the requested field from the matched elements.
+///
+/// Yields a single value if the command expects exactly one element and an
+/// array of values otherwise.
+pub fn select(elements: Vec<Content>, command: &QueryCommand) -> StrResult<Value> {
     if command.one && elements.len() != 1 {
         bail!("expected exactly one element, found {}", elements.len());
     }
@@ -98,12 +107,12 @@ fn format(elements: Vec<Content>, command: &QueryCommand) -> StrResult<String> {
         .collect();
 
     if command.one {
-        let Some(value) = mapped.first() else {
+        let Some(value) = mapped.into_iter().next() else {
             bail!("no such field found for element");
         };
-        serialize(value, command.format)
+        Ok(value)
     } else {
-        serialize(&mapped, command.format)
+        Ok(mapped.into_value())
     }
 }
 
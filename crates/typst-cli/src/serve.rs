@@ -0,0 +1,609 @@
+//! A long-running compiler that answers JSON-RPC 2.0 requests.
+//!
+//! Requests and responses are exchanged as single-line JSON messages, one per
+//! line, on stdin and stdout. Each request must declare `"jsonrpc": "2.0"`.
+//! The world and the compiler's caches are kept alive between requests so that
+//! repeated compilations of the same project only pay for what changed. The supported methods are:
+//!
+//! - `compile`: Compiles the input file and writes the result to `output`.
+//!   Optionally takes a `format`, `pages`, `ppi`, `background`,
+//!   `jpeg-quality`, `pdf-standard`, `merge`, `gap`, and `merge-columns` with
+//!   the same meaning as the respective `compile` arguments.
+//! - `query`: Compiles the input file and returns the elements matching the
+//!   `selector`, optionally reduced to a `field` and to exactly `one` element.
+//! - `update-file`: Replaces the contents of the file at `path` with the
+//!   given `content` in memory. A `content` of `null` reverts to the file on
+//!   disk.
+//! - `set-inputs`: Replaces the string pairs visible through `sys.inputs`
+//!   with the given `inputs`.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use ecow::{eco_format, EcoString};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use typst::diag::{At, Severity, SourceDiagnostic, StrResult};
+use typst::eval::Tracer;
+use typst::foundations::{Dict, IntoValue};
+use typst::model::Document;
+use typst::syntax::Span;
+use typst::{World, WorldExt};
+
+use crate::args::{
+    BackgroundArgument, CompileCommand, LengthArgument, OutputFormat, PageRangeArgument,
+    PdfStandard, QueryCommand, SerializationFormat, ServeCommand,
+};
+use crate::compile::export;
+use crate::query::{retrieve, select};
+use crate::world::SystemWorld;
+
+/// Invalid JSON was received.
+const PARSE_ERROR: i64 = -32700;
+/// The JSON sent is not a valid request object.
+const INVALID_REQUEST: i64 = -32600;
+/// The method does not exist.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// The method parameters are invalid.
+const INVALID_PARAMS: i64 = -32602;
+/// The request was valid, but could not be carried out.
+const REQUEST_FAILED: i64 = -32000;
+
+/// Execute a serve command.
+pub fn serve(command: &ServeCommand) -> StrResult<()> {
+    let mut world = SystemWorld::new(&command.common)?;
+    tracing::info!("Starting server");
+    run(&mut world, command, io::stdin().lock(), io::stdout().lock())
+}
+
+/// Answer the requests read line by line from `input` on `output`.
+fn run(
+    world: &mut SystemWorld,
+    command: &ServeCommand,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> StrResult<()> {
+    for line in input.lines() {
+        let line = line.map_err(|err| eco_format!("failed to read request ({err})"))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(response) = respond(world, command, &line) {
+            serde_json::to_writer(&mut output, &response)
+                .map_err(|err| eco_format!("failed to write response ({err})"))?;
+            writeln!(output)
+                .and_then(|_| output.flush())
+                .map_err(|err| eco_format!("failed to write response ({err})"))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A JSON-RPC request or notification.
+#[derive(Debug, Deserialize)]
+struct Request {
+    /// The protocol version, which must be `2.0`.
+    jsonrpc: String,
+    /// The request's id. Notifications don't have one and get no response.
+    #[serde(default)]
+    id: Option<JsonValue>,
+    /// The method to invoke.
+    method: String,
+    /// The method's parameters.
+    #[serde(default)]
+    params: JsonValue,
+}
+
+/// A JSON-RPC response.
+#[derive(Debug, Serialize)]
+struct Response {
+    jsonrpc: &'static str,
+    id: JsonValue,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<JsonValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Error>,
+}
+
+/// A JSON-RPC error.
+#[derive(Debug, Serialize)]
+struct Error {
+    code: i64,
+    message: EcoString,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<JsonValue>,
+}
+
+impl Error {
+    /// Create an error without additional data.
+    fn new(code: i64, message: impl Into<EcoString>) -> Self {
+        Self { code, message: message.into(), data: None }
+    }
+}
+
+/// Handle a single message and produce the response, if any.
+fn respond(
+    world: &mut SystemWorld,
+    command: &ServeCommand,
+    message: &str,
+) -> Option<Response> {
+    let request = match serde_json::from_str::<JsonValue>(message) {
+        Ok(value) => value,
+        Err(err) => return Some(Response::error(JsonValue::Null, parse_error(err))),
+    };
+
+    let request = match serde_json::from_value::<Request>(request) {
+        Ok(request) => request,
+        Err(err) => {
+            let err = Error::new(INVALID_REQUEST, eco_format!("invalid request ({err})"));
+            return Some(Response::error(JsonValue::Null, err));
+        }
+    };
+
+    if request.jsonrpc != "2.0" {
+        let err = Error::new(
+            INVALID_REQUEST,
+            eco_format!("unsupported JSON-RPC version `{}`", request.jsonrpc),
+        );
+        return Some(Response::error(request.id.unwrap_or(JsonValue::Null), err));
+    }
+
+    let result = match request.method.as_str() {
+        "compile" => params(request.params).and_then(|p| compile(world, command, p)),
+        "query" => params(request.params).and_then(|p| query(world, command, p)),
+        "update-file" => params(request.params).and_then(|p| update_file(world, p)),
+        "set-inputs" => params(request.params).and_then(|p| set_inputs(world, p)),
+        method => {
+            Err(Error::new(METHOD_NOT_FOUND, eco_format!("unknown method `{method}`")))
+        }
+    };
+
+    let id = request.id?;
+    Some(match result {
+        Ok(result) => Response {
+            jsonrpc: "2.0",
+            id,
+            result: Some(result),
+            error: None,
+        },
+        Err(err) => Response::error(id, err),
+    })
+}
+
+impl Response {
+    /// Create an error response.
+    fn error(id: JsonValue, error: Error) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// Parameters of the `compile` method.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct CompileParams {
+    output: PathBuf,
+    format: Option<String>,
+    pages: Option<String>,
+    ppi: Option<f32>,
+    background: Option<String>,
+    jpeg_quality: Option<u8>,
+    pdf_standard: Option<String>,
+    #[serde(default)]
+    merge: bool,
+    gap: Option<String>,
+    merge_columns: Option<NonZeroUsize>,
+}
+
+/// Compile the document and export it.
+fn compile(
+    world: &mut SystemWorld,
+    command: &ServeCommand,
+    params: CompileParams,
+) -> Result<JsonValue, Error> {
+    let format = params
+        .format
+        .map(|format| OutputFormat::from_str(&format, true))
+        .transpose()
+        .map_err(|err| {
+            Error::new(INVALID_PARAMS, eco_format!("invalid format ({err})"))
+        })?;
+
+    let pdf_standard = params
+        .pdf_standard
+        .map(|standard| PdfStandard::from_str(&standard, true))
+        .transpose()
+        .map_err(|err| {
+            Error::new(INVALID_PARAMS, eco_format!("invalid PDF standard ({err})"))
+        })?;
+
+    let pages = params
+        .pages
+        .map(|pages| pages.split(',').map(PageRangeArgument::from_str).collect())
+        .transpose()
+        .map_err(|err| {
+            Error::new(INVALID_PARAMS, eco_format!("invalid pages ({err})"))
+        })?;
+
+    let background = params
+        .background
+        .map(|background| BackgroundArgument::from_str(&background))
+        .transpose()
+        .map_err(|err| {
+            Error::new(INVALID_PARAMS, eco_format!("invalid background ({err})"))
+        })?;
+
+    if matches!(params.jpeg_quality, Some(0 | 101..)) {
+        return Err(Error::new(
+            INVALID_PARAMS,
+            "invalid JPEG quality (must be between 1 and 100)",
+        ));
+    }
+
+    let gap = params
+        .gap
+        .map(|gap| LengthArgument::from_str(&gap))
+        .transpose()
+        .map_err(|err| Error::new(INVALID_PARAMS, eco_format!("invalid gap ({err})")))?;
+
+    let command = CompileCommand {
+        common: command.common.clone(),
+        output: Some(params.output),
+        format,
+        pages,
+        pdf_standard,
+        ppi: params.ppi.unwrap_or(144.0),
+        background,
+        jpeg_quality: params.jpeg_quality,
+        merge: params.merge,
+        gap,
+        merge_columns: params.merge_columns,
+        ..CompileCommand::default()
+    };
+
+    // Ensure that the format can be determined before compiling.
+    command
+        .output_format()
+        .map_err(|err| Error::new(INVALID_PARAMS, err))?;
+
+    let (document, warnings) = compile_document(world)?;
+    let result = export(world, &document, &command, false);
+    comemo::evict(10);
+
+    if let Err(errors) = result {
+        return Err(compilation_failed(world, &errors, &warnings));
+    }
+
+    Ok(json!({ "warnings": diagnostics(world, &warnings) }))
+}
+
+/// Parameters of the `query` method.
+#[derive(Debug, Deserialize)]
+struct QueryParams {
+    selector: String,
+    field: Option<String>,
+    #[serde(default)]
+    one: bool,
+}
+
+/// Compile the document and query it for elements.
+fn query(
+    world: &mut SystemWorld,
+    command: &ServeCommand,
+    params: QueryParams,
+) -> Result<JsonValue, Error> {
+    let command = QueryCommand {
+        common: command.common.clone(),
+        selector: params.selector,
+        field: params.field,
+        one: params.one,
+        format: SerializationFormat::Json,
+    };
+
+    let (document, warnings) = compile_document(world)?;
+    comemo::evict(10);
+
+    let elements = retrieve(world, &command, &document)
+        .map_err(|err| Error::new(INVALID_PARAMS, err))?;
+    let value =
+        select(elements, &command).map_err(|err| Error::new(REQUEST_FAILED, err))?;
+    let result = serde_json::to_value(value)
+        .map_err(|err| Error::new(REQUEST_FAILED, eco_format!("{err}")))?;
+
+    Ok(json!({ "result": result, "warnings": diagnostics(world, &warnings) }))
+}
+
+/// Parameters of the `update-file` method.
+#[derive(Debug, Deserialize)]
+struct UpdateFileParams {
+    path: PathBuf,
+    content: Option<String>,
+}
+
+/// Replace the contents of a file in memory.
+fn update_file(
+    world: &mut SystemWorld,
+    params: UpdateFileParams,
+) -> Result<JsonValue, Error> {
+    let id = world
+        .id_for_path(&params.path)
+        .map_err(|err| Error::new(INVALID_PARAMS, err))?;
+    world.set_overlay(id, params.content.map(String::into_bytes));
+    Ok(JsonValue::Null)
+}
+
+/// Parameters of the `set-inputs` method.
+#[derive(Debug, Deserialize)]
+struct SetInputsParams {
+    inputs: BTreeMap<String, String>,
+}
+
+/// Replace the inputs visible through `sys.inputs`.
+fn set_inputs(
+    world: &mut SystemWorld,
+    params: SetInputsParams,
+) -> Result<JsonValue, Error> {
+    let inputs: Dict = params
+        .inputs
+        .iter()
+        .map(|(k, v)| (k.as_str().into(), v.as_str().into_value()))
+        .collect();
+    world.set_inputs(inputs);
+    Ok(JsonValue::Null)
+}
+
+/// Compile the document, returning it along with the warnings.
+fn compile_document(
+    world: &mut SystemWorld,
+) -> Result<(Document, Vec<SourceDiagnostic>), Error> {
+    world.reset();
+
+    // Check if main file can be read and opened.
+    if let Err(errors) = world.source(world.main()).at(Span::detached()) {
+        return Err(compilation_failed(world, &errors, &[]));
+    }
+
+    let mut tracer = Tracer::new();
+    let result = typst::compile(world, &mut tracer);
+    let warnings = tracer.warnings();
+    match result {
+        Ok(document) => Ok((document, warnings.to_vec())),
+        Err(errors) => Err(compilation_failed(world, &errors, &warnings)),
+    }
+}
+
+/// Deserialize a method's parameters.
+fn params<T: DeserializeOwned>(params: JsonValue) -> Result<T, Error> {
+    serde_json::from_value(params)
+        .map_err(|err| Error::new(INVALID_PARAMS, eco_format!("invalid params ({err})")))
+}
+
+/// Create the error for a message that is not valid JSON.
+fn parse_error(err: serde_json::Error) -> Error {
+    Error::new(PARSE_ERROR, eco_format!("failed to parse request ({err})"))
+}
+
+/// Create the error for a failed compilation.
+fn compilation_failed(
+    world: &SystemWorld,
+    errors: &[SourceDiagnostic],
+    warnings: &[SourceDiagnostic],
+) -> Error {
+    let all: Vec<_> = errors.iter().chain(warnings).cloned().collect();
+    Error {
+        code: REQUEST_FAILED,
+        message: "compilation failed".into(),
+        data: Some(json!({ "diagnostics": diagnostics(world, &all) })),
+    }
+}
+
+/// Convert diagnostics into JSON.
+///
+/// Each diagnostic has a `severity`, a `message`, `hints`, and, if it is
+/// attached to a file, a `path` and a `range` with zero-based lines and
+/// columns.
+fn diagnostics(world: &SystemWorld, diagnostics: &[SourceDiagnostic]) -> JsonValue {
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let mut value = json!({
+                "severity": match diagnostic.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                },
+                "message": diagnostic.message,
+                "hints": diagnostic.hints,
+            });
+
+            if let Some((path, range)) = location(world, diagnostic.span) {
+                value["path"] = path.into();
+                value["range"] = range;
+            }
+
+            value
+        })
+        .collect()
+}
+
+/// The path of the file a span points into and its range within the file.
+fn location(world: &SystemWorld, span: Span) -> Option<(String, JsonValue)> {
+    let id = span.id()?;
+    let source = world.source(id).ok()?;
+    let range = world.range(span)?;
+    let position = |offset| {
+        Some(json!({
+            "line": source.byte_to_line(offset)?,
+            "column": source.byte_to_column(offset)?,
+        }))
+    };
+    let range = json!({ "start": position(range.start)?, "end": position(range.end)? });
+    let path = codespan_reporting::files::Files::name(world, id).ok()?;
+    Some((path, range))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    /// Feed the given lines to a server and collect its responses.
+    fn script(dir: &std::path::Path, lines: &[String]) -> Vec<JsonValue> {
+        let fonts = concat!(env!("CARGO_MANIFEST_DIR"), "/../../assets/fonts");
+        let input = dir.join("main.typ");
+        let command = ServeCommand::try_parse_from([
+            "serve".as_ref(),
+            "--stdio".as_ref(),
+            "--font-path".as_ref(),
+            fonts.as_ref(),
+            input.as_os_str(),
+        ])
+        .unwrap();
+
+        let mut world = SystemWorld::new(&command.common).unwrap();
+        let input = lines.join("\n");
+        let mut output = vec![];
+        run(&mut world, &command, input.as_bytes(), &mut output).unwrap();
+
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_serve_script() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("main.typ");
+        let output = dir.path().join("out.pdf");
+        std::fs::write(&main, "#metadata(sys.inputs.at(\"name\", default: none)) <name>")
+            .unwrap();
+
+        let query = |id: i64| {
+            json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "query",
+                "params": { "selector": "<name>", "field": "value", "one": true },
+            })
+        };
+
+        let messages = [
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "compile",
+                "params": { "output": output },
+            }),
+            query(2),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 3,
+                "method": "set-inputs",
+                "params": { "inputs": { "name": "Typst" } },
+            }),
+            query(4),
+            json!({
+                "jsonrpc": "2.0",
+                "method": "update-file",
+                "params": { "path": main, "content": "#metadata(1) <name>" },
+            }),
+            query(5),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 6,
+                "method": "update-file",
+                "params": { "path": main, "content": "#metadata(1) <name> #nope" },
+            }),
+            query(7),
+        ];
+        let lines: Vec<_> = messages.iter().map(JsonValue::to_string).collect();
+        let responses = script(dir.path(), &lines);
+
+        assert_eq!(responses.len(), 7);
+        assert_eq!(
+            responses[0],
+            json!({ "jsonrpc": "2.0", "id": 1, "result": { "warnings": [] } })
+        );
+        assert!(std::fs::read(&output).unwrap().starts_with(b"%PDF-"));
+
+        let result = |i: usize| responses[i]["result"]["result"].clone();
+        assert_eq!(result(1), JsonValue::Null);
+        assert_eq!(responses[2], json!({ "jsonrpc": "2.0", "id": 3, "result": null }));
+        assert_eq!(result(3), "Typst");
+        assert_eq!(result(4), 1);
+        assert_eq!(responses[5], json!({ "jsonrpc": "2.0", "id": 6, "result": null }));
+
+        let error = &responses[6]["error"];
+        assert_eq!(responses[6]["id"], 7);
+        assert_eq!(error["code"], REQUEST_FAILED);
+        assert_eq!(error["message"], "compilation failed");
+        let diagnostic = &error["data"]["diagnostics"][0];
+        assert_eq!(diagnostic["severity"], "error");
+        assert_eq!(diagnostic["message"], "unknown variable: nope");
+        assert!(diagnostic["path"].as_str().unwrap().ends_with("main.typ"));
+        assert_eq!(diagnostic["range"]["start"], json!({ "line": 0, "column": 21 }));
+    }
+
+    #[test]
+    fn test_serve_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.typ"), "Hello").unwrap();
+
+        let responses = script(
+            dir.path(),
+            &[
+                "{ not json".into(),
+                json!({ "id": 1, "method": "query" }).to_string(),
+                json!({ "jsonrpc": "1.0", "id": 2, "method": "query" }).to_string(),
+                json!({ "jsonrpc": "2.0", "id": 3, "method": "render" }).to_string(),
+                json!({ "jsonrpc": "2.0", "id": 4, "method": "compile", "params": {} })
+                    .to_string(),
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": 5,
+                    "method": "compile",
+                    "params": { "output": "out.pdf", "pages": "3-1" },
+                })
+                .to_string(),
+                json!({ "jsonrpc": "2.0", "method": "render" }).to_string(),
+            ],
+        );
+
+        let codes: Vec<_> = responses
+            .iter()
+            .map(|response| (response["id"].clone(), response["error"]["code"].clone()))
+            .collect();
+        assert_eq!(
+            codes,
+            [
+                (JsonValue::Null, PARSE_ERROR.into()),
+                (JsonValue::Null, INVALID_REQUEST.into()),
+                (2.into(), INVALID_REQUEST.into()),
+                (3.into(), METHOD_NOT_FOUND.into()),
+                (4.into(), INVALID_PARAMS.into()),
+                (5.into(), INVALID_PARAMS.into()),
+            ]
+        );
+        assert_eq!(
+            responses[2]["error"]["message"],
+            "unsupported JSON-RPC version `1.0`"
+        );
+        assert_eq!(
+            responses[5]["error"]["message"],
+            "invalid pages (page range must not end before it starts)"
+        );
+    }
+}
@@ -9,22 +9,38 @@ use same_file::is_same_file;
 use termcolor::WriteColor;
 use typst::diag::StrResult;
 
-use crate::args::CompileCommand;
+use crate::args::{CompileCommand, WatchCommand};
 use crate::color_stream;
 use crate::compile::compile_once;
+use crate::preview::{JumpRequest, Preview};
 use crate::world::SystemWorld;
 
 /// Execute a watching compilation command.
-pub fn watch(mut command: CompileCommand) -> StrResult<()> {
+pub fn watch(command: WatchCommand) -> StrResult<()> {
+    let WatchCommand { compile: mut command, serve, port } = command;
+
     // Create the world that serves sources, files, and fonts.
     let mut world = SystemWorld::new(&command.common)?;
 
+    // Start the preview server, which sends clicks to resolve through the
+    // same channel as the file system events.
+    let (tx, rx) = std::sync::mpsc::channel();
+    let preview = if serve {
+        let format = command.output_format()?;
+        let ranges = command.exported_page_ranges();
+        Some(Preview::serve(port, format, command.ppi, ranges, tx.clone())?)
+    } else {
+        None
+    };
+
     // Perform initial compilation.
-    compile_once(&mut world, &mut command, true)?;
+    compile_once(&mut world, &mut command, true, preview.as_deref())?;
 
     // Setup file watching.
-    let (tx, rx) = std::sync::mpsc::channel();
-    let mut watcher = RecommendedWatcher::new(tx, notify::Config::default())
+    let handler = move |event| {
+        tx.send(Event::Fs(event)).ok();
+    };
+    let mut watcher = RecommendedWatcher::new(handler, notify::Config::default())
         .map_err(|err| eco_format!("failed to setup file watching ({err})"))?;
 
     // Watch all the files that are used by the input file and its dependencies.
@@ -41,8 +57,16 @@ pub fn watch(mut command: CompileCommand) -> StrResult<()> {
             .into_iter()
             .chain(std::iter::from_fn(|| rx.recv_timeout(timeout).ok()))
         {
-            let event =
-                event.map_err(|err| eco_format!("failed to watch directory ({err})"))?;
+            let event = match event {
+                Event::Fs(event) => event
+                    .map_err(|err| eco_format!("failed to watch directory ({err})"))?,
+                Event::Jump(request) => {
+                    if let Some(preview) = &preview {
+                        preview.jump(&world, request);
+                    }
+                    continue;
+                }
+            };
 
             // Workaround for notify-rs' implicit unwatch on remove/rename
             // (triggered by some editors when saving files) with the inotify
@@ -67,7 +91,7 @@ pub fn watch(mut command: CompileCommand) -> StrResult<()> {
             world.reset();
 
             // Recompile.
-            compile_once(&mut world, &mut command, true)?;
+            compile_once(&mut world, &mut command, true, preview.as_deref())?;
             comemo::evict(10);
 
             // Adjust the file watching.
@@ -76,6 +100,14 @@ pub fn watch(mut command: CompileCommand) -> StrResult<()> {
     }
 }
 
+/// An event the watch loop reacts to.
+pub enum Event {
+    /// A change in the file system.
+    Fs(notify::Result<notify::Event>),
+    /// A click in the preview that should be resolved to a source location.
+    Jump(JumpRequest),
+}
+
 /// Adjust the file watching. Watches all new dependencies and unwatches
 /// all previously `watched` files that are no relevant anymore.
 #[tracing::instrument(skip_all)]
@@ -154,7 +186,11 @@ pub enum Status {
 
 impl Status {
     /// Clear the terminal and render the status message.
-    pub fn print(&self, command: &CompileCommand) -> io::Result<()> {
+    pub fn print(
+        &self,
+        command: &CompileCommand,
+        preview: Option<&Preview>,
+    ) -> io::Result<()> {
         let output = command.output();
         let timestamp = chrono::offset::Local::now().format("%H:%M:%S");
         let color = self.color();
@@ -176,6 +212,13 @@ impl Status {
         w.reset()?;
         writeln!(w, " {}", output.display())?;
 
+        if let Some(preview) = preview {
+            w.set_color(&color)?;
+            write!(w, "serving at")?;
+            w.reset()?;
+            writeln!(w, " {}", preview.url())?;
+        }
+
         writeln!(w)?;
         writeln!(w, "[{timestamp}] {}", self.message())?;
         writeln!(w)?;
@@ -71,23 +71,19 @@ impl SystemWorld {
         let main_path = VirtualPath::within_root(&input, &root)
             .ok_or("source file must be contained in project root")?;
 
-        let library = {
-            // Convert the input pairs to a dictionary.
-            let inputs: Dict = command
-                .inputs
-                .iter()
-                .map(|(k, v)| (k.as_str().into(), v.as_str().into_value()))
-                .collect();
-
-            Library::builder().with_inputs(inputs).build()
-        };
+        // Convert the input pairs to a dictionary.
+        let inputs: Dict = command
+            .inputs
+            .iter()
+            .map(|(k, v)| (k.as_str().into(), v.as_str().into_value()))
+            .collect();
 
         Ok(Self {
             workdir: std::env::current_dir().ok(),
             input,
             root,
             main: FileId::new(None, main_path),
-            library: Prehashed::new(library),
+            library: Prehashed::new(Library::builder().with_inputs(inputs).build()),
             book: Prehashed::new(searcher.book),
             fonts: searcher.fonts,
             slots: Mutex::new(HashMap::new()),
@@ -139,6 +135,36 @@ impl SystemWorld {
         self.source(id).expect("file id does not point to any source file")
     }
 
+    /// Replace the inputs visible through `sys.inputs`.
+    pub fn set_inputs(&mut self, inputs: Dict) {
+        self.library = Prehashed::new(Library::builder().with_inputs(inputs).build());
+    }
+
+    /// Resolve a path on the system to the id of a file within the project
+    /// root.
+    pub fn id_for_path(&self, path: &Path) -> StrResult<FileId> {
+        let path = self.workdir().join(path);
+        let path = path.canonicalize().unwrap_or(path);
+        let vpath = VirtualPath::within_root(&path, &self.root)
+            .ok_or("file must be contained in project root")?;
+        Ok(FileId::new(None, vpath))
+    }
+
+    /// Provide in-memory contents for a file, which take precedence over its
+    /// contents on disk. Passing `None` reverts to the file on disk.
+    pub fn set_overlay(&mut self, id: FileId, data: Option<Vec<u8>>) {
+        self.slots
+            .get_mut()
+            .entry(id)
+            .or_insert_with(|| FileSlot::new(id))
+            .overlay = data;
+    }
+
+    /// Resolve the id of a file to its path on the system.
+    pub fn path_for_id(&self, id: FileId) -> FileResult<PathBuf> {
+        system_path(&self.root, id)
+    }
+
     /// Gets access to the export cache.
     pub fn export_cache(&self) -> &ExportCache {
         &self.export_cache
@@ -207,12 +233,19 @@ struct FileSlot {
     source: SlotCell<Source>,
     /// The lazily loaded raw byte buffer.
     file: SlotCell<Bytes>,
+    /// In-memory contents that take precedence over the file on disk.
+    overlay: Option<Vec<u8>>,
 }
 
 impl FileSlot {
     /// Create a new path slot.
     fn new(id: FileId) -> Self {
-        Self { id, file: SlotCell::new(), source: SlotCell::new() }
+        Self {
+            id,
+            file: SlotCell::new(),
+            source: SlotCell::new(),
+            overlay: None,
+        }
     }
 
     /// Whether the file was accessed in the ongoing compilation.
@@ -230,7 +263,7 @@ impl FileSlot {
     /// Retrieve the source for this file.
     fn source(&mut self, project_root: &Path) -> FileResult<Source> {
         self.source.get_or_init(
-            || system_path(project_root, self.id),
+            || load(project_root, self.id, self.overlay.as_deref()),
             |data, prev| {
                 let text = decode_utf8(&data)?;
                 if let Some(mut prev) = prev {
@@ -245,8 +278,10 @@ impl FileSlot {
 
     /// Retrieve the file's bytes.
     fn file(&mut self, project_root: &Path) -> FileResult<Bytes> {
-        self.file
-            .get_or_init(|| system_path(project_root, self.id), |data, _| Ok(data.into()))
+        self.file.get_or_init(
+            || load(project_root, self.id, self.overlay.as_deref()),
+            |data, _| Ok(data.into()),
+        )
     }
 }
 
@@ -280,7 +315,7 @@ impl<T: Clone> SlotCell<T> {
     /// Gets the contents of the cell or initialize them.
     fn get_or_init(
         &mut self,
-        path: impl FnOnce() -> FileResult<PathBuf>,
+        load: impl FnOnce() -> FileResult<Vec<u8>>,
         f: impl FnOnce(Vec<u8>, Option<T>) -> FileResult<T>,
     ) -> FileResult<T> {
         // If we accessed the file already in this compilation, retrieve it.
@@ -291,7 +326,7 @@ impl<T: Clone> SlotCell<T> {
         }
 
         // Read and hash the file.
-        let result = path().and_then(|p| read(&p));
+        let result = load();
         let fingerprint = typst::util::hash128(&result);
 
         // If the file contents didn't change, yield the old processed data.
@@ -326,6 +361,14 @@ fn system_path(project_root: &Path, id: FileId) -> FileResult<PathBuf> {
     id.vpath().resolve(root).ok_or(FileError::AccessDenied)
 }
 
+/// Load a file's contents from its overlay or from the system.
+fn load(project_root: &Path, id: FileId, overlay: Option<&[u8]>) -> FileResult<Vec<u8>> {
+    match overlay {
+        Some(data) => Ok(data.to_vec()),
+        None => read(&system_path(project_root, id)?),
+    }
+}
+
 /// Read a file.
 fn read(path: &Path) -> FileResult<Vec<u8>> {
     let f = |e| FileError::from_io(e, path);
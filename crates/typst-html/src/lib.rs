@@ -0,0 +1,1126 @@
+//! Exporting of Typst documents into HTML.
+
+mod math;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::mem;
+use std::num::NonZeroUsize;
+
+use base64::Engine as _;
+use comemo::Track;
+use ecow::{eco_format, EcoString};
+use typst::diag::{At, SourceResult};
+use typst::engine::{Engine, Route};
+use typst::eval::Tracer;
+use typst::foundations::{
+    Bytes, Content, Guard, NativeElement, Selector, Smart, StyleChain, Value,
+};
+use typst::introspection::{Counter, Location, Locator, Meta, MetaElem};
+use typst::layout::{
+    AlignElem, BlockElem, BoxElem, ColbreakElem, ColumnsElem, GridChild, GridElem,
+    GridItem, HElem, HideElem, MoveElem, PadElem, PageElem, PagebreakElem, PlaceElem,
+    RepeatElem, RotateElem, ScaleElem, StackChild, StackElem, VAlign, VElem,
+};
+use typst::math::EquationElem;
+use typst::model::{
+    Attribution, CitationForm, CiteElem, CiteGroup, Destination, Document, EmphElem,
+    EnumElem, EnumItem, FigureElem, FootnoteBody, FootnoteElem, HeadingElem, LinkElem,
+    LinkTarget, ListElem, ListItem, Outlinable, OutlineElem, ParbreakElem, QuoteElem,
+    RefElem, StrongElem, TableCell, TableChild, TableElem, TableItem, TermItem,
+    TermsElem,
+};
+use typst::realize::realize;
+use typst::text::{
+    HighlightElem, LinebreakElem, LocalName, OverlineElem, RawElem, SmartQuoteElem,
+    SmartQuoter, SmartQuotes, SpaceElem, StrikeElem, SubElem, SuperElem, TextElem,
+    UnderlineElem,
+};
+use typst::util::hash128;
+use typst::visualize::{
+    CircleElem, EllipseElem, ImageElem, ImageFormat, LineElem, PathElem, PolygonElem,
+    RasterFormat, RectElem, SquareElem, VectorFormat,
+};
+use typst::World;
+
+/// Export a document into an HTML file.
+///
+/// The other exporters work with the laid-out pages of a document. A web page
+/// reflows its content, so this exporter instead walks the realized content of
+/// the main source file and writes its elements as their semantic HTML
+/// counterparts. The laid-out document is still needed to resolve counters,
+/// references, and citations.
+#[tracing::instrument(skip_all)]
+pub fn html(world: &dyn World, document: &Document) -> SourceResult<String> {
+    let world = world.track();
+    let mut tracer = Tracer::new();
+
+    // Evaluation is memoized, so this is cheap after compilation.
+    let module = typst::eval::eval(
+        world,
+        Route::default().track(),
+        tracer.track_mut(),
+        &world.main(),
+    )?;
+
+    let library = world.library();
+    let styles = StyleChain::new(&library.styles);
+    let mut locator = Locator::new();
+    let mut engine = Engine {
+        world,
+        route: Route::default(),
+        tracer: tracer.track_mut(),
+        locator: &mut locator,
+        introspector: document.introspector.track(),
+    };
+
+    let mut writer = HtmlWriter::new(&mut engine);
+    let content = module.content();
+    writer.content(&content, styles)?;
+    writer.finish(styles)?;
+    Ok(writer.document(document))
+}
+
+/// Writes realized content as HTML.
+struct HtmlWriter<'a, 'b> {
+    /// The engine used to realize content.
+    engine: &'a mut Engine<'b>,
+    /// The container that is currently being written.
+    container: Container,
+    /// Determines the characters of smart quotes.
+    quoter: SmartQuoter,
+    /// The written footnote entries.
+    footnotes: Vec<EcoString>,
+    /// The indices of the footnote entries, by the location of their notes.
+    footnote_indices: HashMap<Location, usize>,
+    /// The language of the first text in the document.
+    lang: Option<EcoString>,
+}
+
+/// Content that is being written into the same HTML element.
+#[derive(Default)]
+struct Container {
+    /// Whether the container holds inline content, like a heading or a link.
+    /// Everything written into it becomes part of a single paragraph.
+    inline: bool,
+    /// The finished blocks.
+    blocks: Vec<Block>,
+    /// The paragraph that is currently being built.
+    par: String,
+    /// The list that is currently being built.
+    list: Option<List>,
+    /// Consecutive citations that are waiting to be grouped.
+    cites: Vec<CiteElem>,
+    /// Whether there was a space after the waiting citations.
+    cite_space: bool,
+}
+
+/// A block-level piece of HTML.
+enum Block {
+    /// The inner HTML of a paragraph.
+    Par(String),
+    /// Any other block-level element.
+    Other(EcoString),
+}
+
+/// A list whose items are being collected.
+struct List {
+    /// The kind of list.
+    kind: ListKind,
+    /// The HTML of the items so far.
+    items: String,
+    /// The number of the first item of a numbered list.
+    start: usize,
+    /// The number of the next item of a numbered list.
+    next: usize,
+}
+
+/// The kind of a list.
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum ListKind {
+    Bullet,
+    Numbered,
+    Terms,
+}
+
+impl<'a, 'b> HtmlWriter<'a, 'b> {
+    fn new(engine: &'a mut Engine<'b>) -> Self {
+        Self {
+            engine,
+            container: Container::default(),
+            quoter: SmartQuoter::new(),
+            footnotes: vec![],
+            footnote_indices: HashMap::new(),
+            lang: None,
+        }
+    }
+
+    /// Write content in the given styles.
+    fn content(&mut self, content: &Content, styles: StyleChain) -> SourceResult<()> {
+        // References are shown right away because they may become citations,
+        // which are grouped.
+        if content.needs_preparation()
+            || has_recipe(content, styles)
+            || content.is::<RefElem>()
+        {
+            if let Some(realized) = realize(self.engine, content, styles)? {
+                return self.content(&realized, styles);
+            }
+        }
+
+        if let Some(children) = content.to_sequence() {
+            for child in children {
+                self.content(child, styles)?;
+            }
+            return Ok(());
+        }
+
+        if let Some((elem, local)) = content.to_styled() {
+            let styles = styles.chain(local);
+            let metas = MetaElem::data_in(StyleChain::new(local));
+            if metas.iter().any(|meta| matches!(meta, Meta::Hide)) {
+                return Ok(());
+            }
+
+            // Show rules link content with metadata, like references do, and
+            // make it linkable with empty located elements, like bibliography
+            // entries do. Citation groups are linked to from the bibliography.
+            for meta in &metas {
+                match meta {
+                    Meta::Link(dest) => {
+                        if let Some(href) = self.href(dest) {
+                            let body = self.fragment(elem, styles, true)?;
+                            self.link(&href, &body);
+                            return Ok(());
+                        }
+                    }
+                    Meta::Elem(target)
+                        if target.is_empty() || target.is::<CiteGroup>() =>
+                    {
+                        if let Some(location) = target.location() {
+                            let body = self.fragment(elem, styles, true)?;
+                            let id = location_id(location);
+                            self.inline(&format!("<span id=\"{id}\">{body}</span>"));
+                            return Ok(());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            return self.content(elem, styles);
+        }
+
+        // Consecutive citations are grouped, like during layout.
+        if let Some(cite) = content.to::<CiteElem>() {
+            self.container.cites.push(cite.clone());
+            self.container.cite_space = false;
+            return Ok(());
+        } else if !self.container.cites.is_empty() {
+            if content.is::<SpaceElem>() {
+                self.container.cite_space = true;
+                return Ok(());
+            } else if content.is::<MetaElem>() {
+                return Ok(());
+            }
+            self.flush_cites(styles)?;
+        }
+
+        if content.is::<MetaElem>() {
+            return Ok(());
+        }
+
+        if self.container.list.is_some() && !is_item(content) {
+            if content.is::<SpaceElem>() || content.is::<ParbreakElem>() {
+                return Ok(());
+            }
+            self.close_list();
+        }
+
+        self.elem(content, styles)
+    }
+
+    /// Write a single element that is not affected by show rules.
+    fn elem(&mut self, elem: &Content, styles: StyleChain) -> SourceResult<()> {
+        if let Some(text) = elem.to::<TextElem>() {
+            self.lang.get_or_insert_with(|| {
+                let lang = TextElem::lang_in(styles);
+                match TextElem::region_in(styles) {
+                    Some(region) => eco_format!("{}-{}", lang.as_str(), region.as_str()),
+                    None => lang.as_str().into(),
+                }
+            });
+            self.text(text.text());
+        } else if elem.is::<SpaceElem>() {
+            if !self.container.par.is_empty() && !self.container.par.ends_with(' ') {
+                self.text(" ");
+            }
+        } else if elem.is::<LinebreakElem>() {
+            self.inline("<br>");
+        } else if let Some(quote) = elem.to::<SmartQuoteElem>() {
+            self.smart_quote(quote, styles);
+        } else if elem.is::<ParbreakElem>()
+            || elem.is::<VElem>()
+            || elem.is::<PagebreakElem>()
+            || elem.is::<ColbreakElem>()
+        {
+            // Inline content has no paragraphs to break.
+            if self.container.inline {
+                self.elem(&SpaceElem::new().pack(), styles)?;
+            }
+            self.flush_par();
+        } else if let Some(strong) = elem.to::<StrongElem>() {
+            self.wrap("strong", strong.body(), styles)?;
+        } else if let Some(emph) = elem.to::<EmphElem>() {
+            self.wrap("em", emph.body(), styles)?;
+        } else if let Some(underline) = elem.to::<UnderlineElem>() {
+            self.wrap("u", underline.body(), styles)?;
+        } else if let Some(strike) = elem.to::<StrikeElem>() {
+            self.wrap("s", strike.body(), styles)?;
+        } else if let Some(overline) = elem.to::<OverlineElem>() {
+            let body = self.fragment(overline.body(), styles, true)?;
+            self.inline(&format!(
+                "<span style=\"text-decoration: overline\">{body}</span>"
+            ));
+        } else if let Some(highlight) = elem.to::<HighlightElem>() {
+            self.wrap("mark", highlight.body(), styles)?;
+        } else if let Some(sup) = elem.to::<SuperElem>() {
+            self.wrap("sup", sup.body(), styles)?;
+        } else if let Some(sub) = elem.to::<SubElem>() {
+            self.wrap("sub", sub.body(), styles)?;
+        } else if let Some(link) = elem.to::<LinkElem>() {
+            let body = self.fragment(link.body(), styles, true)?;
+            match link.dest() {
+                LinkTarget::Dest(dest) => match self.href(dest) {
+                    Some(href) => self.link(&href, &body),
+                    None => self.inline(&body),
+                },
+                LinkTarget::Label(label) => {
+                    self.link(&eco_format!("#{}", label.as_str()), &body)
+                }
+            }
+        } else if let Some(heading) = elem.to::<HeadingElem>() {
+            self.heading(elem, heading, styles)?;
+        } else if is_item(elem) {
+            self.item(elem, styles)?;
+        } else if let Some(list) = elem.to::<ListElem>() {
+            for item in list.children() {
+                self.item(&item.clone().pack(), styles)?;
+            }
+            self.close_list();
+        } else if let Some(enum_) = elem.to::<EnumElem>() {
+            self.close_list();
+            let mut next = enum_.start(styles);
+            for item in enum_.children() {
+                let number = item.number(styles).unwrap_or(next);
+                self.item(&item.clone().with_number(Some(number)).pack(), styles)?;
+                next = number + 1;
+            }
+            self.close_list();
+        } else if let Some(terms) = elem.to::<TermsElem>() {
+            for item in terms.children() {
+                self.item(&item.clone().pack(), styles)?;
+            }
+            self.close_list();
+        } else if let Some(table) = elem.to::<TableElem>() {
+            self.table(elem, table, styles)?;
+        } else if let Some(grid) = elem.to::<GridElem>() {
+            self.grid(grid, styles)?;
+        } else if let Some(figure) = elem.to::<FigureElem>() {
+            self.figure(elem, figure, styles)?;
+        } else if let Some(image) = elem.to::<ImageElem>() {
+            self.image(image, styles)?;
+        } else if let Some(raw) = elem.to::<RawElem>() {
+            let lang = raw.lang(styles);
+            let code = escape(raw.text());
+            if raw.block(styles) {
+                let class = match lang {
+                    Some(lang) => eco_format!(" class=\"language-{}\"", escape(lang)),
+                    None => EcoString::new(),
+                };
+                self.block(eco_format!("<pre><code{class}>{code}</code></pre>"));
+            } else {
+                self.inline(&format!("<code>{code}</code>"));
+            }
+        } else if let Some(equation) = elem.to::<EquationElem>() {
+            self.equation(elem, equation, styles)?;
+        } else if let Some(footnote) = elem.to::<FootnoteElem>() {
+            self.footnote(elem, footnote, styles)?;
+        } else if let Some(quote) = elem.to::<QuoteElem>() {
+            self.quote(quote, styles)?;
+        } else if let Some(outline) = elem.to::<OutlineElem>() {
+            self.outline(outline, styles)?;
+        } else if let Some(block) = elem.to::<BlockElem>() {
+            if let Some(body) = block.body(styles) {
+                let body = self.fragment(&body, styles, false)?;
+                self.block(eco_format!("<div>{body}</div>"));
+            }
+        } else if let Some(boxed) = elem.to::<BoxElem>() {
+            if let Some(body) = boxed.body(styles) {
+                let body = self.fragment(&body, styles, true)?;
+                self.inline(&body);
+            }
+        } else if let Some(stack) = elem.to::<StackElem>() {
+            for child in stack.children() {
+                if let StackChild::Block(block) = child {
+                    let body = self.fragment(block, styles, false)?;
+                    self.block(eco_format!("<div>{body}</div>"));
+                }
+            }
+        } else if elem.is::<HElem>()
+            || elem.is::<HideElem>()
+            || elem.is::<RepeatElem>()
+            || elem.is::<LineElem>()
+            || elem.is::<PathElem>()
+            || elem.is::<PolygonElem>()
+        {
+            // These have no meaning beyond their looks.
+        } else if let Some(body) = transparent(elem, styles) {
+            self.content(&body, styles)?;
+        } else if let Some(realized) = realize(self.engine, elem, styles)? {
+            self.content(&realized, styles)?;
+        } else if let Some(Value::Content(body)) = elem.get_by_name("body") {
+            self.content(&body, styles)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write text into the current paragraph.
+    fn text(&mut self, text: &str) {
+        if let Some(c) = text.chars().last() {
+            self.quoter.last(c, false);
+        }
+        self.inline(&escape(text));
+    }
+
+    /// Write a smart quote.
+    fn smart_quote(&mut self, elem: &SmartQuoteElem, styles: StyleChain) {
+        let double = elem.double(styles);
+        if !SmartQuoteElem::enabled_in(styles) {
+            self.text(if double { "\"" } else { "'" });
+            return;
+        }
+
+        let quotes = SmartQuoteElem::quotes_in(styles);
+        let quotes = SmartQuotes::new(
+            quotes,
+            TextElem::lang_in(styles),
+            TextElem::region_in(styles),
+            SmartQuoteElem::alternative_in(styles),
+        );
+
+        let quote = self.quoter.quote(&quotes, double, None);
+        if let Some(c) = quote.chars().last() {
+            self.quoter.last(c, true);
+        }
+        self.inline(&escape(quote));
+    }
+
+    /// Write content wrapped in an inline HTML element.
+    fn wrap(
+        &mut self,
+        tag: &str,
+        body: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let body = self.fragment(body, styles, true)?;
+        self.inline(&format!("<{tag}>{body}</{tag}>"));
+        Ok(())
+    }
+
+    /// Write a link.
+    fn link(&mut self, href: &str, body: &str) {
+        self.inline(&format!("<a href=\"{}\">{body}</a>", escape(href)));
+    }
+
+    /// Write a heading.
+    fn heading(
+        &mut self,
+        elem: &Content,
+        heading: &HeadingElem,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let level = heading.level(styles).get().min(6);
+        let mut body = String::new();
+        if let (Some(numbering), Some(location)) =
+            (heading.numbering(styles), elem.location())
+        {
+            let numbers = Counter::of(HeadingElem::elem())
+                .at(self.engine, location)?
+                .display(self.engine, numbering)?;
+            body += self.fragment(&numbers, styles, true)?.trim();
+            body.push(' ');
+        }
+        body += self.fragment(heading.body(), styles, true)?.trim();
+        let id = self.id(elem);
+        self.block(eco_format!("<h{level}{id}>{body}</h{level}>"));
+        Ok(())
+    }
+
+    /// Write a list, enumeration, or term item.
+    fn item(&mut self, elem: &Content, styles: StyleChain) -> SourceResult<()> {
+        let kind = if elem.is::<ListItem>() {
+            ListKind::Bullet
+        } else if elem.is::<EnumItem>() {
+            ListKind::Numbered
+        } else {
+            ListKind::Terms
+        };
+
+        if self.container.list.as_ref().map_or(false, |list| list.kind != kind) {
+            self.close_list();
+        }
+
+        if self.container.list.is_none() {
+            self.flush_par();
+            let start = match elem.to::<EnumItem>().and_then(|item| item.number(styles)) {
+                Some(number) => number,
+                None => EnumElem::start_in(styles),
+            };
+            self.container.list =
+                Some(List { kind, items: String::new(), start, next: start });
+        }
+
+        let html = if let Some(item) = elem.to::<ListItem>() {
+            let body = self.fragment(item.body(), styles, false)?;
+            format!("<li>{body}</li>\n")
+        } else if let Some(item) = elem.to::<EnumItem>() {
+            let body = self.fragment(item.body(), styles, false)?;
+            let list = self.container.list.as_mut().unwrap();
+            let number = item.number(styles).unwrap_or(list.next);
+            let value = if number != list.next {
+                format!(" value=\"{number}\"")
+            } else {
+                String::new()
+            };
+            list.next = number + 1;
+            format!("<li{value}>{body}</li>\n")
+        } else if let Some(item) = elem.to::<TermItem>() {
+            let term = self.fragment(item.term(), styles, true)?;
+            let description = self.fragment(item.description(), styles, false)?;
+            format!("<dt>{}</dt>\n<dd>{description}</dd>\n", term.trim())
+        } else {
+            unreachable!()
+        };
+
+        self.container.list.as_mut().unwrap().items.push_str(&html);
+        Ok(())
+    }
+
+    /// Write a table.
+    fn table(
+        &mut self,
+        elem: &Content,
+        table: &TableElem,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let columns = table.columns(styles).0.len().max(1);
+        let mut cells = Vec::new();
+        let mut occupied = HashSet::new();
+        let mut cursor = 0;
+
+        for child in table.children() {
+            let (section, items) = match child {
+                TableChild::Header(header) => {
+                    (Section::Header, header.children().as_slice())
+                }
+                TableChild::Footer(footer) => {
+                    (Section::Footer, footer.children().as_slice())
+                }
+                TableChild::Item(item) => (Section::Body, std::slice::from_ref(item)),
+            };
+
+            for item in items {
+                let TableItem::Cell(cell) = item else { continue };
+                let x = cell.x(styles).map(|x| x.min(columns - 1));
+                let colspan = cell.colspan(styles).get();
+                let rowspan = cell.rowspan(styles).get();
+                let (x, y, colspan) =
+                    place(&occupied, &mut cursor, columns, x, cell.y(styles), colspan);
+                for dy in 0..rowspan {
+                    for dx in 0..colspan {
+                        occupied.insert((x + dx, y + dy));
+                    }
+                }
+                cells.push(PlacedCell { x, y, colspan, rowspan, section, cell });
+            }
+        }
+
+        let mut rows: BTreeMap<usize, Vec<&PlacedCell>> = BTreeMap::new();
+        for cell in &cells {
+            rows.entry(cell.y).or_default().push(cell);
+        }
+
+        let height = cells.iter().map(|cell| cell.y + cell.rowspan).max().unwrap_or(0);
+        let mut sections = [String::new(), String::new(), String::new()];
+        for y in 0..height {
+            let mut row = rows.remove(&y).unwrap_or_default();
+            row.sort_by_key(|cell| cell.x);
+            let section = row.first().map_or(Section::Body, |cell| cell.section);
+            let html = &mut sections[section as usize];
+            html.push_str("<tr>");
+            for cell in row {
+                let tag = if cell.section == Section::Header { "th" } else { "td" };
+                let body = self.fragment(cell.cell.body(), styles, false)?;
+                html.push('<');
+                html.push_str(tag);
+                if cell.colspan > 1 {
+                    write!(html, " colspan=\"{}\"", cell.colspan).unwrap();
+                }
+                if cell.rowspan > 1 {
+                    write!(html, " rowspan=\"{}\"", cell.rowspan).unwrap();
+                }
+                write!(html, ">{body}</{tag}>").unwrap();
+            }
+            html.push_str("</tr>\n");
+        }
+
+        let mut html = eco_format!("<table{}>\n", label_id(elem));
+        for (section, tag) in sections.iter().zip(["thead", "tbody", "tfoot"]) {
+            if !section.is_empty() {
+                write!(html, "<{tag}>\n{section}</{tag}>\n").unwrap();
+            }
+        }
+        html.push_str("</table>");
+        self.block(html);
+        Ok(())
+    }
+
+    /// Write a grid. Grids only arrange their cells visually, so the cells are
+    /// written one after another.
+    fn grid(&mut self, grid: &GridElem, styles: StyleChain) -> SourceResult<()> {
+        let mut html = EcoString::from("<div>\n");
+        for child in grid.children() {
+            let items = match child {
+                GridChild::Header(header) => header.children().as_slice(),
+                GridChild::Footer(footer) => footer.children().as_slice(),
+                GridChild::Item(item) => std::slice::from_ref(item),
+            };
+            for item in items {
+                let GridItem::Cell(cell) = item else { continue };
+                let body = self.fragment(cell.body(), styles, false)?;
+                writeln!(html, "<div>{body}</div>").unwrap();
+            }
+        }
+        html.push_str("</div>");
+        self.block(html);
+        Ok(())
+    }
+
+    /// Write a figure.
+    fn figure(
+        &mut self,
+        elem: &Content,
+        figure: &FigureElem,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let body = self.fragment(figure.body(), styles, false)?;
+        let mut html = eco_format!("<figure{}>\n", self.id(elem));
+        let caption = match figure.caption(styles) {
+            Some(caption) => {
+                let top = caption.position(styles) == VAlign::Top;
+                let caption = self.fragment(&caption.pack(), styles, true)?;
+                Some((top, eco_format!("<figcaption>{}</figcaption>\n", caption.trim())))
+            }
+            None => None,
+        };
+
+        if let Some((true, caption)) = &caption {
+            html.push_str(caption);
+        }
+        html.push_str(&body);
+        html.push('\n');
+        if let Some((false, caption)) = &caption {
+            html.push_str(caption);
+        }
+        html.push_str("</figure>");
+        self.block(html);
+        Ok(())
+    }
+
+    /// Write an image with its data embedded.
+    fn image(&mut self, image: &ImageElem, styles: StyleChain) -> SourceResult<()> {
+        let mime = match image.determine_format(styles)? {
+            ImageFormat::Raster(RasterFormat::Png) => "image/png",
+            ImageFormat::Raster(RasterFormat::Jpg) => "image/jpeg",
+            ImageFormat::Raster(RasterFormat::Gif) => "image/gif",
+            ImageFormat::Vector(VectorFormat::Svg) => "image/svg+xml",
+        };
+
+        let data = Bytes::from(image.data().clone());
+        let data = base64::engine::general_purpose::STANDARD.encode(data);
+        let alt = image.alt(styles).unwrap_or_default();
+        self.inline(&format!(
+            "<img src=\"data:{mime};base64,{data}\" alt=\"{}\">",
+            escape(&alt)
+        ));
+        Ok(())
+    }
+
+    /// Write an equation as MathML.
+    fn equation(
+        &mut self,
+        elem: &Content,
+        equation: &EquationElem,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let block = equation.block(styles);
+        let math = math::mathml(self.engine, equation.body(), styles, block)?;
+        if !block {
+            self.inline(&math);
+            return Ok(());
+        }
+
+        let mut html = eco_format!("<div class=\"equation\"{}>{math}", self.id(elem));
+        if let (Some(numbering), Some(location)) =
+            (equation.numbering(styles), elem.location())
+        {
+            let numbers = Counter::of(EquationElem::elem())
+                .at(self.engine, location)?
+                .display(self.engine, &numbering)?;
+            let numbers = self.fragment(&numbers, styles, true)?;
+            write!(html, "<span class=\"equation-number\">{}</span>", numbers.trim())
+                .unwrap();
+        }
+        html.push_str("</div>");
+        self.block(html);
+        Ok(())
+    }
+
+    /// Write a footnote reference and collect its entry.
+    fn footnote(
+        &mut self,
+        elem: &Content,
+        footnote: &FootnoteElem,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let Some(location) = elem.location() else { return Ok(()) };
+        let declaration = footnote.declaration_location(self.engine).at(elem.span())?;
+        let numbers = Counter::of(FootnoteElem::elem())
+            .at(self.engine, declaration)?
+            .display(self.engine, footnote.numbering(styles))?;
+        let number = self.fragment(&numbers, styles, true)?;
+        let number = number.trim();
+
+        let index = match footnote.body() {
+            FootnoteBody::Content(body) => {
+                let body = self.fragment(body, styles, false)?;
+                let index = self.footnotes.len() + 1;
+                self.footnotes.push(eco_format!(
+                    "<div class=\"footnote\" id=\"fn-{index}\">\
+                     <sup><a href=\"#fnref-{index}\">{number}</a></sup> {body}</div>"
+                ));
+                self.footnote_indices.insert(location, index);
+                self.inline(&format!(
+                    "<sup><a href=\"#fn-{index}\" id=\"fnref-{index}\">{number}</a></sup>"
+                ));
+                return Ok(());
+            }
+            FootnoteBody::Reference(_) => self.footnote_indices.get(&declaration),
+        };
+
+        match index {
+            Some(index) => {
+                self.inline(&format!("<sup><a href=\"#fn-{index}\">{number}</a></sup>"))
+            }
+            None => self.inline(&format!("<sup>{number}</sup>")),
+        }
+
+        Ok(())
+    }
+
+    /// Write a quote.
+    fn quote(&mut self, quote: &QuoteElem, styles: StyleChain) -> SourceResult<()> {
+        let attribution = quote.attribution(styles);
+        if quote.block(styles) {
+            let body = self.fragment(quote.body(), styles, false)?;
+            let mut html = eco_format!("<blockquote>\n{body}\n");
+            let attribution = match attribution {
+                Some(Attribution::Content(content)) => Some(content.clone()),
+                Some(Attribution::Label(label)) => Some(
+                    CiteElem::new(*label).with_form(Some(CitationForm::Prose)).pack(),
+                ),
+                None => None,
+            };
+            if let Some(attribution) = attribution {
+                let attribution = self.fragment(&attribution, styles, true)?;
+                writeln!(html, "<footer>— {}</footer>", attribution.trim()).unwrap();
+            }
+            html.push_str("</blockquote>");
+            self.block(html);
+        } else {
+            let body = self.fragment(quote.body(), styles, true)?;
+            self.inline(&format!("<q>{body}</q>"));
+            if let Some(Attribution::Label(label)) = attribution {
+                let cite = self.fragment(&CiteElem::new(*label).pack(), styles, true)?;
+                self.inline(&format!(" {}", cite.trim()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write an outline with links to the outlined elements.
+    fn outline(&mut self, outline: &OutlineElem, styles: StyleChain) -> SourceResult<()> {
+        let mut html = EcoString::from("<nav>\n");
+        if let Some(title) = outline.title(styles) {
+            let title = title.unwrap_or_else(|| {
+                TextElem::packed(OutlineElem::local_name_in(styles))
+                    .spanned(outline.span())
+            });
+            let title = self.fragment(&title, styles, true)?;
+            writeln!(html, "<h1>{}</h1>", title.trim()).unwrap();
+        }
+
+        let depth = outline.depth(styles).map_or(usize::MAX, NonZeroUsize::get);
+        let elems = self.engine.introspector.query(&outline.target(styles).0);
+        let mut levels: Vec<usize> = vec![];
+        for elem in &elems {
+            let Some(outlinable) = elem.with::<dyn Outlinable>() else { continue };
+            let Some(location) = elem.location() else { continue };
+            let level = outlinable.level().get();
+            if level > depth {
+                continue;
+            }
+
+            let Some(body) = outlinable.outline(self.engine)? else { continue };
+            let body = self.fragment(&body, styles, true)?;
+
+            while levels.last().map_or(false, |&last| last > level) {
+                html.push_str("</li>\n</ul>\n");
+                levels.pop();
+            }
+            if levels.last() == Some(&level) {
+                html.push_str("</li>\n");
+            } else {
+                html.push_str("<ul>\n");
+                levels.push(level);
+            }
+
+            let href = escape(&self.anchor(location));
+            write!(html, "<li><a href=\"#{href}\">{}</a>", body.trim()).unwrap();
+        }
+
+        for _ in levels {
+            html.push_str("</li>\n</ul>\n");
+        }
+
+        html.push_str("</nav>");
+        self.block(html);
+        Ok(())
+    }
+
+    /// Write content into a new container and return its HTML.
+    fn fragment(
+        &mut self,
+        content: &Content,
+        styles: StyleChain,
+        inline: bool,
+    ) -> SourceResult<String> {
+        let outer =
+            mem::replace(&mut self.container, Container { inline, ..Default::default() });
+        let result = self.content(content, styles).and_then(|_| self.finish(styles));
+        let container = mem::replace(&mut self.container, outer);
+        result?;
+
+        if inline {
+            return Ok(container.par);
+        }
+
+        Ok(match container.blocks.as_slice() {
+            // Content that forms a single paragraph is written without one.
+            [Block::Par(par)] => par.clone(),
+            blocks => blocks.iter().map(Block::to_html).collect::<Vec<_>>().join("\n"),
+        })
+    }
+
+    /// Finish the current container.
+    fn finish(&mut self, styles: StyleChain) -> SourceResult<()> {
+        self.flush_cites(styles)?;
+        self.close_list();
+        self.flush_par();
+        Ok(())
+    }
+
+    /// Write the waiting citations as a group.
+    fn flush_cites(&mut self, styles: StyleChain) -> SourceResult<()> {
+        let cites = mem::take(&mut self.container.cites);
+        if cites.is_empty() {
+            return Ok(());
+        }
+
+        let span = cites[0].span();
+        let group = CiteGroup::new(cites).spanned(span).pack();
+        self.content(&group, styles)?;
+        if mem::take(&mut self.container.cite_space) {
+            self.text(" ");
+        }
+
+        Ok(())
+    }
+
+    /// Write inline HTML into the current paragraph.
+    fn inline(&mut self, html: &str) {
+        self.container.par.push_str(html);
+    }
+
+    /// Write block-level HTML.
+    fn block(&mut self, html: EcoString) {
+        if self.container.inline {
+            self.inline(&html);
+        } else {
+            self.flush_par();
+            self.container.blocks.push(Block::Other(html));
+        }
+    }
+
+    /// Finish the current paragraph.
+    fn flush_par(&mut self) {
+        if self.container.inline {
+            return;
+        }
+
+        let par = mem::take(&mut self.container.par);
+        let par = par.trim();
+        if !par.is_empty() {
+            self.container.blocks.push(Block::Par(par.into()));
+        }
+    }
+
+    /// Finish the current list.
+    fn close_list(&mut self) {
+        let Some(list) = self.container.list.take() else { return };
+        let html = match list.kind {
+            ListKind::Bullet => eco_format!("<ul>\n{}</ul>", list.items),
+            ListKind::Numbered if list.start != 1 => {
+                eco_format!("<ol start=\"{}\">\n{}</ol>", list.start, list.items)
+            }
+            ListKind::Numbered => eco_format!("<ol>\n{}</ol>", list.items),
+            ListKind::Terms => eco_format!("<dl>\n{}</dl>", list.items),
+        };
+        self.block(html);
+    }
+
+    /// The link target for a destination, if it has one on the web.
+    fn href(&self, dest: &Destination) -> Option<EcoString> {
+        match dest {
+            Destination::Url(url) => Some(url.clone()),
+            Destination::Location(location) => {
+                Some(eco_format!("#{}", self.anchor(*location)))
+            }
+            Destination::Position(_) => None,
+        }
+    }
+
+    /// The ID of the element at a location. This is its label, if it has one.
+    fn anchor(&self, location: Location) -> EcoString {
+        self.engine
+            .introspector
+            .query_first(&Selector::Location(location))
+            .and_then(|elem| elem.label())
+            .map(|label| label.as_str().into())
+            .unwrap_or_else(|| location_id(location))
+    }
+
+    /// The ID attribute of an element that can be linked to.
+    fn id(&self, elem: &Content) -> EcoString {
+        match (elem.label(), elem.location()) {
+            (Some(_), _) => label_id(elem),
+            (None, Some(location)) => eco_format!(" id=\"{}\"", location_id(location)),
+            (None, None) => EcoString::new(),
+        }
+    }
+
+    /// Write the whole HTML document.
+    fn document(self, document: &Document) -> String {
+        let mut html = String::from("<!DOCTYPE html>\n");
+        let lang = self.lang.unwrap_or_else(|| "en".into());
+        writeln!(html, "<html lang=\"{}\">", escape(&lang)).unwrap();
+        html.push_str("<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str(
+            "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n",
+        );
+        if let Some(title) = &document.title {
+            writeln!(html, "<title>{}</title>", escape(title)).unwrap();
+        }
+        if !document.author.is_empty() {
+            let author = document.author.join(", ");
+            writeln!(html, "<meta name=\"author\" content=\"{}\">", escape(&author))
+                .unwrap();
+        }
+        if let Some(subject) = &document.subject {
+            writeln!(html, "<meta name=\"description\" content=\"{}\">", escape(subject))
+                .unwrap();
+        }
+        if !document.keywords.is_empty() {
+            let keywords = document.keywords.join(", ");
+            writeln!(html, "<meta name=\"keywords\" content=\"{}\">", escape(&keywords))
+                .unwrap();
+        }
+        html.push_str("</head>\n<body>\n");
+
+        for block in &self.container.blocks {
+            html.push_str(&block.to_html());
+            html.push('\n');
+        }
+
+        if !self.footnotes.is_empty() {
+            html.push_str("<section class=\"footnotes\">\n");
+            for footnote in &self.footnotes {
+                html.push_str(footnote);
+                html.push('\n');
+            }
+            html.push_str("</section>\n");
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+impl Block {
+    fn to_html(&self) -> String {
+        match self {
+            Self::Par(par) => format!("<p>{par}</p>"),
+            Self::Other(html) => html.to_string(),
+        }
+    }
+}
+
+/// The section of a table a cell belongs to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Section {
+    Header,
+    Body,
+    Footer,
+}
+
+/// A table cell with its resolved position.
+struct PlacedCell<'a> {
+    x: usize,
+    y: usize,
+    colspan: usize,
+    rowspan: usize,
+    section: Section,
+    cell: &'a TableCell,
+}
+
+/// Find the position of a table cell, filling the table row by row like
+/// layout does. Returns the cell's position and its clamped colspan.
+fn place(
+    occupied: &HashSet<(usize, usize)>,
+    cursor: &mut usize,
+    columns: usize,
+    x: Smart<usize>,
+    y: Smart<usize>,
+    colspan: usize,
+) -> (usize, usize, usize) {
+    let fits = |x: usize, y: usize, colspan: usize| {
+        (x..x + colspan).all(|x| !occupied.contains(&(x, y)))
+    };
+
+    match (x, y) {
+        (Smart::Custom(x), Smart::Custom(y)) => (x, y, colspan.min(columns - x)),
+        (Smart::Custom(x), Smart::Auto) => {
+            let colspan = colspan.min(columns - x);
+            let mut y = *cursor / columns;
+            while !fits(x, y, colspan) {
+                y += 1;
+            }
+            (x, y, colspan)
+        }
+        (Smart::Auto, Smart::Custom(y)) => {
+            let colspan = colspan.min(columns);
+            let x = (0..=columns - colspan).find(|&x| fits(x, y, colspan)).unwrap_or(0);
+            (x, y, colspan)
+        }
+        (Smart::Auto, Smart::Auto) => {
+            let colspan = colspan.min(columns);
+            loop {
+                let (x, y) = (*cursor % columns, *cursor / columns);
+                *cursor += 1;
+                if x + colspan <= columns && fits(x, y, colspan) {
+                    *cursor += colspan - 1;
+                    return (x, y, colspan);
+                }
+            }
+        }
+    }
+}
+
+/// Whether content is an item of a list, enumeration, or term list.
+fn is_item(content: &Content) -> bool {
+    content.is::<ListItem>() || content.is::<EnumItem>() || content.is::<TermItem>()
+}
+
+/// Whether a show rule applies to the content.
+fn has_recipe(content: &Content, styles: StyleChain) -> bool {
+    let mut n = styles.recipes().count();
+    for recipe in styles.recipes() {
+        if recipe.applicable(content) && !content.is_guarded(Guard::Nth(n)) {
+            return true;
+        }
+        n -= 1;
+    }
+    false
+}
+
+/// The body of an element that only affects the layout of its content.
+fn transparent(elem: &Content, styles: StyleChain) -> Option<Content> {
+    if let Some(elem) = elem.to::<AlignElem>() {
+        Some(elem.body().clone())
+    } else if let Some(elem) = elem.to::<PadElem>() {
+        Some(elem.body().clone())
+    } else if let Some(elem) = elem.to::<MoveElem>() {
+        Some(elem.body().clone())
+    } else if let Some(elem) = elem.to::<ScaleElem>() {
+        Some(elem.body().clone())
+    } else if let Some(elem) = elem.to::<RotateElem>() {
+        Some(elem.body().clone())
+    } else if let Some(elem) = elem.to::<ColumnsElem>() {
+        Some(elem.body().clone())
+    } else if let Some(elem) = elem.to::<PlaceElem>() {
+        Some(elem.body().clone())
+    } else if let Some(elem) = elem.to::<PageElem>() {
+        Some(elem.body().clone())
+    } else if let Some(elem) = elem.to::<RectElem>() {
+        elem.body(styles)
+    } else if let Some(elem) = elem.to::<SquareElem>() {
+        elem.body(styles)
+    } else if let Some(elem) = elem.to::<EllipseElem>() {
+        elem.body(styles)
+    } else if let Some(elem) = elem.to::<CircleElem>() {
+        elem.body(styles)
+    } else {
+        None
+    }
+}
+
+/// The ID attribute of an element with a label.
+fn label_id(elem: &Content) -> EcoString {
+    match elem.label() {
+        Some(label) => eco_format!(" id=\"{}\"", escape(label.as_str())),
+        None => EcoString::new(),
+    }
+}
+
+/// The ID of an element without a label.
+fn location_id(location: Location) -> EcoString {
+    eco_format!("loc-{:032x}", hash128(&location))
+}
+
+/// Escape text for use in HTML content and attribute values.
+fn escape(text: &str) -> EcoString {
+    let mut escaped = EcoString::new();
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,452 @@
+use ecow::{eco_format, EcoString};
+use typst::diag::SourceResult;
+use typst::engine::Engine;
+use typst::foundations::{Content, IntoValue, StyleChain, Value};
+use typst::math::{
+    AccentElem, AlignPointElem, AttachElem, BinomElem, CancelElem, CasesElem, ClassElem,
+    Delimiter, EquationElem, FracElem, LimitsElem, LrElem, MatElem, MathStyleElem,
+    MathVariant, MidElem, OpElem, OverbraceElem, OverbracketElem, OverlineElem,
+    PrimesElem, RootElem, ScriptsElem, UnderbraceElem, UnderbracketElem, UnderlineElem,
+    VecElem,
+};
+use typst::realize::realize;
+use typst::text::{LinebreakElem, SpaceElem, TextElem};
+
+use crate::{escape, has_recipe};
+
+/// Convert the body of an equation into a MathML `<math>` element.
+///
+/// The body is realized piece by piece in the equation's styles, so show and
+/// set rules for math elements apply like they do during layout.
+pub fn mathml(
+    engine: &mut Engine,
+    body: &Content,
+    styles: StyleChain,
+    block: bool,
+) -> SourceResult<EcoString> {
+    let mut math = MathMl { engine, block, out: EcoString::new() };
+    math.out
+        .push_str(if block { "<math display=\"block\">" } else { "<math>" });
+    math.row(body, styles)?;
+    math.out.push_str("</math>");
+    Ok(math.out)
+}
+
+/// Writes math content as MathML.
+struct MathMl<'a, 'b> {
+    /// The engine used to realize content.
+    engine: &'a mut Engine<'b>,
+    /// Whether the equation is displayed as a block.
+    block: bool,
+    /// The MathML written so far.
+    out: EcoString,
+}
+
+impl MathMl<'_, '_> {
+    /// Write content as a single MathML element, grouping it into an `<mrow>`
+    /// if it consists of multiple pieces.
+    fn row(&mut self, content: &Content, styles: StyleChain) -> SourceResult<()> {
+        let outer = std::mem::take(&mut self.out);
+        let count = self.pieces(content, styles)?;
+        let inner = std::mem::replace(&mut self.out, outer);
+        if count == 1 {
+            self.out.push_str(&inner);
+        } else {
+            self.out.push_str("<mrow>");
+            self.out.push_str(&inner);
+            self.out.push_str("</mrow>");
+        }
+        Ok(())
+    }
+
+    /// Write the pieces of math content, looking through sequences and styles
+    /// and realizing elements that show rules apply to. Returns the number of
+    /// written elements.
+    fn pieces(&mut self, content: &Content, styles: StyleChain) -> SourceResult<usize> {
+        if let Some(seq) = content.to_sequence() {
+            let mut count = 0;
+            for child in seq {
+                count += self.pieces(child, styles)?;
+            }
+            return Ok(count);
+        }
+
+        if let Some((elem, local)) = content.to_styled() {
+            return self.pieces(elem, styles.chain(local));
+        }
+
+        if is_ignorable(content) {
+            return Ok(0);
+        }
+
+        // Equations produced by show rules are part of the surrounding math.
+        if let Some(equation) = content.to::<EquationElem>() {
+            return self.pieces(equation.body(), styles);
+        }
+
+        if content.needs_preparation() || has_recipe(content, styles) {
+            if let Some(realized) = realize(self.engine, content, styles)? {
+                return self.pieces(&realized, styles);
+            }
+        }
+
+        self.node(content, styles)?;
+        Ok(1)
+    }
+
+    /// Write a single math element.
+    fn node(&mut self, elem: &Content, styles: StyleChain) -> SourceResult<()> {
+        if let Some(text) = elem.to::<TextElem>() {
+            self.text(text.text());
+        } else if let Some(attach) = elem.to::<AttachElem>() {
+            self.attach(attach, styles)?;
+        } else if let Some(frac) = elem.to::<FracElem>() {
+            self.out.push_str("<mfrac>");
+            self.row(frac.num(), styles)?;
+            self.row(frac.denom(), styles)?;
+            self.out.push_str("</mfrac>");
+        } else if let Some(binom) = elem.to::<BinomElem>() {
+            self.out.push_str("<mrow><mo>(</mo><mfrac linethickness=\"0\">");
+            self.row(binom.upper(), styles)?;
+            self.out.push_str("<mrow>");
+            for (i, lower) in binom.lower().iter().enumerate() {
+                if i > 0 {
+                    self.out.push_str("<mo>,</mo>");
+                }
+                self.row(lower, styles)?;
+            }
+            self.out.push_str("</mrow></mfrac><mo>)</mo></mrow>");
+        } else if let Some(root) = elem.to::<RootElem>() {
+            match root.index(styles) {
+                Some(index) => {
+                    self.out.push_str("<mroot>");
+                    self.row(root.radicand(), styles)?;
+                    self.row(&index, styles)?;
+                    self.out.push_str("</mroot>");
+                }
+                None => {
+                    self.out.push_str("<msqrt>");
+                    self.row(root.radicand(), styles)?;
+                    self.out.push_str("</msqrt>");
+                }
+            }
+        } else if let Some(lr) = elem.to::<LrElem>() {
+            self.row(lr.body(), styles)?;
+        } else if let Some(mid) = elem.to::<MidElem>() {
+            self.row(mid.body(), styles)?;
+        } else if let Some(op) = elem.to::<OpElem>() {
+            self.out.push_str("<mi>");
+            self.out.push_str(&escape(&op.text().plain_text()));
+            self.out.push_str("</mi>");
+        } else if let Some(vec) = elem.to::<VecElem>() {
+            let rows: Vec<_> = vec.children().iter().map(std::slice::from_ref).collect();
+            self.table(&rows, vec.delim(styles).map(delimiters), styles)?;
+        } else if let Some(mat) = elem.to::<MatElem>() {
+            let rows: Vec<_> = mat.rows().iter().map(Vec::as_slice).collect();
+            self.table(&rows, mat.delim(styles).map(delimiters), styles)?;
+        } else if let Some(cases) = elem.to::<CasesElem>() {
+            let rows: Vec<_> =
+                cases.children().iter().map(std::slice::from_ref).collect();
+            let (open, close) = delimiters(cases.delim(styles));
+            let delims = if cases.reverse(styles) { ("", close) } else { (open, "") };
+            self.table(&rows, Some(delims), styles)?;
+        } else if let Some(accent) = elem.to::<AccentElem>() {
+            let mark = match accent.accent().into_value() {
+                Value::Str(mark) => mark,
+                _ => Default::default(),
+            };
+            self.out.push_str("<mover accent=\"true\">");
+            self.row(accent.base(), styles)?;
+            self.out.push_str(&eco_format!("<mo>{}</mo></mover>", escape(&mark)));
+        } else if let Some(underline) = elem.to::<UnderlineElem>() {
+            self.under(underline.body(), '_', None, styles)?;
+        } else if let Some(overline) = elem.to::<OverlineElem>() {
+            self.over(overline.body(), '‾', None, styles)?;
+        } else if let Some(brace) = elem.to::<UnderbraceElem>() {
+            self.under(brace.body(), '⏟', brace.annotation(styles), styles)?;
+        } else if let Some(brace) = elem.to::<OverbraceElem>() {
+            self.over(brace.body(), '⏞', brace.annotation(styles), styles)?;
+        } else if let Some(bracket) = elem.to::<UnderbracketElem>() {
+            self.under(bracket.body(), '⎵', bracket.annotation(styles), styles)?;
+        } else if let Some(bracket) = elem.to::<OverbracketElem>() {
+            self.over(bracket.body(), '⎴', bracket.annotation(styles), styles)?;
+        } else if let Some(cancel) = elem.to::<CancelElem>() {
+            let notation = if cancel.cross(styles) {
+                "updiagonalstrike downdiagonalstrike"
+            } else if cancel.inverted(styles) {
+                "downdiagonalstrike"
+            } else {
+                "updiagonalstrike"
+            };
+            self.out.push_str(&eco_format!("<menclose notation=\"{notation}\">"));
+            self.row(cancel.body(), styles)?;
+            self.out.push_str("</menclose>");
+        } else if let Some(style) = elem.to::<MathStyleElem>() {
+            self.style(style, styles)?;
+        } else if let Some(class) = elem.to::<ClassElem>() {
+            self.row(class.body(), styles)?;
+        } else if let Some(limits) = elem.to::<LimitsElem>() {
+            self.row(limits.body(), styles)?;
+        } else if let Some(scripts) = elem.to::<ScriptsElem>() {
+            self.row(scripts.body(), styles)?;
+        } else if let Some(primes) = elem.to::<PrimesElem>() {
+            let primes = match primes.count() {
+                1 => "′",
+                2 => "″",
+                3 => "‴",
+                _ => "⁗",
+            };
+            self.out.push_str(&eco_format!("<mo>{primes}</mo>"));
+        } else if elem.is::<LinebreakElem>() {
+            self.out.push_str("<mspace linebreak=\"newline\"/>");
+        } else {
+            self.out.push_str("<mtext>");
+            self.out.push_str(&escape(&elem.plain_text()));
+            self.out.push_str("</mtext>");
+        }
+        Ok(())
+    }
+
+    /// Write a piece of text as an identifier, number, operator, or text.
+    fn text(&mut self, text: &str) {
+        let mut chars = text.chars();
+        let tag = if text.chars().all(|c| c.is_ascii_digit() || c == '.')
+            && text.starts_with(|c: char| c.is_ascii_digit())
+        {
+            "mn"
+        } else if chars.next().map_or(false, char::is_alphabetic)
+            && chars.next().is_none()
+        {
+            "mi"
+        } else if text.chars().any(char::is_alphanumeric) {
+            "mtext"
+        } else {
+            "mo"
+        };
+        self.out.push_str(&eco_format!("<{tag}>{}</{tag}>", escape(text)));
+    }
+
+    /// Write an element with attachments.
+    fn attach(&mut self, attach: &AttachElem, styles: StyleChain) -> SourceResult<()> {
+        let base = attach.base();
+        let (mut t, mut b) = (attach.t(styles), attach.b(styles));
+        let (mut tr, mut br) = (attach.tr(styles), attach.br(styles));
+        let (tl, bl) = (attach.tl(styles), attach.bl(styles));
+
+        // Attachments above and below are limits if the base asks for them.
+        let limits = base.is::<LimitsElem>()
+            || (self.block && base.to::<OpElem>().map_or(false, |op| op.limits(styles)))
+            || (self.block && is_large_op(base));
+
+        if !limits {
+            tr = t.take().or(tr);
+            br = b.take().or(br);
+        }
+
+        let scripted = [&tr, &br, &tl, &bl].iter().any(|script| script.is_some());
+        let tag = match (&tr, &br) {
+            _ if tl.is_some() || bl.is_some() => "mmultiscripts",
+            (Some(_), Some(_)) => "msubsup",
+            (Some(_), None) => "msup",
+            _ => "msub",
+        };
+
+        if scripted {
+            self.out.push_str(&eco_format!("<{tag}>"));
+        }
+
+        let tag_limits = match (&t, &b) {
+            (Some(_), Some(_)) => Some("munderover"),
+            (Some(_), None) => Some("mover"),
+            (None, Some(_)) => Some("munder"),
+            (None, None) => None,
+        };
+
+        match tag_limits {
+            Some(tag) => {
+                self.out.push_str(&eco_format!("<{tag}>"));
+                self.row(base, styles)?;
+                for script in [&b, &t].into_iter().flatten() {
+                    self.row(script, styles)?;
+                }
+                self.out.push_str(&eco_format!("</{tag}>"));
+            }
+            None => self.row(base, styles)?,
+        }
+
+        if !scripted {
+            return Ok(());
+        }
+
+        if tag == "mmultiscripts" {
+            self.script(br.as_ref(), styles)?;
+            self.script(tr.as_ref(), styles)?;
+            self.out.push_str("<mprescripts/>");
+            self.script(bl.as_ref(), styles)?;
+            self.script(tl.as_ref(), styles)?;
+        } else {
+            for script in [&br, &tr].into_iter().flatten() {
+                self.row(script, styles)?;
+            }
+        }
+
+        self.out.push_str(&eco_format!("</{tag}>"));
+        Ok(())
+    }
+
+    /// Write a script in a `<mmultiscripts>` element.
+    fn script(
+        &mut self,
+        script: Option<&Content>,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        match script {
+            Some(script) => self.row(script, styles)?,
+            None => self.out.push_str("<none/>"),
+        }
+        Ok(())
+    }
+
+    /// Write rows of cells as a table, optionally with delimiters.
+    fn table(
+        &mut self,
+        rows: &[&[Content]],
+        delims: Option<(&str, &str)>,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        let (open, close) = delims.unwrap_or_default();
+        self.out.push_str("<mrow>");
+        if !open.is_empty() {
+            self.out.push_str(&eco_format!("<mo>{open}</mo>"));
+        }
+        self.out.push_str("<mtable>");
+        for row in rows {
+            self.out.push_str("<mtr>");
+            for cell in row.iter() {
+                self.out.push_str("<mtd>");
+                self.row(cell, styles)?;
+                self.out.push_str("</mtd>");
+            }
+            self.out.push_str("</mtr>");
+        }
+        self.out.push_str("</mtable>");
+        if !close.is_empty() {
+            self.out.push_str(&eco_format!("<mo>{close}</mo>"));
+        }
+        self.out.push_str("</mrow>");
+        Ok(())
+    }
+
+    /// Write content with a line or brace below it.
+    fn under(
+        &mut self,
+        body: &Content,
+        mark: char,
+        annotation: Option<Content>,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        if annotation.is_some() {
+            self.out.push_str("<munder>");
+        }
+        self.out.push_str("<munder>");
+        self.row(body, styles)?;
+        self.out.push_str(&eco_format!("<mo>{mark}</mo></munder>"));
+        if let Some(annotation) = annotation {
+            self.row(&annotation, styles)?;
+            self.out.push_str("</munder>");
+        }
+        Ok(())
+    }
+
+    /// Write content with a line or brace above it.
+    fn over(
+        &mut self,
+        body: &Content,
+        mark: char,
+        annotation: Option<Content>,
+        styles: StyleChain,
+    ) -> SourceResult<()> {
+        if annotation.is_some() {
+            self.out.push_str("<mover>");
+        }
+        self.out.push_str("<mover>");
+        self.row(body, styles)?;
+        self.out.push_str(&eco_format!("<mo>{mark}</mo></mover>"));
+        if let Some(annotation) = annotation {
+            self.row(&annotation, styles)?;
+            self.out.push_str("</mover>");
+        }
+        Ok(())
+    }
+
+    /// Write content in a different math style.
+    fn style(&mut self, elem: &MathStyleElem, styles: StyleChain) -> SourceResult<()> {
+        let variant = match (elem.variant(styles), elem.bold(styles), elem.italic(styles))
+        {
+            (Some(MathVariant::Bb), ..) => Some("double-struck"),
+            (Some(MathVariant::Cal), Some(true), _) => Some("bold-script"),
+            (Some(MathVariant::Cal), ..) => Some("script"),
+            (Some(MathVariant::Frak), Some(true), _) => Some("bold-fraktur"),
+            (Some(MathVariant::Frak), ..) => Some("fraktur"),
+            (Some(MathVariant::Mono), ..) => Some("monospace"),
+            (Some(MathVariant::Sans), Some(true), _) => Some("bold-sans-serif"),
+            (Some(MathVariant::Sans), ..) => Some("sans-serif"),
+            (_, Some(true), Some(true)) => Some("bold-italic"),
+            (_, Some(true), _) => Some("bold"),
+            (_, _, Some(false)) | (Some(MathVariant::Serif), _, None) => Some("normal"),
+            (_, _, Some(true)) => Some("italic"),
+            _ => None,
+        };
+
+        match variant {
+            Some(variant) => {
+                self.out.push_str(&eco_format!("<mstyle mathvariant=\"{variant}\">"));
+                self.row(elem.body(), styles)?;
+                self.out.push_str("</mstyle>");
+            }
+            None => self.row(elem.body(), styles)?,
+        }
+        Ok(())
+    }
+}
+
+/// Whether a piece of math content has no MathML counterpart. Spacing is
+/// determined by the browser.
+fn is_ignorable(content: &Content) -> bool {
+    content.is::<SpaceElem>()
+        || content.is::<AlignPointElem>()
+        || content.is::<typst::layout::HElem>()
+        || content.is::<typst::introspection::MetaElem>()
+        || content.to::<TextElem>().map_or(false, |text| text.text().is_empty())
+}
+
+/// Whether content is a large operator that takes limits in display style.
+fn is_large_op(content: &Content) -> bool {
+    content.to::<TextElem>().map_or(false, |text| {
+        matches!(
+            text.text().as_str(),
+            "∑" | "∏"
+                | "∐"
+                | "⋀"
+                | "⋁"
+                | "⋂"
+                | "⋃"
+                | "⨀"
+                | "⨁"
+                | "⨂"
+                | "⨄"
+                | "⨆"
+        )
+    })
+}
+
+/// The opening and closing characters of a delimiter.
+fn delimiters(delim: Delimiter) -> (&'static str, &'static str) {
+    match delim {
+        Delimiter::Paren => ("(", ")"),
+        Delimiter::Bracket => ("[", "]"),
+        Delimiter::Brace => ("{", "}"),
+        Delimiter::Bar => ("|", "|"),
+        Delimiter::DoubleBar => ("‖", "‖"),
+    }
+}
@@ -423,11 +423,24 @@ fn field_access_completions(ctx: &mut CompletionContext, value: &Value) {
         }
         Value::Plugin(plugin) => {
             for name in plugin.iter() {
+                // Suggest the parameters declared in the plugin's manifest.
+                let signature = plugin.signature(name);
+                let apply = signature.map(|sig| {
+                    if sig.params.is_empty() {
+                        return eco_format!("{name}()${{}}");
+                    }
+                    let params: Vec<_> = sig
+                        .params
+                        .iter()
+                        .map(|param| eco_format!("${{{param}}}"))
+                        .collect();
+                    eco_format!("{name}({})", params.join(", "))
+                });
                 ctx.completions.push(Completion {
                     kind: CompletionKind::Func,
                     label: name.clone(),
-                    apply: None,
-                    detail: None,
+                    apply,
+                    detail: signature.and_then(|sig| sig.docs.clone()),
                 })
             }
         }
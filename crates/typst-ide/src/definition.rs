@@ -0,0 +1,254 @@
+use std::ops::Range;
+
+use typst::foundations::{Label, Value};
+use typst::model::Document;
+use typst::syntax::ast::AstNode;
+use typst::syntax::{ast, FileId, LinkedNode, Source, Span, SyntaxKind, SyntaxNode};
+use typst::World;
+
+use crate::analyze::analyze_expr;
+
+/// Find the definition of the item under the cursor.
+///
+/// Identifiers resolve to the binding that introduces them, following imports
+/// into other files. References resolve to the label they refer to.
+///
+/// Passing a `document` (from a previous compilation) is optional, but allows
+/// to find labels that are attached in other files.
+pub fn definition(
+    world: &dyn World,
+    document: Option<&Document>,
+    source: &Source,
+    cursor: usize,
+) -> Option<Definition> {
+    let leaf = LinkedNode::new(source.root()).leaf_at(cursor)?;
+    match leaf.kind() {
+        SyntaxKind::Ident | SyntaxKind::MathIdent => {
+            ident_definition(world, source, &leaf)
+        }
+        SyntaxKind::RefMarker => {
+            label_definition(world, document, source, leaf.text().trim_start_matches('@'))
+        }
+        SyntaxKind::Label => {
+            let name = leaf.text().trim_start_matches('<').trim_end_matches('>');
+            label_definition(world, document, source, name)
+        }
+        _ => None,
+    }
+}
+
+/// Where an item is defined.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Definition {
+    /// The file that contains the definition.
+    pub id: FileId,
+    /// The byte range of the defining name within the file.
+    pub range: Range<usize>,
+}
+
+impl Definition {
+    /// The definition made up by a node in a source file.
+    fn new(source: &Source, node: &SyntaxNode) -> Option<Self> {
+        Some(Self { id: source.id(), range: source.range(node.span())? })
+    }
+
+    /// The definition made up by the node a span points to.
+    fn from_span(world: &dyn World, span: Span) -> Option<Self> {
+        let source = world.source(span.id()?).ok()?;
+        let node = source.find(span)?;
+
+        // Point to the name of a named closure.
+        let node = match node.cast::<ast::Closure>().and_then(|closure| closure.name()) {
+            Some(name) => name.to_untyped().clone(),
+            None => node.get().clone(),
+        };
+
+        Self::new(&source, &node)
+    }
+}
+
+/// Find the definition of an identifier.
+fn ident_definition(
+    world: &dyn World,
+    source: &Source,
+    leaf: &LinkedNode,
+) -> Option<Definition> {
+    // The field of a field access isn't bound in the current scope.
+    let is_field =
+        leaf.parent_kind() == Some(SyntaxKind::FieldAccess) && leaf.index() > 0;
+    if !is_field {
+        if let Some(definition) = find_binding(world, source, leaf, leaf.text()) {
+            return Some(definition);
+        }
+    }
+
+    // Functions know where they are defined.
+    let node = if is_field { leaf.parent()? } else { leaf };
+    analyze_expr(world, node).into_iter().find_map(|value| match value {
+        Value::Func(func) if !func.span().is_detached() => {
+            Definition::from_span(world, func.span())
+        }
+        _ => None,
+    })
+}
+
+/// Find the binding of a name that is visible at a node.
+///
+/// Walks up the syntax tree and looks at the parameters of enclosing closures
+/// and loops as well as at preceding bindings and imports in enclosing blocks.
+fn find_binding(
+    world: &dyn World,
+    source: &Source,
+    node: &LinkedNode,
+    name: &str,
+) -> Option<Definition> {
+    let mut child = node.clone();
+    while let Some(parent) = child.parent() {
+        let parent = parent.clone();
+
+        if let Some(closure) = parent.cast::<ast::Closure>() {
+            let params = closure.params().children().flat_map(|param| match param {
+                ast::Param::Pos(pattern) => pattern.idents(),
+                ast::Param::Named(named) => vec![named.name()],
+                ast::Param::Sink(spread) => spread.name().into_iter().collect(),
+            });
+            if let Some(ident) =
+                closure.name().into_iter().chain(params).find(|i| i.as_str() == name)
+            {
+                return Definition::new(source, ident.to_untyped());
+            }
+        }
+
+        if let Some(for_loop) = parent.cast::<ast::ForLoop>() {
+            if for_loop.body().span() == child.span() {
+                if let Some(ident) =
+                    for_loop.pattern().idents().into_iter().find(|i| i.as_str() == name)
+                {
+                    return Definition::new(source, ident.to_untyped());
+                }
+            }
+        }
+
+        for sibling in parent.children().take(child.index()).rev() {
+            if let Some(definition) = binding_in(world, source, sibling.get(), name, 0) {
+                return Some(definition);
+            }
+        }
+
+        child = parent;
+    }
+
+    None
+}
+
+/// The maximum depth of imports to follow.
+const MAX_IMPORT_DEPTH: usize = 8;
+
+/// Find the definition of a name introduced by a node that is a let binding
+/// or an import.
+fn binding_in(
+    world: &dyn World,
+    source: &Source,
+    node: &SyntaxNode,
+    name: &str,
+    depth: usize,
+) -> Option<Definition> {
+    if let Some(binding) = node.cast::<ast::LetBinding>() {
+        let ident = binding.kind().idents().into_iter().find(|i| i.as_str() == name)?;
+        return Definition::new(source, ident.to_untyped());
+    }
+
+    let import = node.cast::<ast::ModuleImport>()?;
+    match import.imports() {
+        Some(ast::Imports::Items(items)) => {
+            let item = items.iter().find(|item| item.bound_name().as_str() == name)?;
+            imported_source(world, source, import, depth)
+                .and_then(|module| {
+                    top_level_binding(world, &module, &item.original_name(), depth + 1)
+                })
+                .or_else(|| Definition::new(source, item.bound_name().to_untyped()))
+        }
+        Some(ast::Imports::Wildcard) => {
+            let module = imported_source(world, source, import, depth)?;
+            top_level_binding(world, &module, name, depth + 1)
+        }
+        None => {
+            let new_name = import.new_name()?;
+            if new_name.as_str() != name {
+                return None;
+            }
+            Definition::new(source, new_name.to_untyped())
+        }
+    }
+}
+
+/// Find the binding of a name at the top level of a file.
+fn top_level_binding(
+    world: &dyn World,
+    source: &Source,
+    name: &str,
+    depth: usize,
+) -> Option<Definition> {
+    if depth > MAX_IMPORT_DEPTH {
+        return None;
+    }
+
+    source
+        .root()
+        .children()
+        .rev()
+        .find_map(|child| binding_in(world, source, child, name, depth))
+}
+
+/// Load the file a module import refers to.
+fn imported_source(
+    world: &dyn World,
+    source: &Source,
+    import: ast::ModuleImport,
+    depth: usize,
+) -> Option<Source> {
+    if depth > MAX_IMPORT_DEPTH {
+        return None;
+    }
+
+    let ast::Expr::Str(path) = import.source() else { return None };
+    let path = path.get();
+    if path.starts_with('@') {
+        return None;
+    }
+
+    world.source(source.id().join(&path)).ok()
+}
+
+/// Find the definition of a label.
+fn label_definition(
+    world: &dyn World,
+    document: Option<&Document>,
+    source: &Source,
+    name: &str,
+) -> Option<Definition> {
+    // The label may be attached in another file.
+    let span = document.and_then(|document| {
+        let elem = document.introspector.query_label(Label::new(name)).ok()?;
+        Some(elem.span())
+    });
+
+    let source = match span.and_then(Span::id) {
+        Some(id) if id != source.id() => world.source(id).ok()?,
+        _ => source.clone(),
+    };
+
+    find_label(&source, source.root(), name)
+        .or_else(|| Some(Definition { id: source.id(), range: source.range(span?)? }))
+}
+
+/// Find a label node in a subtree.
+fn find_label(source: &Source, node: &SyntaxNode, name: &str) -> Option<Definition> {
+    if let Some(label) = node.cast::<ast::Label>() {
+        if label.get() == name {
+            return Definition::new(source, node);
+        }
+    }
+
+    node.children().find_map(|child| find_label(source, child, name))
+}
@@ -2,12 +2,16 @@
 
 mod analyze;
 mod complete;
+mod definition;
 mod jump;
+mod symbols;
 mod tooltip;
 
 pub use self::analyze::analyze_labels;
 pub use self::complete::{autocomplete, Completion, CompletionKind};
+pub use self::definition::{definition, Definition};
 pub use self::jump::{jump_from_click, jump_from_cursor, Jump};
+pub use self::symbols::{document_symbols, DocumentSymbol, SymbolKind};
 pub use self::tooltip::{tooltip, Tooltip};
 
 use std::fmt::Write;
@@ -0,0 +1,131 @@
+use std::ops::Range;
+
+use ecow::EcoString;
+use typst::syntax::ast::AstNode;
+use typst::syntax::{ast, LinkedNode, Source, SyntaxKind};
+
+/// List the symbols of a source file for display in an outline.
+///
+/// Headings are nested by their level. Top-level bindings are placed below
+/// the heading they follow.
+pub fn document_symbols(source: &Source) -> Vec<DocumentSymbol> {
+    let mut collector = Collector { stack: vec![], symbols: vec![] };
+    collector.visit(&LinkedNode::new(source.root()), true);
+    collector.finish()
+}
+
+/// A symbol in a source file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DocumentSymbol {
+    /// The name of the symbol.
+    pub name: EcoString,
+    /// The kind of the symbol.
+    pub kind: SymbolKind,
+    /// The byte range of the whole syntactical structure.
+    pub range: Range<usize>,
+    /// The byte range of the symbol's name.
+    pub selection_range: Range<usize>,
+    /// The symbols nested in this one.
+    pub children: Vec<DocumentSymbol>,
+}
+
+/// A kind of symbol.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SymbolKind {
+    /// A heading.
+    Heading,
+    /// A variable binding.
+    Variable,
+    /// A function binding.
+    Function,
+    /// A label.
+    Label,
+}
+
+/// Collects symbols in document order.
+struct Collector {
+    /// Open headings with their level.
+    stack: Vec<(usize, DocumentSymbol)>,
+    /// Finished top-level symbols.
+    symbols: Vec<DocumentSymbol>,
+}
+
+impl Collector {
+    /// Collect the symbols in a node and its descendants.
+    fn visit(&mut self, node: &LinkedNode, top_level: bool) {
+        if let Some(heading) = node.cast::<ast::Heading>() {
+            let level = heading.level().get();
+            let name = heading.body().to_untyped().clone().into_text();
+            self.close(level);
+            self.stack
+                .push((level, symbol(name.trim().into(), SymbolKind::Heading, node)));
+            return;
+        }
+
+        if let Some(binding) = node.cast::<ast::LetBinding>() {
+            if top_level {
+                let kind = match binding.kind() {
+                    ast::LetBindingKind::Closure(_) => SymbolKind::Function,
+                    ast::LetBindingKind::Normal(_) => SymbolKind::Variable,
+                };
+                for ident in binding.kind().idents() {
+                    let mut symbol = symbol(ident.get().clone(), kind, node);
+                    if let Some(name) = node.find(ident.span()) {
+                        symbol.selection_range = name.range();
+                    }
+                    self.push(symbol);
+                }
+            }
+            return;
+        }
+
+        if node.kind() == SyntaxKind::Label {
+            let name = node.text().trim_start_matches('<').trim_end_matches('>');
+            self.push(symbol(name.into(), SymbolKind::Label, node));
+            return;
+        }
+
+        // Bindings in nested blocks are local.
+        let top_level = top_level && node.kind() == SyntaxKind::Markup;
+        for child in node.children() {
+            self.visit(&child, top_level);
+        }
+    }
+
+    /// Add a symbol below the innermost open heading, extending the heading's
+    /// range to cover it.
+    fn push(&mut self, symbol: DocumentSymbol) {
+        match self.stack.last_mut() {
+            Some((_, heading)) => {
+                heading.range.end = heading.range.end.max(symbol.range.end);
+                heading.children.push(symbol);
+            }
+            None => self.symbols.push(symbol),
+        }
+    }
+
+    /// Close all open headings at or below the given level.
+    fn close(&mut self, level: usize) {
+        while self.stack.last().map_or(false, |&(l, _)| l >= level) {
+            let (_, heading) = self.stack.pop().unwrap();
+            self.push(heading);
+        }
+    }
+
+    /// Close all open headings and return the symbols.
+    fn finish(mut self) -> Vec<DocumentSymbol> {
+        self.close(1);
+        self.symbols
+    }
+}
+
+/// Create a symbol for a node.
+fn symbol(name: EcoString, kind: SymbolKind, node: &LinkedNode) -> DocumentSymbol {
+    DocumentSymbol {
+        name,
+        kind,
+        range: node.range(),
+        selection_range: node.range(),
+        children: vec![],
+    }
+}
@@ -419,7 +419,7 @@ fn rewrite_fn_item(item: &syn::ItemFn) -> syn::ItemFn {
         Some(input)
     });
     let mut item = item.clone();
-    item.attrs.clear();
+    item.attrs.retain(|attr| attr.path().is_ident("allow"));
     item.sig.inputs = parse_quote! { #(#inputs),* };
     item
 }
@@ -27,6 +27,16 @@ static GRAY_ICC_DEFLATED: Lazy<Vec<u8>> =
 static OKLAB_DEFLATED: Lazy<Vec<u8>> =
     Lazy::new(|| deflate(minify(include_str!("postscript/oklab.ps")).as_bytes()));
 
+/// Write the sRGB ICC profile for use as the destination profile of a PDF/A
+/// output intent.
+pub fn write_output_intent_profile(chunk: &mut Chunk, id: Ref) {
+    chunk
+        .icc_profile(id, &SRGB_ICC_DEFLATED)
+        .n(3)
+        .range([0.0, 1.0, 0.0, 1.0, 0.0, 1.0])
+        .filter(Filter::FlateDecode);
+}
+
 /// The color spaces present in the PDF document
 #[derive(Default)]
 pub struct ColorSpaces {
@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+
+use pdf_writer::{Finish, Name, Str, TextStr};
+use typst::diag::{bail, SourceResult};
+use typst::foundations::{NativeElement, StyleChain};
+use typst::model::pdf::{EmbedElem, EmbeddedFileRelationship};
+
+use crate::{deflate, PdfContext};
+
+/// Write the files embedded with `pdf.embed` and remember their file
+/// specifications so that the catalog can reference them.
+#[tracing::instrument(skip_all)]
+pub(crate) fn write_embedded_files(ctx: &mut PdfContext) -> SourceResult<()> {
+    let elements = ctx.document.introspector.query(&EmbedElem::elem().select());
+    let mut files = BTreeMap::new();
+
+    for element in elements.iter() {
+        let embed = element.to::<EmbedElem>().unwrap();
+        let name = embed.file_name();
+        if files.contains_key(name) {
+            bail!(embed.span(), "a file named `{name}` is already embedded");
+        }
+
+        let spec_ref = write_embedded_file(ctx, embed);
+        files.insert(name.into(), spec_ref);
+    }
+
+    ctx.embedded_files = files;
+    Ok(())
+}
+
+/// Write a single embedded file and its file specification.
+fn write_embedded_file(ctx: &mut PdfContext, embed: &EmbedElem) -> pdf_writer::Ref {
+    // The fields used here are materialized during realization.
+    let styles = StyleChain::default();
+    let data = embed.data();
+    let compressed = deflate(data);
+
+    let file_ref = ctx.alloc.bump();
+    let mut file = ctx.pdf.embedded_file(file_ref, &compressed);
+    file.filter(pdf_writer::Filter::FlateDecode);
+    if let Some(mime_type) = embed.mime_type(styles) {
+        file.subtype(Name(mime_type.as_bytes()));
+    }
+    file.params().size(data.len() as i32);
+    file.finish();
+
+    let name = embed.file_name();
+    let spec_ref = ctx.alloc.bump();
+    let mut spec = ctx.pdf.file_spec(spec_ref);
+    spec.path(Str(name.as_bytes()));
+    spec.unic_file(TextStr(name));
+    spec.insert(Name(b"EF"))
+        .dict()
+        .pair(Name(b"F"), file_ref)
+        .pair(Name(b"UF"), file_ref);
+    if let Some(description) = embed.description(styles) {
+        spec.description(TextStr(&description));
+    }
+
+    let relationship = match embed.relationship(styles) {
+        Some(EmbeddedFileRelationship::Source) => "Source",
+        Some(EmbeddedFileRelationship::Data) => "Data",
+        Some(EmbeddedFileRelationship::Alternative) => "Alternative",
+        Some(EmbeddedFileRelationship::Supplement) => "Supplement",
+        None => "Unspecified",
+    };
+    spec.pair(Name(b"AFRelationship"), Name(relationship.as_bytes()));
+    spec.finish();
+
+    spec_ref
+}
@@ -0,0 +1,245 @@
+use ecow::EcoString;
+use pdf_writer::types::{AnnotationFlags, LineCapStyle, LineJoinStyle};
+use pdf_writer::writers::Annotation;
+use pdf_writer::{Content, Finish, Name, Rect, Ref, Str, TextStr};
+use typst::diag::{bail, SourceResult};
+use typst::model::form::{Widget, WidgetKind};
+
+use crate::PdfContext;
+
+/// The name of the on state of a checkbox.
+const CHECKED: &str = "Yes";
+
+/// The name of the off state of a button.
+const OFF: &str = "Off";
+
+/// A form field, which groups the widgets with the same name.
+pub(crate) struct Field {
+    /// The field's name.
+    name: EcoString,
+    /// The indirect reference of the field dictionary.
+    id: Ref,
+    /// The kind of the field's first widget. All widgets of a field must be
+    /// of the same kind.
+    kind: WidgetKind,
+    /// The on state of the field's selected button, if any.
+    selected: Option<EcoString>,
+    /// The field's widget annotations.
+    kids: Vec<Ref>,
+}
+
+/// Write a widget annotation and add it to its field.
+pub(crate) fn write_widget(
+    ctx: &mut PdfContext,
+    widget: &Widget,
+    rect: Rect,
+    page: usize,
+    annotation_ref: Ref,
+    struct_parent: Option<i32>,
+) -> SourceResult<()> {
+    if let WidgetKind::Radio { option, .. } = &widget.kind {
+        if option == OFF {
+            bail!(
+                widget.span,
+                "radio button option cannot be named `{OFF}`";
+                hint: "`{OFF}` is the state of unselected buttons in PDF"
+            );
+        }
+    }
+
+    let index = match ctx.fields.iter().position(|field| field.name == widget.name) {
+        Some(index) => {
+            let kind = &ctx.fields[index].kind;
+            if std::mem::discriminant(kind) != std::mem::discriminant(&widget.kind) {
+                bail!(
+                    widget.span,
+                    "field `{}` is already a {}", widget.name, describe(kind);
+                    hint: "widgets with the same name belong to the same field"
+                );
+            }
+            index
+        }
+        None => {
+            ctx.fields.push(Field {
+                name: widget.name.clone(),
+                id: ctx.alloc.bump(),
+                kind: widget.kind.clone(),
+                selected: None,
+                kids: vec![],
+            });
+            ctx.fields.len() - 1
+        }
+    };
+
+    // Buttons need appearances for their on and off states.
+    let state = match &widget.kind {
+        WidgetKind::Checkbox { checked } => Some((EcoString::from(CHECKED), *checked)),
+        WidgetKind::Radio { option, selected } => Some((option.clone(), *selected)),
+        WidgetKind::Text { .. } | WidgetKind::Dropdown { .. } => None,
+    };
+
+    let appearances = state.as_ref().map(|_| {
+        let on = ctx.alloc.bump();
+        let off = ctx.alloc.bump();
+        write_appearance(ctx, on, rect, Some(&widget.kind));
+        write_appearance(ctx, off, rect, None);
+        (on, off)
+    });
+
+    let field = &mut ctx.fields[index];
+    field.kids.push(annotation_ref);
+    if let Some((name, true)) = &state {
+        field.selected.get_or_insert_with(|| name.clone());
+    }
+
+    let mut annotation = ctx.pdf.indirect(annotation_ref).start::<Annotation>();
+    annotation.pair(Name(b"Subtype"), Name(b"Widget"));
+    annotation.rect(rect).flags(AnnotationFlags::PRINT);
+    annotation.pair(Name(b"P"), ctx.page_refs[page]);
+    annotation.pair(Name(b"Parent"), field.id);
+    if let Some(key) = struct_parent {
+        annotation.struct_parent(key);
+    }
+
+    if let (Some((name, on)), Some((on_ref, off_ref))) = (state, appearances) {
+        let as_ = if on { name.as_str() } else { OFF };
+        annotation.pair(Name(b"AS"), Name(as_.as_bytes()));
+        let mut normal = annotation.insert(Name(b"AP")).dict();
+        let mut states = normal.insert(Name(b"N")).dict();
+        states.pair(Name(name.as_bytes()), on_ref);
+        states.pair(Name(OFF.as_bytes()), off_ref);
+    }
+
+    Ok(())
+}
+
+/// Write the field dictionaries and the interactive form dictionary.
+///
+/// Returns the reference of the interactive form dictionary if the document
+/// contains any form fields.
+pub(crate) fn write_fields(ctx: &mut PdfContext) -> Option<Ref> {
+    if ctx.fields.is_empty() {
+        return None;
+    }
+
+    for field in &ctx.fields {
+        let mut dict = ctx.pdf.indirect(field.id).dict();
+        dict.pair(Name(b"T"), TextStr(&field.name));
+        dict.insert(Name(b"Kids")).array().items(field.kids.iter().copied());
+
+        match &field.kind {
+            WidgetKind::Text { value, multiline, max_length } => {
+                dict.pair(Name(b"FT"), Name(b"Tx"));
+                dict.pair(Name(b"V"), TextStr(value));
+                dict.pair(Name(b"DV"), TextStr(value));
+                if *multiline {
+                    dict.pair(Name(b"Ff"), 1 << 12);
+                }
+                if let Some(max_length) = max_length {
+                    dict.pair(Name(b"MaxLen"), max_length.get() as i32);
+                }
+            }
+            WidgetKind::Checkbox { .. } | WidgetKind::Radio { .. } => {
+                dict.pair(Name(b"FT"), Name(b"Btn"));
+                if matches!(field.kind, WidgetKind::Radio { .. }) {
+                    // The radio and no-toggle-to-off flags.
+                    dict.pair(Name(b"Ff"), (1 << 15) | (1 << 14));
+                }
+                let value = field.selected.as_deref().unwrap_or(OFF);
+                dict.pair(Name(b"V"), Name(value.as_bytes()));
+                dict.pair(Name(b"DV"), Name(value.as_bytes()));
+            }
+            WidgetKind::Dropdown { options, value } => {
+                dict.pair(Name(b"FT"), Name(b"Ch"));
+                // The combo flag.
+                dict.pair(Name(b"Ff"), 1 << 17);
+                dict.insert(Name(b"Opt"))
+                    .array()
+                    .items(options.iter().map(|option| TextStr(option)));
+                if let Some(value) = value {
+                    dict.pair(Name(b"V"), TextStr(value));
+                    dict.pair(Name(b"DV"), TextStr(value));
+                }
+            }
+        }
+    }
+
+    let form_ref = ctx.alloc.bump();
+    let mut form = ctx.pdf.indirect(form_ref).dict();
+    form.insert(Name(b"Fields"))
+        .array()
+        .items(ctx.fields.iter().map(|field| field.id));
+
+    // Text fields and dropdowns don't come with appearances, so the reader
+    // has to generate them from their values.
+    let generated = ctx.fields.iter().any(|field| {
+        matches!(field.kind, WidgetKind::Text { .. } | WidgetKind::Dropdown { .. })
+    });
+
+    if generated {
+        form.pair(Name(b"NeedAppearances"), true);
+        form.pair(Name(b"DA"), Str(b"/Helv 0 Tf 0 g"));
+        let mut resources = form.insert(Name(b"DR")).dict();
+        let mut fonts = resources.insert(Name(b"Font")).dict();
+        fonts
+            .insert(Name(b"Helv"))
+            .dict()
+            .pair(Name(b"Type"), Name(b"Font"))
+            .pair(Name(b"Subtype"), Name(b"Type1"))
+            .pair(Name(b"BaseFont"), Name(b"Helvetica"));
+        fonts.finish();
+        resources.finish();
+    }
+
+    form.finish();
+    Some(form_ref)
+}
+
+/// A human-readable name for a kind of field.
+fn describe(kind: &WidgetKind) -> &'static str {
+    match kind {
+        WidgetKind::Text { .. } => "text field",
+        WidgetKind::Checkbox { .. } => "checkbox",
+        WidgetKind::Radio { .. } => "radio group",
+        WidgetKind::Dropdown { .. } => "dropdown",
+    }
+}
+
+/// Write the appearance of a button's on state (with a check mark or, for a
+/// radio button, a dot) or, if no kind is given, its off state (with
+/// nothing).
+fn write_appearance(ctx: &mut PdfContext, id: Ref, rect: Rect, on: Option<&WidgetKind>) {
+    let w = (rect.x2 - rect.x1).abs();
+    let h = (rect.y2 - rect.y1).abs();
+    let s = w.min(h);
+
+    let mut content = Content::new();
+    match on {
+        Some(WidgetKind::Checkbox { .. }) => {
+            content.set_stroke_gray(0.0);
+            content.set_line_width(0.12 * s);
+            content.set_line_cap(LineCapStyle::RoundCap);
+            content.set_line_join(LineJoinStyle::RoundJoin);
+            content.move_to(0.5 * w - 0.28 * s, 0.5 * h);
+            content.line_to(0.5 * w - 0.08 * s, 0.5 * h - 0.22 * s);
+            content.line_to(0.5 * w + 0.28 * s, 0.5 * h + 0.26 * s);
+            content.stroke();
+        }
+        Some(WidgetKind::Radio { .. }) => {
+            // Approximate the dot with four cubic Bézier curves.
+            let (cx, cy, r) = (0.5 * w, 0.5 * h, 0.25 * s);
+            let k = 0.5523 * r;
+            content.set_fill_gray(0.0);
+            content.move_to(cx + r, cy);
+            content.cubic_to(cx + r, cy + k, cx + k, cy + r, cx, cy + r);
+            content.cubic_to(cx - k, cy + r, cx - r, cy + k, cx - r, cy);
+            content.cubic_to(cx - r, cy - k, cx - k, cy - r, cx, cy - r);
+            content.cubic_to(cx + k, cy - r, cx + r, cy - k, cx + r, cy);
+            content.fill_nonzero();
+        }
+        _ => {}
+    }
+
+    let data = content.finish();
+    ctx.pdf.form_xobject(id, &data).bbox(Rect::new(0.0, 0.0, w, h));
+}
@@ -1,13 +1,18 @@
 //! Exporting into PDF documents.
 
 mod color;
+mod embed;
 mod extg;
 mod font;
+mod form;
 mod gradient;
 mod image;
 mod outline;
 mod page;
 mod pattern;
+mod standard;
+mod tags;
+mod viewer;
 
 use std::cmp::Eq;
 use std::collections::{BTreeMap, HashMap};
@@ -16,59 +21,113 @@ use std::sync::Arc;
 
 use base64::Engine;
 use ecow::{eco_format, EcoString};
-use pdf_writer::types::Direction;
-use pdf_writer::{Finish, Name, Pdf, Ref, TextStr};
+use pdf_writer::types::{Direction, OutputIntentSubtype};
+use pdf_writer::writers::OutputIntent;
+use pdf_writer::{Finish, Name, Pdf, Ref, Str, TextStr};
+use typst::diag::SourceResult;
 use typst::foundations::Datetime;
-use typst::layout::{Abs, Dir, Em, Transform};
+use typst::layout::{Abs, Dir, Em, PageRanges, Position, Transform};
 use typst::model::Document;
 use typst::text::{Font, Lang};
 use typst::util::Deferred;
 use typst::visualize::Image;
-use xmp_writer::{DateTime, LangId, RenditionClass, Timezone, XmpWriter};
+use xmp_writer::{
+    DateTime, LangId, Namespace, RdfCollectionType, RenditionClass, Timezone, XmpWriter,
+};
 
 use crate::color::ColorSpaces;
 use crate::extg::ExtGState;
+use crate::form::Field;
 use crate::gradient::PdfGradient;
 use crate::image::EncodedImage;
 use crate::page::Page;
 use crate::pattern::PdfPattern;
+use crate::tags::Tags;
+use crate::viewer::Viewer;
 
 /// Export a document into a PDF file.
 ///
-/// Returns the raw bytes making up the PDF file.
-///
-/// The `ident` parameter shall be a string that uniquely and stably identifies
-/// the document. It should not change between compilations of the same
-/// document. Its hash will be used to create a PDF document identifier (the
-/// identifier itself is not leaked). If `ident` is `None`, a hash of the
-/// document is used instead (which means that it _will_ change across
-/// compilations).
-///
-/// The `timestamp`, if given, is expected to be the creation date of the
-/// document as a UTC datetime. It will only be used if `set document(date: ..)`
-/// is `auto`.
+/// Returns the raw bytes making up the PDF file or errors if the document
+/// cannot be exported in conformance with the requested
+/// [standard](PdfOptions::standard).
 #[tracing::instrument(skip_all)]
-pub fn pdf(
-    document: &Document,
-    ident: Option<&str>,
-    timestamp: Option<Datetime>,
-) -> Vec<u8> {
-    let mut ctx = PdfContext::new(document);
-    page::construct_pages(&mut ctx, &document.pages);
+pub fn pdf(document: &Document, options: &PdfOptions) -> SourceResult<Vec<u8>> {
+    if let Some(standard) = options.standard {
+        standard::validate(document, standard, options.page_ranges.as_ref())?;
+    }
+
+    let mut ctx = PdfContext::new(document, options.standard);
+    page::construct_pages(&mut ctx, &document.pages, options.page_ranges.as_ref())?;
     font::write_fonts(&mut ctx);
     image::write_images(&mut ctx);
     gradient::write_gradients(&mut ctx);
     extg::write_external_graphics_states(&mut ctx);
     pattern::write_patterns(&mut ctx);
-    page::write_page_tree(&mut ctx);
-    write_catalog(&mut ctx, ident, timestamp);
-    ctx.pdf.finish()
+    embed::write_embedded_files(&mut ctx)?;
+    page::write_page_tree(&mut ctx)?;
+    write_catalog(&mut ctx, options.ident, options.timestamp)?;
+    Ok(ctx.pdf.finish())
+}
+
+/// Settings for PDF export.
+#[derive(Debug, Default, Clone)]
+pub struct PdfOptions<'a> {
+    /// A string that uniquely and stably identifies the document. It should
+    /// not change between compilations of the same document. Its hash will be
+    /// used to create a PDF document identifier (the identifier itself is not
+    /// leaked). If `ident` is `None`, a hash of the document is used instead
+    /// (which means that it _will_ change across compilations).
+    pub ident: Option<&'a str>,
+    /// The creation date of the document as a UTC datetime. It will only be
+    /// used if `set document(date: ..)` is `auto`.
+    pub timestamp: Option<Datetime>,
+    /// An archival standard the PDF must conform to. If `None`, a plain
+    /// PDF 1.7 file is written.
+    pub standard: Option<PdfStandard>,
+    /// Which pages to export. If `None`, all pages are exported. Links and
+    /// outline entries that point to pages which are not exported are left
+    /// out.
+    pub page_ranges: Option<PageRanges>,
+}
+
+/// An archival standard that a PDF file can conform to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum PdfStandard {
+    /// PDF/A-2b (ISO 19005-2, basic conformance).
+    A2b,
+    /// PDF/A-3b (ISO 19005-3, basic conformance).
+    A3b,
+}
+
+impl PdfStandard {
+    /// The part of ISO 19005 that specifies the standard.
+    fn part(self) -> &'static str {
+        match self {
+            Self::A2b => "2",
+            Self::A3b => "3",
+        }
+    }
+
+    /// The conformance level within the part.
+    fn conformance(self) -> &'static str {
+        "B"
+    }
+
+    /// A human-readable name of the standard.
+    fn name(self) -> &'static str {
+        match self {
+            Self::A2b => "PDF/A-2b",
+            Self::A3b => "PDF/A-3b",
+        }
+    }
 }
 
 /// Context for exporting a whole PDF document.
 struct PdfContext<'a> {
     /// The document that we're currently exporting.
     document: &'a Document,
+    /// The archival standard the document is exported in conformance with.
+    standard: Option<PdfStandard>,
     /// The writer we are writing the PDF into.
     pdf: Pdf,
     /// Content of exported pages.
@@ -83,6 +142,12 @@ struct PdfContext<'a> {
     /// The number of glyphs for all referenced languages in the document.
     /// We keep track of this to determine the main document language.
     languages: HashMap<Lang, usize>,
+    /// The logical structure of the document.
+    tags: Tags,
+    /// The document's form fields.
+    fields: Vec<Field>,
+    /// The file specifications of embedded files, by their names.
+    embedded_files: BTreeMap<EcoString, Ref>,
 
     /// Allocator for indirect reference IDs.
     alloc: Ref,
@@ -90,6 +155,9 @@ struct PdfContext<'a> {
     page_tree_ref: Ref,
     /// The IDs of written pages.
     page_refs: Vec<Ref>,
+    /// Maps the indices of the document's pages to the indices of the
+    /// exported pages. Pages that are not exported map to `None`.
+    page_indices: Vec<Option<usize>>,
     /// The IDs of written fonts.
     font_refs: Vec<Ref>,
     /// The IDs of written images.
@@ -118,18 +186,23 @@ struct PdfContext<'a> {
 }
 
 impl<'a> PdfContext<'a> {
-    fn new(document: &'a Document) -> Self {
+    fn new(document: &'a Document, standard: Option<PdfStandard>) -> Self {
         let mut alloc = Ref::new(1);
         let page_tree_ref = alloc.bump();
         Self {
             document,
+            standard,
             pdf: Pdf::new(),
             pages: vec![],
             glyph_sets: HashMap::new(),
             languages: HashMap::new(),
+            tags: Tags::new(),
+            fields: vec![],
+            embedded_files: BTreeMap::new(),
             alloc,
             page_tree_ref,
             page_refs: vec![],
+            page_indices: vec![],
             font_refs: vec![],
             image_refs: vec![],
             gradient_refs: vec![],
@@ -146,9 +219,23 @@ impl<'a> PdfContext<'a> {
     }
 }
 
+impl PdfContext<'_> {
+    /// The index of the exported page a position lies on, if that page is
+    /// exported.
+    fn page_index(&self, pos: &Position) -> Option<usize> {
+        self.page_indices.get(pos.page.get() - 1).copied().flatten()
+    }
+}
+
 /// Write the document catalog.
 #[tracing::instrument(skip_all)]
-fn write_catalog(ctx: &mut PdfContext, ident: Option<&str>, timestamp: Option<Datetime>) {
+fn write_catalog(
+    ctx: &mut PdfContext,
+    ident: Option<&str>,
+    timestamp: Option<Datetime>,
+) -> SourceResult<()> {
+    let viewer = Viewer::resolve(ctx)?;
+
     let lang = ctx
         .languages
         .iter()
@@ -167,6 +254,12 @@ fn write_catalog(ctx: &mut PdfContext, ident: Option<&str>, timestamp: Option<Da
     // Write the page labels.
     let page_labels = page::write_page_labels(ctx);
 
+    // Write the structure tree.
+    let struct_tree_root_id = ctx.document.tagged.then(|| tags::write_structure(ctx));
+
+    // Write the form fields.
+    let form_id = form::write_fields(ctx);
+
     // Write the document information.
     let mut info = ctx.pdf.document_info(ctx.alloc.bump());
     let mut xmp = XmpWriter::new();
@@ -198,9 +291,23 @@ fn write_catalog(ctx: &mut PdfContext, ident: Option<&str>, timestamp: Option<Da
         xmp.creator([joined.as_str()]);
     }
 
-    let creator = eco_format!("Typst {}", env!("CARGO_PKG_VERSION"));
-    info.creator(TextStr(&creator));
-    xmp.creator_tool(&creator);
+    if let Some(subject) = &ctx.document.subject {
+        info.subject(TextStr(subject));
+        xmp.description([(None, subject.as_str())]);
+    }
+
+    let typst = eco_format!("Typst {}", env!("CARGO_PKG_VERSION"));
+    let creator = ctx.document.creator.clone().unwrap_or_else(|| Some(typst.clone()));
+    if let Some(creator) = &creator {
+        info.creator(TextStr(creator));
+        xmp.creator_tool(creator);
+    }
+
+    let producer = ctx.document.producer.clone().unwrap_or(Some(typst));
+    if let Some(producer) = &producer {
+        info.producer(TextStr(producer));
+        xmp.producer(producer);
+    }
 
     let keywords = &ctx.document.keywords;
     if !keywords.is_empty() {
@@ -221,8 +328,15 @@ fn write_catalog(ctx: &mut PdfContext, ident: Option<&str>, timestamp: Option<Da
         }
     }
 
+    // Custom entries go into a namespace that PDF viewers list as custom
+    // document properties.
+    for (key, value) in &ctx.document.custom {
+        info.pair(Name(key.as_bytes()), TextStr(value));
+        xmp.element(key, PDFX).value(value.as_str());
+    }
+
     info.finish();
-    xmp.num_pages(ctx.document.pages.len() as u32);
+    xmp.num_pages(ctx.pages.len() as u32);
     xmp.format("application/pdf");
     xmp.language(ctx.languages.keys().map(|lang| LangId(lang.as_str())));
 
@@ -246,6 +360,13 @@ fn write_catalog(ctx: &mut PdfContext, ident: Option<&str>, timestamp: Option<Da
     xmp.rendition_class(RenditionClass::Proof);
     xmp.pdf_version("1.7");
 
+    // Write the PDF/A identification schema.
+    if let Some(standard) = ctx.standard {
+        xmp.pdfa_part(standard.part());
+        xmp.pdfa_conformance(standard.conformance());
+        write_pdfa_extension_schema(&mut xmp, &ctx.document.custom);
+    }
+
     let xmp_buf = xmp.finish(None);
     let meta_ref = ctx.alloc.bump();
     ctx.pdf
@@ -253,12 +374,46 @@ fn write_catalog(ctx: &mut PdfContext, ident: Option<&str>, timestamp: Option<Da
         .pair(Name(b"Type"), Name(b"Metadata"))
         .pair(Name(b"Subtype"), Name(b"XML"));
 
+    // Write the ICC profile referenced by the output intent.
+    let output_intent_ref = ctx.standard.map(|_| {
+        let icc_ref = ctx.alloc.bump();
+        color::write_output_intent_profile(&mut ctx.pdf, icc_ref);
+        icc_ref
+    });
+
     // Write the document catalog.
     let mut catalog = ctx.pdf.catalog(ctx.alloc.bump());
     catalog.pages(ctx.page_tree_ref);
-    catalog.viewer_preferences().direction(dir);
+    let mut viewer_preferences = catalog.viewer_preferences();
+    viewer_preferences.direction(dir);
+    if viewer.display_title {
+        viewer_preferences.pair(Name(b"DisplayDocTitle"), true);
+    }
+    viewer_preferences.finish();
+    viewer.write(&mut catalog);
     catalog.metadata(meta_ref);
 
+    // Mark the document as tagged so that assistive technology uses the
+    // structure tree.
+    if let Some(struct_tree_root_id) = struct_tree_root_id {
+        catalog.pair(Name(b"StructTreeRoot"), struct_tree_root_id);
+        catalog.mark_info().marked(true);
+    }
+
+    // Archival standards require an output intent that specifies how the
+    // document's device colors are to be interpreted.
+    if let Some(icc_ref) = output_intent_ref {
+        catalog
+            .insert(Name(b"OutputIntents"))
+            .array()
+            .push()
+            .start::<OutputIntent>()
+            .subtype(OutputIntentSubtype::PDFA)
+            .output_condition_identifier(TextStr("sRGB"))
+            .info(TextStr("sRGB IEC61966-2.1"))
+            .dest_output_profile(icc_ref);
+    }
+
     // Insert the page labels.
     if !page_labels.is_empty() {
         let mut num_tree = catalog.page_labels();
@@ -272,9 +427,72 @@ fn write_catalog(ctx: &mut PdfContext, ident: Option<&str>, timestamp: Option<Da
         catalog.outlines(outline_root_id);
     }
 
+    if let Some(form_id) = form_id {
+        catalog.pair(Name(b"AcroForm"), form_id);
+    }
+
+    // Attach the embedded files to the document, both by name and as
+    // associated files.
+    if !ctx.embedded_files.is_empty() {
+        let mut names = catalog.names();
+        let mut embedded_files = names.embedded_files();
+        let mut entries = embedded_files.names();
+        for (name, spec_ref) in &ctx.embedded_files {
+            entries.insert(Str(name.as_bytes()), *spec_ref);
+        }
+        entries.finish();
+        embedded_files.finish();
+        names.finish();
+
+        catalog
+            .insert(Name(b"AF"))
+            .array()
+            .items(ctx.embedded_files.values().copied());
+    }
+
     if let Some(lang) = lang {
         catalog.lang(TextStr(lang.as_str()));
     }
+
+    Ok(())
+}
+
+/// The XMP namespace of custom document properties.
+const PDFX: Namespace<'static> =
+    Namespace::Custom(("pdfx", "http://ns.adobe.com/pdfx/1.3/"));
+
+/// Describe the custom document properties in a PDF/A extension schema.
+///
+/// Archival standards only permit XMP properties that are either predefined or
+/// described in the metadata itself.
+fn write_pdfa_extension_schema(xmp: &mut XmpWriter, custom: &[(EcoString, EcoString)]) {
+    if custom.is_empty() {
+        return;
+    }
+
+    let extension =
+        Namespace::Custom(("pdfaExtension", "http://www.aiim.org/pdfa/ns/extension/"));
+    let schema = Namespace::Custom(("pdfaSchema", "http://www.aiim.org/pdfa/ns/schema#"));
+    let property =
+        Namespace::Custom(("pdfaProperty", "http://www.aiim.org/pdfa/ns/property#"));
+
+    let mut schemas = xmp.element("schemas", extension).array(RdfCollectionType::Bag);
+    let mut pdfx = schemas.element().obj();
+    pdfx.element("schema", schema.clone())
+        .value("Custom document properties");
+    pdfx.element("namespaceURI", schema.clone()).value(PDFX.url());
+    pdfx.element("prefix", schema.clone()).value(PDFX.prefix());
+
+    let mut properties = pdfx.element("property", schema).array(RdfCollectionType::Seq);
+    for (key, _) in custom {
+        let mut entry = properties.element().obj();
+        entry.element("name", property.clone()).value(key.as_str());
+        entry.element("valueType", property.clone()).value("Text");
+        entry.element("category", property.clone()).value("external");
+        entry
+            .element("description", property.clone())
+            .value("A custom document property");
+    }
 }
 
 /// Compress data with the DEFLATE algorithm.
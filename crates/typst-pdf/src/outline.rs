@@ -19,7 +19,11 @@ pub(crate) fn write_outline(ctx: &mut PdfContext) -> Option<Ref> {
     // enforced in the manner shown below.
     let mut last_skipped_level = None;
     for heading in ctx.document.introspector.query(&HeadingElem::elem().select()).iter() {
-        let leaf = HeadingNode::leaf((**heading).clone());
+        let mut leaf = HeadingNode::leaf((**heading).clone());
+
+        // Headings on pages that are not exported are skipped.
+        let pos = ctx.document.introspector.position(heading.location().unwrap());
+        leaf.bookmarked &= ctx.page_index(&pos).is_some();
 
         if leaf.bookmarked {
             let mut children = &mut tree;
@@ -164,8 +168,8 @@ fn write_outline_item(
 
     let loc = node.element.location().unwrap();
     let pos = ctx.document.introspector.position(loc);
-    let index = pos.page.get() - 1;
-    if let Some(page) = ctx.pages.get(index) {
+    if let Some(index) = ctx.page_indices[pos.page.get() - 1] {
+        let page = &ctx.pages[index];
         let y = (pos.point.y - Abs::pt(10.0)).max(Abs::zero());
         outline.dest().page(ctx.page_refs[index]).xyz(
             pos.point.x.to_f32(),
@@ -3,17 +3,20 @@ use std::num::NonZeroUsize;
 
 use ecow::{eco_format, EcoString};
 use pdf_writer::types::{
-    ActionType, AnnotationFlags, AnnotationType, ColorSpaceOperand, LineCapStyle,
-    LineJoinStyle, NumberingStyle,
+    ActionType, AnnotationFlags, AnnotationType, ArtifactType, ColorSpaceOperand,
+    LineCapStyle, LineJoinStyle, NumberingStyle, TabOrder,
 };
-use pdf_writer::writers::PageLabel;
+use pdf_writer::writers::{Annotation, PageLabel};
 use pdf_writer::{Content, Filter, Finish, Name, Rect, Ref, Str, TextStr};
+use typst::diag::{bail, SourceResult};
 use typst::introspection::Meta;
 use typst::layout::{
-    Abs, Em, Frame, FrameItem, GroupItem, PdfPageLabel, PdfPageLabelStyle, Point, Ratio,
-    Size, Transform,
+    Abs, Em, Frame, FrameItem, GroupItem, PageRanges, PdfPageLabel, PdfPageLabelStyle,
+    Point, Ratio, Size, Transform,
 };
+use typst::model::form::Widget;
 use typst::model::Destination;
+use typst::syntax::Span;
 use typst::text::{Font, TextItem};
 use typst::util::{Deferred, Numeric};
 use typst::visualize::{
@@ -23,22 +26,47 @@ use typst::visualize::{
 use crate::color::PaintEncode;
 use crate::extg::ExtGState;
 use crate::image::deferred_image;
-use crate::{deflate_deferred, AbsExt, EmExt, PdfContext};
+use crate::tags::Tag;
+use crate::{deflate_deferred, form, AbsExt, EmExt, PdfContext};
 
-/// Construct page objects.
+/// Construct page objects for the frames in the given page ranges.
 #[tracing::instrument(skip_all)]
-pub(crate) fn construct_pages(ctx: &mut PdfContext, frames: &[Frame]) {
-    for frame in frames {
-        let (page_ref, page) = construct_page(ctx, frame);
+pub(crate) fn construct_pages(
+    ctx: &mut PdfContext,
+    frames: &[Frame],
+    ranges: Option<&PageRanges>,
+) -> SourceResult<()> {
+    for (i, frame) in frames.iter().enumerate() {
+        if ranges.map_or(false, |ranges| !ranges.includes_page_index(i)) {
+            ctx.page_indices.push(None);
+            continue;
+        }
+
+        ctx.page_indices.push(Some(ctx.pages.len()));
+        let (page_ref, page) = construct_page(ctx, frame, ctx.document.tagged);
         ctx.page_refs.push(page_ref);
         ctx.pages.push(page);
     }
+
+    if ctx.pages.is_empty() {
+        bail!(Span::detached(), "page selection contains no pages of the document");
+    }
+
+    Ok(())
 }
 
 /// Construct a page object.
+///
+/// If `tagged` is true, the page's content is added to the document's
+/// structure tree.
 #[tracing::instrument(skip_all)]
-pub(crate) fn construct_page(ctx: &mut PdfContext, frame: &Frame) -> (Ref, Page) {
+pub(crate) fn construct_page(
+    ctx: &mut PdfContext,
+    frame: &Frame,
+    tagged: bool,
+) -> (Ref, Page) {
     let page_ref = ctx.alloc.bump();
+    let index = ctx.pages.len();
 
     let mut ctx = PageContext {
         parent: ctx,
@@ -50,7 +78,12 @@ pub(crate) fn construct_page(ctx: &mut PdfContext, frame: &Frame) -> (Ref, Page)
         saves: vec![],
         bottom: 0.0,
         links: vec![],
+        widgets: vec![],
         resources: HashMap::default(),
+        tagged,
+        index,
+        tags: vec![],
+        marked: vec![],
     };
 
     let size = frame.size();
@@ -75,8 +108,10 @@ pub(crate) fn construct_page(ctx: &mut PdfContext, frame: &Frame) -> (Ref, Page)
         id: ctx.page_ref,
         uses_opacities: ctx.uses_opacities,
         links: ctx.links,
+        widgets: ctx.widgets,
         label: ctx.label,
         resources: ctx.resources,
+        marked: ctx.marked,
     };
 
     (page_ref, page)
@@ -84,9 +119,9 @@ pub(crate) fn construct_page(ctx: &mut PdfContext, frame: &Frame) -> (Ref, Page)
 
 /// Write the page tree.
 #[tracing::instrument(skip_all)]
-pub(crate) fn write_page_tree(ctx: &mut PdfContext) {
+pub(crate) fn write_page_tree(ctx: &mut PdfContext) -> SourceResult<()> {
     for i in 0..ctx.pages.len() {
-        write_page(ctx, i);
+        write_page(ctx, i)?;
     }
 
     let mut pages = ctx.pdf.pages(ctx.page_tree_ref);
@@ -139,14 +174,29 @@ pub(crate) fn write_page_tree(ctx: &mut PdfContext) {
 
     // Write all of the functions used by the document.
     ctx.colors.write_functions(&mut ctx.pdf);
+
+    Ok(())
 }
 
 /// Write a page tree node.
 #[tracing::instrument(skip_all)]
-fn write_page(ctx: &mut PdfContext, i: usize) {
+fn write_page(ctx: &mut PdfContext, i: usize) -> SourceResult<()> {
     let page = &ctx.pages[i];
     let content_id = ctx.alloc.bump();
 
+    // Links to pages that are not exported are dropped.
+    let links: Vec<_> = page
+        .links
+        .iter()
+        .filter(|(dest, _, _)| match dest {
+            Destination::Url(_) => true,
+            Destination::Position(pos) => ctx.page_index(pos).is_some(),
+            Destination::Location(loc) => {
+                ctx.page_index(&ctx.document.introspector.position(*loc)).is_some()
+            }
+        })
+        .collect();
+
     let mut page_writer = ctx.pdf.page(page.id);
     page_writer.parent(ctx.page_tree_ref);
 
@@ -165,12 +215,38 @@ fn write_page(ctx: &mut PdfContext, i: usize) {
             .srgb();
     }
 
-    let mut annotations = page_writer.annotations();
-    for (dest, rect) in &page.links {
-        let mut annotation = annotations.push();
-        annotation.subtype(AnnotationType::Link).rect(*rect);
+    // Annotations are written as indirect objects so that the structure tree
+    // can refer to them.
+    let annotation_refs: Vec<Ref> = links.iter().map(|_| ctx.alloc.bump()).collect();
+    let widget_refs: Vec<Ref> = page.widgets.iter().map(|_| ctx.alloc.bump()).collect();
+    if !annotation_refs.is_empty() || !widget_refs.is_empty() {
+        page_writer
+            .insert(Name(b"Annots"))
+            .array()
+            .items(annotation_refs.iter().chain(&widget_refs).copied());
+    }
+
+    // The page's marked content is keyed by the page's index in the
+    // structure tree's parent tree.
+    let tagged = ctx.document.tagged;
+    if tagged {
+        page_writer.struct_parents(i as i32);
+        page_writer.tab_order(TabOrder::StructureOrder);
+    }
+    page_writer.finish();
+
+    let pages = ctx.pages.len();
+    for (&&(ref dest, rect, elem), &annotation_ref) in links.iter().zip(&annotation_refs)
+    {
+        let mut annotation = ctx.pdf.indirect(annotation_ref).start::<Annotation>();
+        annotation.subtype(AnnotationType::Link).rect(rect);
         annotation.border(0.0, 0.0, 0.0, None).flags(AnnotationFlags::PRINT);
 
+        if tagged {
+            let key = ctx.tags.annotation(elem, i, annotation_ref);
+            annotation.struct_parent((pages + key) as i32);
+        }
+
         let pos = match dest {
             Destination::Url(uri) => {
                 annotation
@@ -183,9 +259,9 @@ fn write_page(ctx: &mut PdfContext, i: usize) {
             Destination::Location(loc) => ctx.document.introspector.position(*loc),
         };
 
-        let index = pos.page.get() - 1;
         let y = (pos.point.y - Abs::pt(10.0)).max(Abs::zero());
-        if let Some(page) = ctx.pages.get(index) {
+        if let Some(index) = ctx.page_indices[pos.page.get() - 1] {
+            let page = &ctx.pages[index];
             annotation
                 .action()
                 .action_type(ActionType::GoTo)
@@ -195,12 +271,23 @@ fn write_page(ctx: &mut PdfContext, i: usize) {
         }
     }
 
-    annotations.finish();
-    page_writer.finish();
+    // Widgets belong to form elements, which readers visit in structure order
+    // when tabbing through the fields.
+    let widgets = std::mem::take(&mut ctx.pages[i].widgets);
+    for ((widget, rect, elem), &annotation_ref) in widgets.iter().zip(&widget_refs) {
+        let struct_parent = tagged.then(|| {
+            let form = ctx.tags.form(*elem);
+            (pages + ctx.tags.annotation(form, i, annotation_ref)) as i32
+        });
+        form::write_widget(ctx, widget, *rect, i, annotation_ref, struct_parent)?;
+    }
 
+    let page = &ctx.pages[i];
     ctx.pdf
         .stream(content_id, page.content.wait())
         .filter(Filter::FlateDecode);
+
+    Ok(())
 }
 
 /// Write the page labels.
@@ -262,12 +349,19 @@ pub struct Page {
     pub content: Deferred<Vec<u8>>,
     /// Whether the page uses opacities.
     pub uses_opacities: bool,
-    /// Links in the PDF coordinate system.
-    pub links: Vec<(Destination, Rect)>,
+    /// Links in the PDF coordinate system and the structure elements they
+    /// belong to.
+    pub links: Vec<(Destination, Rect, usize)>,
+    /// Form field widgets in the PDF coordinate system and the structure
+    /// elements they lie within.
+    pub widgets: Vec<(Widget, Rect, usize)>,
     /// The page's PDF label.
     pub label: Option<PdfPageLabel>,
     /// The page's used resources
     pub resources: HashMap<PageResource, usize>,
+    /// The structure elements of the page's marked-content sequences, indexed
+    /// by their marked-content identifiers.
+    pub marked: Vec<usize>,
 }
 
 /// Represents a resource being used in a PDF page by its name.
@@ -335,9 +429,18 @@ pub struct PageContext<'a, 'b> {
     saves: Vec<State>,
     bottom: f32,
     uses_opacities: bool,
-    links: Vec<(Destination, Rect)>,
+    links: Vec<(Destination, Rect, usize)>,
+    widgets: Vec<(Widget, Rect, usize)>,
     /// Keep track of the resources being used in the page.
     pub resources: HashMap<PageResource, usize>,
+    /// Whether the page's content is added to the structure tree.
+    tagged: bool,
+    /// The index of the page in the document.
+    index: usize,
+    /// The tags of the frames that are currently being written.
+    tags: Vec<Tag>,
+    /// The structure elements of the marked-content sequences written so far.
+    marked: Vec<usize>,
 }
 
 /// A simulated graphics state used to deduplicate graphics state changes and
@@ -553,28 +656,112 @@ impl PageContext<'_, '_> {
     pub fn reset_stroke_color_space(&mut self) {
         self.state.stroke_space = None;
     }
+
+    /// Resolve the element markers at the start of a frame and push their
+    /// tags.
+    fn open_tags(&mut self, frame: &Frame) {
+        for (_, item) in frame.items() {
+            let tag = match item {
+                FrameItem::Meta(Meta::Elem(elem), _) => match self.tags.last() {
+                    Some(Tag::Artifact) => continue,
+                    _ => self.parent.tags.open(elem, self.elem()),
+                },
+                FrameItem::Meta(Meta::Artifact, _) => Some(Tag::Artifact),
+                FrameItem::Meta(..) => None,
+                _ => break,
+            };
+
+            // Leaf frames repeat the markers of their ancestors.
+            if let Some(Tag::Elem(elem)) = tag {
+                if self.tags.iter().any(|t| matches!(t, Tag::Elem(e) if *e == elem)) {
+                    continue;
+                }
+            }
+
+            self.tags.extend(tag);
+        }
+    }
+
+    /// The innermost structure element of the content being written.
+    fn elem(&self) -> usize {
+        self.tags
+            .iter()
+            .rev()
+            .find_map(|tag| match tag {
+                Tag::Elem(elem) => Some(*elem),
+                Tag::Artifact => None,
+            })
+            .unwrap_or(0)
+    }
+
+    /// Wrap content in a marked-content sequence that belongs to the
+    /// innermost structure element or mark it as an artifact.
+    ///
+    /// Decorative content (like shapes) is only part of the structure within
+    /// figures.
+    fn mark(&mut self, decorative: bool, f: impl FnOnce(&mut Self)) {
+        if !self.tagged {
+            f(self);
+            return;
+        }
+
+        let elem = self.elem();
+        if self.tags.iter().any(|tag| matches!(tag, Tag::Artifact)) {
+            self.content
+                .begin_marked_content_with_properties(Name(b"Artifact"))
+                .properties()
+                .artifact()
+                .kind(ArtifactType::Pagination);
+        } else if decorative && !self.parent.tags.in_figure(elem) {
+            self.content.begin_marked_content(Name(b"Artifact"));
+        } else {
+            let mcid = self.marked.len() as i32;
+            self.marked.push(elem);
+            self.parent.tags.content(elem, self.index, mcid);
+            self.content
+                .begin_marked_content_with_properties(Name(b"Span"))
+                .properties()
+                .identify(mcid);
+        }
+
+        f(self);
+        self.content.end_marked_content();
+    }
 }
 
 /// Encode a frame into the content stream.
 fn write_frame(ctx: &mut PageContext, frame: &Frame) {
+    let depth = ctx.tags.len();
+    if ctx.tagged {
+        ctx.open_tags(frame);
+    }
+
     for &(pos, ref item) in frame.items() {
         let x = pos.x.to_f32();
         let y = pos.y.to_f32();
 
         match item {
             FrameItem::Group(group) => write_group(ctx, pos, group),
-            FrameItem::Text(text) => write_text(ctx, pos, text),
-            FrameItem::Shape(shape, _) => write_shape(ctx, pos, shape),
-            FrameItem::Image(image, size, _) => write_image(ctx, x, y, image, *size),
+            FrameItem::Text(text) => ctx.mark(false, |ctx| write_text(ctx, pos, text)),
+            FrameItem::Shape(shape, _) => {
+                ctx.mark(true, |ctx| write_shape(ctx, pos, shape))
+            }
+            FrameItem::Image(image, size, _) => {
+                ctx.mark(false, |ctx| write_image(ctx, x, y, image, *size))
+            }
             FrameItem::Meta(meta, size) => match meta {
                 Meta::Link(dest) => write_link(ctx, pos, dest, *size),
                 Meta::Elem(_) => {}
                 Meta::Hide => {}
+                Meta::Artifact => {}
                 Meta::PageNumbering(_) => {}
                 Meta::PdfPageLabel(label) => ctx.label = Some(label.clone()),
+                Meta::Widget(widget) => write_widget(ctx, pos, widget, *size),
             },
         }
     }
+
+    ctx.tags.truncate(depth);
 }
 
 /// Encode a group into the content stream.
@@ -786,12 +973,27 @@ fn write_image(ctx: &mut PageContext, x: f32, y: f32, image: &Image, size: Size)
 
 /// Save a link for later writing in the annotations dictionary.
 fn write_link(ctx: &mut PageContext, pos: Point, dest: &Destination, size: Size) {
+    let rect = annotation_rect(ctx, pos, size);
+    let elem = ctx.elem();
+    let elem = ctx.parent.tags.link(elem).unwrap_or(elem);
+    ctx.links.push((dest.clone(), rect, elem));
+}
+
+/// Save a form field widget for later writing in the annotations dictionary.
+fn write_widget(ctx: &mut PageContext, pos: Point, widget: &Widget, size: Size) {
+    let rect = annotation_rect(ctx, pos, size);
+    let elem = ctx.elem();
+    ctx.widgets.push((widget.clone(), rect, elem));
+}
+
+/// The bounding box of an annotation in the PDF coordinate system.
+fn annotation_rect(ctx: &PageContext, pos: Point, size: Size) -> Rect {
     let mut min_x = Abs::inf();
     let mut min_y = Abs::inf();
     let mut max_x = -Abs::inf();
     let mut max_y = -Abs::inf();
 
-    // Compute the bounding box of the transformed link.
+    // Compute the bounding box of the transformed annotation.
     for point in [
         pos,
         pos + Point::with_x(size.x),
@@ -809,9 +1011,7 @@ fn write_link(ctx: &mut PageContext, pos: Point, dest: &Destination, size: Size)
     let x2 = max_x.to_f32();
     let y1 = max_y.to_f32();
     let y2 = min_y.to_f32();
-    let rect = Rect::new(x1, y1, x2, y2);
-
-    ctx.links.push((dest.clone(), rect));
+    Rect::new(x1, y1, x2, y2)
 }
 
 fn to_pdf_line_cap(cap: LineCap) -> LineCapStyle {
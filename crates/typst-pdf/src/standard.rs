@@ -0,0 +1,218 @@
+use std::collections::HashSet;
+
+use ecow::{eco_format, EcoString, EcoVec};
+use ttf_parser::Permissions;
+use typst::diag::{SourceDiagnostic, SourceResult};
+use typst::foundations::{NativeElement, StyleChain};
+use typst::introspection::Meta;
+use typst::layout::{Frame, FrameItem, PageRanges};
+use typst::model::form::{Widget, WidgetKind};
+use typst::model::pdf::EmbedElem;
+use typst::model::Document;
+use typst::syntax::Span;
+use typst::text::{Font, TextItem};
+use typst::visualize::{Color, ColorSpace, FixedStroke, Image, ImageKind, Paint};
+
+use crate::PdfStandard;
+
+/// Check that a document can be exported in conformance with an archival
+/// standard.
+///
+/// Typst always embeds fonts and writes device-independent colors, so the
+/// remaining violations stem from what the document itself contains: fonts
+/// whose license forbids embedding, missing glyphs, CMYK colors and raster
+/// images whose color profile does not match their pixels (which cannot be
+/// reconciled with the sRGB output intent), form fields whose appearance the
+/// reader would have to generate, and embedded files that the standard does
+/// not permit.
+///
+/// Only the pages in the given ranges are checked as the others are not
+/// exported. Raster images nested in SVG images are not checked.
+#[tracing::instrument(skip_all)]
+pub(crate) fn validate(
+    document: &Document,
+    standard: PdfStandard,
+    ranges: Option<&PageRanges>,
+) -> SourceResult<()> {
+    let mut validator = Validator {
+        standard,
+        errors: EcoVec::new(),
+        restricted: HashSet::new(),
+    };
+
+    for (i, frame) in document.pages.iter().enumerate() {
+        if ranges.map_or(true, |ranges| ranges.includes_page_index(i)) {
+            validator.frame(frame);
+        }
+    }
+
+    for element in document.introspector.query(&EmbedElem::elem().select()).iter() {
+        validator.embed(element.to::<EmbedElem>().unwrap());
+    }
+
+    if validator.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(validator.errors)
+    }
+}
+
+/// Collects conformance violations while walking the document's frames.
+struct Validator {
+    /// The standard to check against.
+    standard: PdfStandard,
+    /// The violations found so far.
+    errors: EcoVec<SourceDiagnostic>,
+    /// Fonts that were already reported as not embeddable.
+    restricted: HashSet<Font>,
+}
+
+impl Validator {
+    /// Check all items in a frame.
+    fn frame(&mut self, frame: &Frame) {
+        for (_, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => self.frame(&group.frame),
+                FrameItem::Text(text) => self.text(text),
+                FrameItem::Shape(shape, span) => {
+                    if let Some(fill) = &shape.fill {
+                        self.paint(fill, *span);
+                    }
+                    if let Some(stroke) = &shape.stroke {
+                        self.stroke(stroke, *span);
+                    }
+                }
+                FrameItem::Meta(Meta::Widget(widget), _) => self.widget(widget),
+                FrameItem::Image(image, _, span) => self.image(image, *span),
+                FrameItem::Meta(..) => {}
+            }
+        }
+    }
+
+    /// Check a text run's font, glyphs, and paints.
+    fn text(&mut self, text: &TextItem) {
+        let span = text.glyphs.first().map_or(Span::detached(), |g| g.span.0);
+
+        if text.font.ttf().permissions() == Some(Permissions::Restricted)
+            && self.restricted.insert(text.font.clone())
+        {
+            let family = &text.font.info().family;
+            self.error(
+                span,
+                eco_format!(
+                    "the license of the font {family} does not allow embedding it"
+                ),
+                "use a different font for this text",
+            );
+        }
+
+        for glyph in &text.glyphs {
+            if glyph.id == 0 {
+                let c = text.text[glyph.range()].chars().next().unwrap_or(' ');
+                self.error(
+                    glyph.span.0,
+                    eco_format!("no font could render the character {c:?}"),
+                    "try a font that contains this character",
+                );
+            }
+        }
+
+        self.paint(&text.fill, span);
+        if let Some(stroke) = &text.stroke {
+            self.stroke(stroke, span);
+        }
+    }
+
+    /// Check that a raster image's color profile describes its pixels.
+    ///
+    /// Raster images are written in their embedded profile's color space.
+    /// CMYK JPEGs are converted to RGB when they are decoded, but keep their
+    /// CMYK profile, which then no longer matches the data.
+    fn image(&mut self, image: &Image, span: Span) {
+        let ImageKind::Raster(raster) = image.kind() else { return };
+        let Some(icc) = raster.icc() else { return };
+
+        let (expected, colors) = if raster.dynamic().color().has_color() {
+            (b"RGB ", "RGB")
+        } else {
+            (b"GRAY", "gray")
+        };
+
+        let space = icc.get(16..20).unwrap_or_default();
+        if space != expected {
+            let profile = String::from_utf8_lossy(space);
+            self.error(
+                span,
+                eco_format!(
+                    "the {} color profile of this image does not match its {colors} pixels",
+                    profile.trim(),
+                ),
+                "convert the image to sRGB and remove its color profile",
+            );
+        }
+    }
+
+    /// Check that a form field comes with its own appearance.
+    fn widget(&mut self, widget: &Widget) {
+        if matches!(widget.kind, WidgetKind::Text { .. } | WidgetKind::Dropdown { .. }) {
+            self.error(
+                widget.span,
+                "text fields and dropdowns are not supported",
+                "remove the field or export without a PDF/A standard",
+            );
+        }
+    }
+
+    /// Check that an embedded file is permitted and fully described.
+    fn embed(&mut self, embed: &EmbedElem) {
+        match self.standard {
+            PdfStandard::A2b => self.error(
+                embed.span(),
+                "embedded files are not supported",
+                "export with PDF/A-3b to embed arbitrary files",
+            ),
+            PdfStandard::A3b => {
+                // The MIME type is materialized during realization.
+                if embed.mime_type(StyleChain::default()).is_none() {
+                    self.error(
+                        embed.span(),
+                        "embedded files must have a MIME type",
+                        "specify the file's type with the `mime-type` argument",
+                    );
+                }
+            }
+        }
+    }
+
+    /// Check a stroke's paint.
+    fn stroke(&mut self, stroke: &FixedStroke, span: Span) {
+        self.paint(&stroke.paint, span);
+    }
+
+    /// Check that a paint does not use CMYK colors.
+    fn paint(&mut self, paint: &Paint, span: Span) {
+        let cmyk = match paint {
+            Paint::Solid(color) => matches!(color, Color::Cmyk(_)),
+            Paint::Gradient(gradient) => gradient.space() == ColorSpace::Cmyk,
+            Paint::Pattern(pattern) => {
+                self.frame(pattern.frame());
+                false
+            }
+        };
+
+        if cmyk {
+            self.error(
+                span,
+                "CMYK colors cannot be used with an sRGB output intent",
+                "convert the color to RGB with `rgb(..)`",
+            );
+        }
+    }
+
+    /// Report a violation of the standard.
+    fn error(&mut self, span: Span, message: impl Into<EcoString>, hint: &str) {
+        let message = eco_format!("{}: {}", self.standard.name(), message.into());
+        self.errors
+            .push(SourceDiagnostic::error(span, message).with_hint(hint));
+    }
+}
@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+
+use ecow::EcoString;
+use pdf_writer::types::{ListNumbering, StructRole};
+use pdf_writer::writers::{StructElement, StructTreeRoot};
+use pdf_writer::{Finish, Name, Ref, TextStr};
+use typst::foundations::{Content, StyleChain};
+use typst::introspection::Location;
+use typst::model::{
+    EnumElem, EnumItem, FigureElem, FootnoteElem, FootnoteEntry, HeadingElem, LinkElem,
+    ListElem, ListItem, ParElem, TableCell, TableElem, TermItem, TermsElem,
+};
+use typst::visualize::ImageElem;
+
+use crate::PdfContext;
+
+/// The logical structure of a document, built up while writing its pages.
+///
+/// In tagged documents, layout attaches the elements that make up the
+/// structure to the frames they produce via `Meta::Elem` markers. While a page
+/// is written, the markers around each piece of content are resolved into
+/// structure elements and the content is wrapped in a marked-content sequence
+/// that refers to the innermost of them.
+pub(crate) struct Tags {
+    /// The structure elements. The first one is the document root.
+    elems: Vec<StructElem>,
+    /// Maps element locations to their structure elements.
+    indices: HashMap<Location, usize>,
+    /// The synthesized row elements of tables, keyed by the table's structure
+    /// element and the row's index.
+    rows: HashMap<(usize, usize), usize>,
+    /// The cells that were already tagged, keyed by the table's structure
+    /// element and the cell's position.
+    cells: HashMap<(usize, usize, usize), Location>,
+    /// The structure elements of annotations in the order of their keys in
+    /// the parent tree.
+    annotations: Vec<usize>,
+}
+
+/// What content on a page belongs to.
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum Tag {
+    /// The structure element with the given index.
+    Elem(usize),
+    /// Content that is not part of the logical structure, like page headers
+    /// and footers.
+    Artifact,
+}
+
+/// A structure element.
+struct StructElem {
+    /// The element's role.
+    role: StructRole,
+    /// The index of the parent element.
+    parent: usize,
+    /// The element's children in reading order.
+    children: Vec<StructChild>,
+    /// An alternate description of the element.
+    alt: Option<EcoString>,
+    /// The element's layout-related attributes.
+    attrs: Attrs,
+}
+
+/// A child of a structure element.
+enum StructChild {
+    /// Another structure element.
+    Elem(usize),
+    /// A marked-content sequence on a page.
+    Content { page: usize, mcid: i32 },
+    /// A link or widget annotation on a page.
+    Annotation { page: usize, annot: Ref },
+}
+
+/// Attributes of a structure element.
+enum Attrs {
+    None,
+    List(ListNumbering),
+    Cell { x: usize, rowspan: usize, colspan: usize },
+}
+
+impl Tags {
+    /// Create the structure with just a document root.
+    pub fn new() -> Self {
+        Self {
+            elems: vec![StructElem::new(StructRole::Document, 0)],
+            indices: HashMap::new(),
+            rows: HashMap::new(),
+            cells: HashMap::new(),
+            annotations: vec![],
+        }
+    }
+
+    /// Resolve an element marker to the tag of its content.
+    ///
+    /// Returns `None` for elements that have no counterpart in the structure
+    /// tree. Their content belongs to the closest ancestor instead.
+    pub fn open(&mut self, elem: &Content, parent: usize) -> Option<Tag> {
+        let loc = elem.location()?;
+        if let Some(&index) = self.indices.get(&loc) {
+            return Some(Tag::Elem(index));
+        }
+
+        // The fields used here are materialized during realization.
+        let styles = StyleChain::default();
+        let role = self.elems[parent].role;
+        let index = if let Some(heading) = elem.to::<HeadingElem>() {
+            let level = heading.level(styles).get().min(6);
+            let role = [
+                StructRole::H1,
+                StructRole::H2,
+                StructRole::H3,
+                StructRole::H4,
+                StructRole::H5,
+                StructRole::H6,
+            ][level - 1];
+            self.push(role, parent)
+        } else if elem.is::<ParElem>() {
+            // Headings are paragraphs of their own.
+            if is_heading(role) {
+                return None;
+            }
+            self.push(StructRole::P, parent)
+        } else if elem.is::<ListElem>() {
+            let index = self.push(StructRole::L, parent);
+            self.elems[index].attrs = Attrs::List(ListNumbering::Disc);
+            index
+        } else if elem.is::<EnumElem>() {
+            let index = self.push(StructRole::L, parent);
+            self.elems[index].attrs = Attrs::List(ListNumbering::Decimal);
+            index
+        } else if elem.is::<TermsElem>() {
+            let index = self.push(StructRole::L, parent);
+            self.elems[index].attrs = Attrs::List(ListNumbering::None);
+            index
+        } else if elem.is::<ListItem>() || elem.is::<EnumItem>() || elem.is::<TermItem>()
+        {
+            self.push(StructRole::LI, parent)
+        } else if elem.is::<TableElem>() {
+            self.push(StructRole::Table, parent)
+        } else if let Some(cell) = elem.to::<TableCell>() {
+            if role != StructRole::Table {
+                return None;
+            }
+
+            let x = cell.x(styles).as_custom()?;
+            let y = cell.y(styles).as_custom()?;
+
+            // Repeated headers and footers lay out the same cells again.
+            match self.cells.get(&(parent, x, y)) {
+                Some(&prev) if prev != loc => return Some(Tag::Artifact),
+                _ => self.cells.insert((parent, x, y), loc),
+            };
+
+            let row = match self.rows.get(&(parent, y)) {
+                Some(&row) => row,
+                None => {
+                    let row = self.push(StructRole::TR, parent);
+                    self.rows.insert((parent, y), row);
+                    row
+                }
+            };
+
+            let role = if cell.header(styles) { StructRole::TH } else { StructRole::TD };
+            let index = self.push(role, row);
+            self.elems[index].attrs = Attrs::Cell {
+                x,
+                rowspan: cell.rowspan(styles).get(),
+                colspan: cell.colspan(styles).get(),
+            };
+
+            // Cells that span multiple rows are laid out after the cells to
+            // their right, but readers expect a row's cells in column order.
+            let siblings = &self.elems[row].children;
+            let at = siblings
+                .iter()
+                .position(|child| match *child {
+                    StructChild::Elem(j) => match self.elems[j].attrs {
+                        Attrs::Cell { x: other, .. } => other > x,
+                        _ => false,
+                    },
+                    _ => false,
+                })
+                .unwrap_or(siblings.len() - 1);
+            let child = self.elems[row].children.pop().unwrap();
+            self.elems[row].children.insert(at, child);
+            index
+        } else if elem.is::<FigureElem>() {
+            self.push(StructRole::Figure, parent)
+        } else if let Some(image) = elem.to::<ImageElem>() {
+            let alt = image.alt(styles);
+
+            // An image that makes up a figure provides its alternate text.
+            if role == StructRole::Figure && self.elems[parent].alt.is_none() {
+                self.elems[parent].alt = alt;
+                parent
+            } else {
+                let index = self.push(StructRole::Figure, parent);
+                self.elems[index].alt = alt;
+                index
+            }
+        } else if elem.is::<LinkElem>() {
+            self.push(StructRole::Link, parent)
+        } else if elem.is::<FootnoteElem>() {
+            self.push(StructRole::Reference, parent)
+        } else if elem.is::<FootnoteEntry>() {
+            self.push(StructRole::Note, parent)
+        } else {
+            return None;
+        };
+
+        self.indices.insert(loc, index);
+        Some(Tag::Elem(index))
+    }
+
+    /// Add a marked-content sequence to an element.
+    pub fn content(&mut self, elem: usize, page: usize, mcid: i32) {
+        self.elems[elem].children.push(StructChild::Content { page, mcid });
+    }
+
+    /// Add an annotation to an element and return its key in the parent tree.
+    pub fn annotation(&mut self, elem: usize, page: usize, annot: Ref) -> usize {
+        self.elems[elem]
+            .children
+            .push(StructChild::Annotation { page, annot });
+        self.annotations.push(elem);
+        self.annotations.len() - 1
+    }
+
+    /// Add a form element for a widget annotation within an element.
+    pub fn form(&mut self, parent: usize) -> usize {
+        self.push(StructRole::Form, parent)
+    }
+
+    /// Whether an element is or lies within a figure.
+    pub fn in_figure(&self, mut elem: usize) -> bool {
+        while elem != 0 {
+            if self.elems[elem].role == StructRole::Figure {
+                return true;
+            }
+            elem = self.elems[elem].parent;
+        }
+        false
+    }
+
+    /// The link element an element is or lies within.
+    pub fn link(&self, mut elem: usize) -> Option<usize> {
+        while elem != 0 {
+            if self.elems[elem].role == StructRole::Link {
+                return Some(elem);
+            }
+            elem = self.elems[elem].parent;
+        }
+        None
+    }
+
+    /// Add a new structure element.
+    fn push(&mut self, role: StructRole, parent: usize) -> usize {
+        let index = self.elems.len();
+        self.elems.push(StructElem::new(role, parent));
+        self.elems[parent].children.push(StructChild::Elem(index));
+        index
+    }
+}
+
+impl StructElem {
+    fn new(role: StructRole, parent: usize) -> Self {
+        Self {
+            role,
+            parent,
+            children: vec![],
+            alt: None,
+            attrs: Attrs::None,
+        }
+    }
+
+    /// Whether this is a paragraph without content. Such paragraphs consist
+    /// only of invisible markers and are left out of the structure tree.
+    fn is_empty_par(&self) -> bool {
+        self.role == StructRole::P && self.children.is_empty()
+    }
+}
+
+/// Whether the role is a heading.
+fn is_heading(role: StructRole) -> bool {
+    matches!(
+        role,
+        StructRole::H1
+            | StructRole::H2
+            | StructRole::H3
+            | StructRole::H4
+            | StructRole::H5
+            | StructRole::H6
+    )
+}
+
+/// Write the structure tree and return the reference of its root.
+///
+/// The parent tree maps each page (keyed by its index) to the elements of its
+/// marked-content sequences and each annotation (keyed after the pages)
+/// to its element.
+#[tracing::instrument(skip_all)]
+pub(crate) fn write_structure(ctx: &mut PdfContext) -> Ref {
+    let root_ref = ctx.alloc.bump();
+
+    // Empty paragraphs are left out, so they don't get a reference. Nothing
+    // refers to them as they have no content.
+    let refs: Vec<Option<Ref>> = ctx
+        .tags
+        .elems
+        .iter()
+        .map(|elem| (!elem.is_empty_par()).then(|| ctx.alloc.bump()))
+        .collect();
+    let get = |elem: usize| refs[elem].unwrap();
+
+    for (i, elem) in ctx.tags.elems.iter().enumerate() {
+        let Some(id) = refs[i] else { continue };
+        let mut writer = ctx.pdf.indirect(id).start::<StructElement>();
+        writer.kind(elem.role);
+        writer.parent(if i == 0 { root_ref } else { get(elem.parent) });
+
+        if let Some(alt) = &elem.alt {
+            writer.alt(TextStr(alt));
+        }
+
+        match elem.attrs {
+            Attrs::None => {}
+            Attrs::List(numbering) => {
+                writer.attributes().push().list().list_numbering(numbering);
+            }
+            Attrs::Cell { rowspan, colspan, .. } if rowspan > 1 || colspan > 1 => {
+                let mut attributes = writer.attributes();
+                let mut table = attributes.push().table();
+                if rowspan > 1 {
+                    table.row_span(rowspan as i32);
+                }
+                if colspan > 1 {
+                    table.col_span(colspan as i32);
+                }
+            }
+            Attrs::Cell { .. } => {}
+        }
+
+        let mut children = writer.children();
+        for child in &elem.children {
+            match *child {
+                StructChild::Elem(j) => {
+                    if let Some(child) = refs[j] {
+                        children.struct_element(child);
+                    }
+                }
+                StructChild::Content { page, mcid } => {
+                    children
+                        .marked_content_ref()
+                        .marked_content_id(mcid)
+                        .page(ctx.page_refs[page]);
+                }
+                StructChild::Annotation { page, annot } => {
+                    children.object_ref().object(annot).page(ctx.page_refs[page]);
+                }
+            }
+        }
+    }
+
+    let mut root = ctx.pdf.indirect(root_ref).start::<StructTreeRoot>();
+    root.child(get(0));
+
+    let pages = ctx.pages.len();
+    let mut parent_tree = root.insert(Name(b"ParentTree")).dict();
+    let mut nums = parent_tree.insert(Name(b"Nums")).array();
+    for (i, page) in ctx.pages.iter().enumerate() {
+        nums.item(i as i32);
+        nums.push().array().items(page.marked.iter().map(|&elem| get(elem)));
+    }
+    for (i, &elem) in ctx.tags.annotations.iter().enumerate() {
+        nums.item((pages + i) as i32);
+        nums.item(get(elem));
+    }
+    nums.finish();
+    parent_tree.finish();
+
+    root.parent_tree_next_key((pages + ctx.tags.annotations.len()) as i32);
+    root_ref
+}
@@ -0,0 +1,100 @@
+use pdf_writer::types::PageLayout;
+use pdf_writer::writers::{Catalog, Destination};
+use pdf_writer::{Name, Ref};
+use typst::diag::{bail, SourceResult};
+use typst::foundations::{NativeElement, Smart, StyleChain};
+use typst::model::pdf::{self, ViewerElem};
+
+use crate::{AbsExt, PdfContext};
+
+/// How viewers should present the document, as configured with `pdf.viewer`.
+pub(crate) struct Viewer {
+    /// Whether the viewer shows the document's title instead of its file name.
+    pub display_title: bool,
+    /// How the viewer lays out pages.
+    layout: Option<PageLayout>,
+    /// Which panel the viewer shows.
+    mode: Option<Name<'static>>,
+    /// The exported page to open at and its height.
+    open_to: Option<(Ref, f32)>,
+}
+
+impl Viewer {
+    /// Resolve the document's viewer settings.
+    #[tracing::instrument(skip_all)]
+    pub fn resolve(ctx: &PdfContext) -> SourceResult<Self> {
+        // The fields used here are materialized during realization.
+        let styles = StyleChain::default();
+        let elements = ctx.document.introspector.query(&ViewerElem::elem().select());
+
+        let mut viewer = Self {
+            display_title: ctx.document.title.is_some(),
+            layout: None,
+            mode: None,
+            open_to: None,
+        };
+
+        let Some(element) = elements.first() else { return Ok(viewer) };
+        if let Some(second) = elements.get(1) {
+            bail!(second.span(), "viewer settings can only be specified once");
+        }
+
+        let elem = element.to::<ViewerElem>().unwrap();
+        if let Smart::Custom(display_title) = elem.display_title(styles) {
+            viewer.display_title = display_title;
+        }
+
+        viewer.layout = elem.page_layout(styles).map(|layout| match layout {
+            pdf::PageLayout::SinglePage => PageLayout::SinglePage,
+            pdf::PageLayout::OneColumn => PageLayout::OneColumn,
+            pdf::PageLayout::TwoColumnLeft => PageLayout::TwoColumnLeft,
+            pdf::PageLayout::TwoColumnRight => PageLayout::TwoColumnRight,
+            pdf::PageLayout::TwoPageLeft => PageLayout::TwoPageLeft,
+            pdf::PageLayout::TwoPageRight => PageLayout::TwoPageRight,
+        });
+
+        // Writing the names ourselves because pdf-writer's `PageMode` lacks
+        // the attachments panel.
+        viewer.mode = elem.page_mode(styles).map(|mode| match mode {
+            pdf::PageMode::Outline => Name(b"UseOutlines"),
+            pdf::PageMode::Thumbnails => Name(b"UseThumbs"),
+            pdf::PageMode::Attachments => Name(b"UseAttachments"),
+            pdf::PageMode::FullScreen => Name(b"FullScreen"),
+        });
+
+        if let Some(number) = elem.open_to(styles) {
+            let count = ctx.document.pages.len();
+            if number.get() > count {
+                bail!(
+                    elem.span(), "cannot open to page {number}";
+                    hint: "the document ends at page {count}"
+                );
+            }
+
+            // Pages that are not exported cannot be opened to.
+            viewer.open_to = ctx.page_indices[number.get() - 1]
+                .map(|index| (ctx.page_refs[index], ctx.pages[index].size.y.to_f32()));
+        }
+
+        Ok(viewer)
+    }
+
+    /// Write the settings into the document catalog.
+    pub fn write(&self, catalog: &mut Catalog) {
+        if let Some(layout) = self.layout {
+            catalog.page_layout(layout);
+        }
+
+        if let Some(mode) = self.mode {
+            catalog.pair(Name(b"PageMode"), mode);
+        }
+
+        if let Some((page_ref, height)) = self.open_to {
+            catalog
+                .insert(Name(b"OpenAction"))
+                .start::<Destination>()
+                .page(page_ref)
+                .xyz(0.0, height, None);
+        }
+    }
+}
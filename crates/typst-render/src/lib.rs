@@ -1,6 +1,7 @@
 //! Rendering into raster images.
 
 use std::io::Read;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 
 use image::imageops::FilterType;
@@ -40,43 +41,63 @@ pub fn render(frame: &Frame, pixel_per_pt: f32, fill: Color) -> sk::Pixmap {
 
 /// Export multiple frames into a single raster image.
 ///
-/// The padding will be added around and between the individual frames.
+/// The frames are laid out in a grid with the given number of columns. The
+/// padding will be added around and between the individual frames. Returns
+/// `None` if there are no frames or the merged image would be too large.
 pub fn render_merged(
     frames: &[Frame],
     pixel_per_pt: f32,
     frame_fill: Color,
     padding: Abs,
     padding_fill: Color,
-) -> sk::Pixmap {
+    columns: NonZeroUsize,
+) -> Option<sk::Pixmap> {
+    if frames.is_empty() {
+        return None;
+    }
+
     let pixmaps: Vec<_> = frames
         .iter()
         .map(|frame| render(frame, pixel_per_pt, frame_fill))
         .collect();
 
+    // Each column is as wide as its widest frame and each row as high as its
+    // highest frame.
+    let columns = columns.get().min(pixmaps.len());
+    let mut widths = vec![0; columns];
+    let mut heights = vec![0; (pixmaps.len() + columns - 1) / columns];
+    for (i, pixmap) in pixmaps.iter().enumerate() {
+        widths[i % columns] = widths[i % columns].max(pixmap.width());
+        heights[i / columns] = heights[i / columns].max(pixmap.height());
+    }
+
     let padding = (pixel_per_pt * padding.to_f32()).round() as u32;
-    let pxw =
-        2 * padding + pixmaps.iter().map(sk::Pixmap::width).max().unwrap_or_default();
-    let pxh =
-        padding + pixmaps.iter().map(|pixmap| pixmap.height() + padding).sum::<u32>();
+    let pxw = padding + widths.iter().map(|w| w + padding).sum::<u32>();
+    let pxh = padding + heights.iter().map(|h| h + padding).sum::<u32>();
 
-    let mut canvas = sk::Pixmap::new(pxw, pxh).unwrap();
+    let mut canvas = sk::Pixmap::new(pxw, pxh)?;
     canvas.fill(to_sk_color(padding_fill));
 
-    let [x, mut y] = [padding; 2];
-    for pixmap in pixmaps {
-        canvas.draw_pixmap(
-            x as i32,
-            y as i32,
-            pixmap.as_ref(),
-            &sk::PixmapPaint::default(),
-            sk::Transform::identity(),
-            None,
-        );
+    let mut y = padding;
+    for (row, chunk) in pixmaps.chunks(columns).enumerate() {
+        let mut x = padding;
+        for (column, pixmap) in chunk.iter().enumerate() {
+            canvas.draw_pixmap(
+                x as i32,
+                y as i32,
+                pixmap.as_ref(),
+                &sk::PixmapPaint::default(),
+                sk::Transform::identity(),
+                None,
+            );
+
+            x += widths[column] + padding;
+        }
 
-        y += pixmap.height() + padding;
+        y += heights[row] + padding;
     }
 
-    canvas
+    Some(canvas)
 }
 
 /// Additional metadata carried through the rendering process.
@@ -167,6 +188,8 @@ fn render_frame(canvas: &mut sk::Pixmap, state: State, frame: &Frame) {
                 Meta::PageNumbering(_) => {}
                 Meta::PdfPageLabel(_) => {}
                 Meta::Hide => {}
+                Meta::Artifact => {}
+                Meta::Widget(_) => {}
             },
         }
     }
@@ -1128,3 +1151,70 @@ fn alpha_mul(color: u32, scale: u32) -> u32 {
 fn offset_bounding_box(bbox: Size, stroke_width: Abs) -> Size {
     Size::new(bbox.x + stroke_width * 2.0, bbox.y + stroke_width * 2.0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Merge empty frames of the given sizes in points at one pixel per point,
+    /// filling frames with white and the gaps with black.
+    fn merge(sizes: &[(f64, f64)], gap: f64, columns: usize) -> Option<sk::Pixmap> {
+        let frames: Vec<Frame> = sizes
+            .iter()
+            .map(|&(w, h)| Frame::soft(Size::new(Abs::pt(w), Abs::pt(h))))
+            .collect();
+        render_merged(
+            &frames,
+            1.0,
+            Color::WHITE,
+            Abs::pt(gap),
+            Color::BLACK,
+            NonZeroUsize::new(columns).unwrap(),
+        )
+    }
+
+    /// Whether the pixel at the given position belongs to a frame.
+    fn is_frame(pixmap: &sk::Pixmap, x: u32, y: u32) -> bool {
+        pixmap.pixel(x, y).unwrap().red() == u8::MAX
+    }
+
+    #[test]
+    fn test_render_merged_columns() {
+        // Columns are as wide as their widest frame and rows as high as their
+        // highest frame.
+        let pixmap = merge(&[(10.0, 5.0), (4.0, 8.0), (6.0, 3.0)], 2.0, 2).unwrap();
+        assert_eq!(
+            (pixmap.width(), pixmap.height()),
+            (2 + 10 + 2 + 4 + 2, 2 + 8 + 2 + 3 + 2)
+        );
+
+        // The first row.
+        assert!(!is_frame(&pixmap, 1, 1));
+        assert!(is_frame(&pixmap, 2, 2));
+        assert!(is_frame(&pixmap, 11, 6));
+        assert!(!is_frame(&pixmap, 11, 7));
+        assert!(!is_frame(&pixmap, 12, 2));
+        assert!(is_frame(&pixmap, 14, 2));
+        assert!(is_frame(&pixmap, 17, 9));
+
+        // The second row, whose second cell stays empty.
+        assert!(is_frame(&pixmap, 2, 12));
+        assert!(is_frame(&pixmap, 7, 14));
+        assert!(!is_frame(&pixmap, 8, 14));
+        assert!(!is_frame(&pixmap, 14, 12));
+    }
+
+    #[test]
+    fn test_render_merged_more_columns_than_frames() {
+        let pixmap = merge(&[(10.0, 5.0), (4.0, 8.0)], 0.0, 5).unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (14, 8));
+        assert!(is_frame(&pixmap, 13, 7));
+        assert!(!is_frame(&pixmap, 9, 7));
+    }
+
+    #[test]
+    fn test_render_merged_no_frames() {
+        assert!(merge(&[], 0.0, 1).is_none());
+        assert!(merge(&[], 10.0, 3).is_none());
+    }
+}
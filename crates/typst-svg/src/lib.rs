@@ -2,15 +2,18 @@ use std::collections::HashMap;
 use std::f32::consts::TAU;
 use std::fmt::{self, Display, Formatter, Write};
 use std::io::Read;
+use std::num::NonZeroUsize;
 
 use base64::Engine;
 use ecow::{eco_format, EcoString};
 use ttf_parser::{GlyphId, OutlineBuilder};
 use typst::foundations::Repr;
+use typst::introspection::Meta;
 use typst::layout::{
-    Abs, Angle, Axes, Frame, FrameItem, FrameKind, GroupItem, Point, Quadrant, Ratio,
-    Size, Transform,
+    Abs, Angle, Axes, Frame, FrameItem, FrameKind, GroupItem, PageRanges, Point,
+    Position, Quadrant, Ratio, Size, Transform,
 };
+use typst::model::{Destination, Document};
 use typst::text::{Font, TextItem};
 use typst::util::hash128;
 use typst::visualize::{
@@ -25,6 +28,9 @@ use xmlwriter::XmlWriter;
 const CONIC_SEGMENT: usize = 360;
 
 /// Export a frame into a SVG file.
+///
+/// Links to URLs are exported as clickable areas. Links within a document are
+/// not, as a single frame does not contain their targets.
 #[tracing::instrument(skip_all)]
 pub fn svg(frame: &Frame) -> String {
     let mut renderer = SVGRenderer::new();
@@ -35,27 +41,61 @@ pub fn svg(frame: &Frame) -> String {
     renderer.finalize()
 }
 
-/// Export multiple frames into a single SVG file.
+/// Export the pages of a document into a single SVG file.
 ///
-/// The padding will be added around and between the individual frames.
+/// The pages are laid out in a grid with the given number of columns. The
+/// padding will be added around and between the individual pages. If
+/// `page_ranges` is given, only the pages it includes are exported. Unlike
+/// with [`svg`], links within the document are exported, too, unless they
+/// point to a page that is not exported.
 #[tracing::instrument(skip_all)]
-pub fn svg_merged(frames: &[Frame], padding: Abs) -> String {
-    let width = 2.0 * padding
-        + frames.iter().map(|frame| frame.width()).max().unwrap_or_default();
-    let height = padding + frames.iter().map(|page| page.height() + padding).sum::<Abs>();
+pub fn svg_merged(
+    document: &Document,
+    padding: Abs,
+    columns: NonZeroUsize,
+    page_ranges: Option<&PageRanges>,
+) -> String {
+    let includes = |i: usize| page_ranges.map_or(true, |r| r.includes_page_index(i));
+    let frames: Vec<(usize, &Frame)> = document
+        .pages
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| includes(i))
+        .collect();
+
+    // Each column is as wide as its widest page and each row as high as its
+    // highest page.
+    let columns = columns.get().min(frames.len().max(1));
+    let mut widths = vec![Abs::zero(); columns];
+    let mut heights = vec![Abs::zero(); (frames.len() + columns - 1) / columns];
+    for (k, (_, frame)) in frames.iter().enumerate() {
+        widths[k % columns].set_max(frame.width());
+        heights[k / columns].set_max(frame.height());
+    }
+
+    let width = padding + widths.iter().map(|&w| w + padding).sum::<Abs>();
+    let height = padding + heights.iter().map(|&h| h + padding).sum::<Abs>();
     let size = Size::new(width, height);
 
     let mut renderer = SVGRenderer::new();
+    renderer.resolve_links(document, &frames);
     renderer.write_header(size);
 
-    let [x, mut y] = [padding; 2];
-    for frame in frames {
-        let ts = Transform::translate(x, y);
-        let state = State::new(frame.size(), Transform::identity());
-        renderer.render_frame(state, ts, frame);
-        y += frame.height() + padding;
+    let mut offsets = vec![None; document.pages.len()];
+    let mut y = padding;
+    for (row, chunk) in frames.chunks(columns).enumerate() {
+        let mut x = padding;
+        for (column, &(i, frame)) in chunk.iter().enumerate() {
+            let ts = Transform::translate(x, y);
+            let state = State::new(frame.size(), Transform::identity());
+            renderer.render_frame(state, ts, frame);
+            offsets[i] = Some(Point::new(x, y));
+            x += widths[column] + padding;
+        }
+        y += heights[row] + padding;
     }
 
+    renderer.write_anchors(&offsets);
     renderer.finalize()
 }
 
@@ -97,6 +137,12 @@ struct SVGRenderer {
     patterns: Deduplicator<Pattern>,
     /// These are the gradients that compose a conic gradient.
     conic_subgradients: Deduplicator<SVGSubGradient>,
+    /// The anchors that in-document links point to. They are placed at their
+    /// positions once all pages are rendered.
+    anchors: Deduplicator<Position>,
+    /// Maps from in-document link destinations to the anchors they point to.
+    /// Only filled when a whole document is rendered.
+    link_targets: HashMap<Destination, Id>,
 }
 
 /// Contextual information for rendering.
@@ -226,6 +272,33 @@ impl SVGRenderer {
             conic_subgradients: Deduplicator::new('s'),
             pattern_refs: Deduplicator::new('p'),
             patterns: Deduplicator::new('t'),
+            anchors: Deduplicator::new('a'),
+            link_targets: HashMap::new(),
+        }
+    }
+
+    /// Resolve the destinations of all in-document links on the given pages
+    /// to anchors. Destinations on other pages are left unresolved.
+    fn resolve_links(&mut self, document: &Document, frames: &[(usize, &Frame)]) {
+        let mut destinations = vec![];
+        for (_, frame) in frames {
+            collect_destinations(frame, &mut destinations);
+        }
+
+        for dest in destinations {
+            let pos = match dest {
+                Destination::Url(_) => continue,
+                Destination::Position(pos) => *pos,
+                Destination::Location(loc) => document.introspector.position(*loc),
+            };
+
+            let index = pos.page.get() - 1;
+            if !frames.iter().any(|&(i, _)| i == index) {
+                continue;
+            }
+
+            let id = self.anchors.insert_with(hash128(&pos), || pos);
+            self.link_targets.insert(dest.clone(), id);
         }
     }
 
@@ -268,7 +341,7 @@ impl SVGRenderer {
         }
 
         for (pos, item) in frame.items() {
-            // File size optimization
+            // File size optimization. Links are rendered below.
             if matches!(item, FrameItem::Meta(_, _)) {
                 continue;
             }
@@ -296,9 +369,57 @@ impl SVGRenderer {
             self.xml.end_element();
         }
 
+        // Links are rendered on top of everything else so that they can be
+        // clicked.
+        for (pos, item) in frame.items() {
+            if let FrameItem::Meta(Meta::Link(dest), size) = item {
+                self.render_link(*pos, dest, *size);
+            }
+        }
+
         self.xml.end_element();
     }
 
+    /// Render a link as a transparent, clickable area.
+    fn render_link(&mut self, pos: Point, dest: &Destination, size: Size) {
+        let href = match dest {
+            Destination::Url(url) => url.clone(),
+            _ => match self.link_targets.get(dest) {
+                Some(id) => eco_format!("#{id}"),
+                None => return,
+            },
+        };
+
+        self.xml.start_element("a");
+        self.xml.write_attribute("xlink:href", &href);
+        self.xml.start_element("rect");
+        self.xml.write_attribute("class", "typst-link");
+        self.xml.write_attribute("x", &pos.x.to_pt());
+        self.xml.write_attribute("y", &pos.y.to_pt());
+        self.xml.write_attribute("width", &size.x.to_pt());
+        self.xml.write_attribute("height", &size.y.to_pt());
+        self.xml.write_attribute("fill", "transparent");
+        self.xml.end_element();
+        self.xml.end_element();
+    }
+
+    /// Place the anchors that in-document links point to, given the offsets
+    /// of the exported pages.
+    fn write_anchors(&mut self, offsets: &[Option<Point>]) {
+        for (id, pos) in self.anchors.iter() {
+            let Some(offset) = offsets[pos.page.get() - 1] else { continue };
+            self.xml.start_element("rect");
+            self.xml.write_attribute("id", &id);
+            self.xml.write_attribute("class", "typst-anchor");
+            self.xml.write_attribute("x", &(offset.x + pos.point.x).to_pt());
+            self.xml.write_attribute("y", &(offset.y + pos.point.y).to_pt());
+            self.xml.write_attribute("width", "0");
+            self.xml.write_attribute("height", "0");
+            self.xml.write_attribute("fill", "none");
+            self.xml.end_element();
+        }
+    }
+
     /// Render a group. If the group has `clips` set to true, a clip path will
     /// be created.
     fn render_group(&mut self, state: State, group: &GroupItem) {
@@ -355,6 +476,32 @@ impl SVGRenderer {
         }
 
         self.xml.end_element();
+        self.render_text_layer(text);
+    }
+
+    /// Render the text of a text item as an invisible text element on top of
+    /// its glyphs. This makes the text selectable and searchable. The element
+    /// is stretched to the width of the glyphs so that a selection covers
+    /// them, even if the viewer uses a different font.
+    fn render_text_layer(&mut self, text: &TextItem) {
+        let width = text.width().to_pt();
+        if text.text.is_empty() || width <= 0.0 {
+            return;
+        }
+
+        self.xml.start_element("text");
+        self.xml.write_attribute("class", "typst-text-layer");
+        self.xml.write_attribute("font-family", &text.font.info().family);
+        self.xml.write_attribute("font-size", &text.size.to_pt());
+        self.xml.write_attribute("textLength", &width);
+        self.xml.write_attribute("lengthAdjust", "spacingAndGlyphs");
+        self.xml.write_attribute("fill", "transparent");
+        self.xml.write_attribute("xml:space", "preserve");
+        // The writer only escapes `<` and would indent the text.
+        self.xml.set_preserve_whitespaces(true);
+        self.xml.write_text(&text.text.replace("&", "&amp;"));
+        self.xml.end_element();
+        self.xml.set_preserve_whitespaces(false);
     }
 
     /// Render a glyph defined by an SVG.
@@ -1282,6 +1429,17 @@ impl<T> Deduplicator<T> {
     }
 }
 
+/// Collect the destinations of all links in a frame and its groups.
+fn collect_destinations<'a>(frame: &'a Frame, destinations: &mut Vec<&'a Destination>) {
+    for (_, item) in frame.items() {
+        match item {
+            FrameItem::Group(group) => collect_destinations(&group.frame, destinations),
+            FrameItem::Meta(Meta::Link(dest), _) => destinations.push(dest),
+            _ => {}
+        }
+    }
+}
+
 /// Identifies a `<def>`.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 struct Id(char, u128, usize);
@@ -1,7 +1,9 @@
 use comemo::{Prehashed, Tracked, TrackedMut};
 use ecow::{eco_format, EcoVec};
 
-use crate::diag::{bail, error, At, HintedStrResult, SourceResult, Trace, Tracepoint};
+use crate::diag::{
+    bail, error, warning, At, HintedStrResult, SourceResult, Trace, Tracepoint,
+};
 use crate::engine::Engine;
 use crate::eval::{Access, Eval, FlowEvent, Route, Tracer, Vm};
 use crate::foundations::{
@@ -63,9 +65,21 @@ impl Eval for ast::FuncCall<'_> {
 
             // Handle plugins.
             if let Value::Plugin(plugin) = &target {
-                let bytes = args.all::<Bytes>()?;
-                args.finish()?;
-                return Ok(plugin.call(&field, bytes).at(span)?.into_value());
+                let (output, printed) =
+                    if plugin.signature(&field).is_some_and(|sig| sig.typed) {
+                        let values = args.all::<Value>()?;
+                        args.finish()?;
+                        plugin.call_typed(&field, values).at(span)?
+                    } else {
+                        let bytes = args.all::<Bytes>()?;
+                        args.finish()?;
+                        let (output, printed) = plugin.call(&field, bytes).at(span)?;
+                        (output.into_value(), printed)
+                    };
+                for line in printed {
+                    vm.engine.tracer.warn(warning!(span, "plugin printed: {line}"));
+                }
+                return Ok(output);
             }
 
             // Prioritize associated functions on the value's type (i.e.,
@@ -7,7 +7,8 @@ use wasmi::{AsContext, AsContextMut};
 
 use crate::diag::{bail, At, SourceResult, StrResult};
 use crate::engine::Engine;
-use crate::foundations::{func, repr, scope, ty, Bytes};
+use crate::foundations::{func, repr, scope, ty, Array, Bytes, Dict, Value};
+use crate::loading::{decode_cbor, encode_cbor};
 use crate::syntax::Spanned;
 use crate::World;
 
@@ -19,14 +20,15 @@ use crate::World;
 /// functions that perform the necessary conversions between native Typst types
 /// and bytes.
 ///
-/// Plugins run in isolation from your system, which means that printing,
-/// reading files, or anything like that will not be supported for security
-/// reasons. To run as a plugin, a program needs to be compiled to a 32-bit
-/// shared WebAssembly library. Many compilers will use the
+/// Plugins run in isolation from your system, which means that reading files,
+/// accessing the network, or anything like that will not be supported for
+/// security reasons. To run as a plugin, a program needs to be compiled to a
+/// 32-bit shared WebAssembly library. Many compilers will use the
 /// [WASI ABI](https://wasi.dev/) by default or as their only option (e.g.
-/// emscripten), which allows printing, reading files, etc. This ABI will not
-/// directly work with Typst. You will either need to compile to a different
-/// target or [stub all functions](https://github.com/astrale-sharp/wasm-minimal-protocol/blob/master/wasi-stub).
+/// emscripten), which allows printing, reading files, etc. This ABI does not
+/// work with Typst out of the box. You can either compile to a different
+/// target, [stub all functions](https://github.com/astrale-sharp/wasm-minimal-protocol/blob/master/wasi-stub),
+/// or load the plugin with `wasi: true` as described [below](#wasi).
 ///
 /// # Plugins and Packages
 /// Plugins are distributed as packages. A package can make use of a plugin
@@ -42,6 +44,27 @@ use crate::World;
 /// particular, if a plugin function is called twice with the same arguments,
 /// Typst might cache the results and call your function only once.
 ///
+/// # WASI
+/// When loaded with `wasi: true`, a plugin can import functions from the
+/// `wasi_snapshot_preview1` module. Typst provides a small, deterministic
+/// subset of them:
+///
+/// - Text written to stdout and stderr is shown as warnings at the call site.
+/// - There is no file system, no environment variables, and no command line
+///   arguments. Reading from stdin immediately hits the end of the input.
+/// - The clock always reports time zero and the random number generator
+///   produces the same bytes in every call.
+/// - All other WASI functions are present, but report that they are not
+///   supported.
+///
+/// If the module exports an `_initialize` function, as WASI reactors do, it
+/// is called once after loading.
+///
+/// # Resource limits
+/// Plugins can be restricted in how much work they may do per call with the
+/// `fuel` argument and in how much memory they may use with the `memory`
+/// argument. Exceeding a limit aborts the call with an error.
+///
 /// # Example
 /// ```example
 /// #let myplugin = plugin("hello.wasm")
@@ -105,6 +128,24 @@ use crate::World;
 ///   immediately after this function returns. If the message should be
 ///   interpreted as an error message, it should be encoded as UTF-8.
 ///
+/// ## Typed functions
+/// Instead of byte buffers, plugin functions can also take and return arbitrary
+/// Typst values. To declare such functions, a plugin exports a function
+/// `typst_manifest` that takes no arguments and follows the protocol above. It
+/// must return a [CBOR]($cbor)-encoded dictionary whose `functions` key holds
+/// an array of dictionaries with the following keys:
+///
+/// - `name`: The name of an exported function.
+/// - `params`: The names of the function's parameters. Defaults to no
+///   parameters.
+/// - `docs`: An optional description of the function.
+/// - `typed`: Whether the function takes and returns values instead of bytes.
+///   Defaults to `{true}`.
+///
+/// When a typed function is called, each argument is encoded as CBOR and the
+/// function's output is decoded from CBOR. The manifest is also used by
+/// editors to suggest the plugin's functions along with their parameters.
+///
 /// # Resources
 /// For more resources, check out the
 /// [wasm-minimal-protocol repository](https://github.com/astrale-sharp/wasm-minimal-protocol).
@@ -123,12 +164,41 @@ pub struct Plugin(Arc<Repr>);
 struct Repr {
     /// The raw WebAssembly bytes.
     bytes: Bytes,
+    /// How the plugin was loaded.
+    config: PluginConfig,
     /// The function defined by the WebAssembly module.
     functions: Vec<(EcoString, wasmi::Func)>,
+    /// The signatures declared in the plugin's manifest.
+    signatures: Vec<PluginSignature>,
     /// Owns all data associated with the WebAssembly module.
     store: Mutex<Store>,
 }
 
+/// How a plugin is loaded and run.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PluginConfig {
+    /// Whether to provide the deterministic subset of WASI preview 1.
+    pub wasi: bool,
+    /// The maximum amount of fuel a single call may consume.
+    pub fuel: Option<u64>,
+    /// The maximum size of the plugin's memory in bytes.
+    pub memory: Option<usize>,
+}
+
+/// The signature of a plugin function, as declared in the plugin's manifest.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PluginSignature {
+    /// The name of the function.
+    pub name: EcoString,
+    /// The names of the function's parameters.
+    pub params: Vec<EcoString>,
+    /// A description of the function.
+    pub docs: Option<EcoString>,
+    /// Whether the arguments and the result are Typst values transported as
+    /// CBOR instead of raw bytes.
+    pub typed: bool,
+}
+
 /// Owns all data associated with the WebAssembly module.
 type Store = wasmi::Store<StoreData>;
 
@@ -145,6 +215,45 @@ struct StoreData {
     args: Vec<Bytes>,
     output: Vec<u8>,
     memory_error: Option<MemoryError>,
+    /// What the plugin wrote to stdout and stderr through WASI.
+    printed: Vec<u8>,
+    /// The state of the deterministic random number generator for WASI.
+    rng: u64,
+    /// Restricts the growth of the plugin's memory.
+    limiter: MemoryLimiter,
+}
+
+/// Enforces the memory limit of a plugin.
+#[derive(Default)]
+struct MemoryLimiter {
+    /// The maximum size of the memory in bytes.
+    limit: Option<usize>,
+    /// Whether the plugin tried to grow its memory beyond the limit.
+    exceeded: bool,
+}
+
+impl wasmi::ResourceLimiter for MemoryLimiter {
+    fn memory_growing(
+        &mut self,
+        _: usize,
+        desired: usize,
+        _: Option<usize>,
+    ) -> Result<bool, wasmi::errors::MemoryError> {
+        if self.limit.is_some_and(|limit| desired > limit) {
+            self.exceeded = true;
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    fn table_growing(
+        &mut self,
+        _: u32,
+        _: u32,
+        _: Option<u32>,
+    ) -> Result<bool, wasmi::errors::TableError> {
+        Ok(true)
+    }
 }
 
 #[scope]
@@ -156,19 +265,37 @@ impl Plugin {
         engine: &mut Engine,
         /// Path to a WebAssembly file.
         path: Spanned<EcoString>,
+        /// Whether to provide a deterministic subset of WASI preview 1 to the
+        /// plugin. See the [WASI section](#wasi) for details.
+        #[named]
+        #[default(false)]
+        wasi: bool,
+        /// The maximum amount of fuel a single call of a plugin function may
+        /// consume. Roughly, executing one WebAssembly instruction consumes one
+        /// unit of fuel. If set to `{none}`, calls are not limited.
+        #[named]
+        #[default]
+        fuel: Option<u64>,
+        /// The maximum size of the plugin's memory in bytes. If set to
+        /// `{none}`, the memory is only limited by WebAssembly itself.
+        #[named]
+        #[default]
+        memory: Option<usize>,
     ) -> SourceResult<Plugin> {
         let Spanned { v: path, span } = path;
         let id = span.resolve_path(&path).at(span)?;
         let data = engine.world.file(id).at(span)?;
-        Plugin::new(data).at(span)
+        Plugin::new(data, PluginConfig { wasi, fuel, memory }).at(span)
     }
 }
 
 impl Plugin {
     /// Create a new plugin from raw WebAssembly bytes.
     #[comemo::memoize]
-    pub fn new(bytes: Bytes) -> StrResult<Plugin> {
-        let engine = wasmi::Engine::default();
+    pub fn new(bytes: Bytes, config: PluginConfig) -> StrResult<Plugin> {
+        let mut engine_config = wasmi::Config::default();
+        engine_config.consume_fuel(config.fuel.is_some());
+        let engine = wasmi::Engine::new(&engine_config);
         let module = wasmi::Module::new(&engine, bytes.as_slice())
             .map_err(|err| format!("failed to load WebAssembly module ({err})"))?;
 
@@ -188,11 +315,29 @@ impl Plugin {
             )
             .unwrap();
 
-        let mut store = Store::new(&engine, StoreData::default());
+        if config.wasi {
+            define_wasi(&mut linker, &module);
+        } else if module.imports().any(|import| import.module() == WASI_MODULE) {
+            bail!("plugin imports WASI functions, but was not loaded with `wasi: true`");
+        }
+
+        let data = StoreData {
+            limiter: MemoryLimiter { limit: config.memory, exceeded: false },
+            ..Default::default()
+        };
+        let mut store = Store::new(&engine, data);
+        store.limiter(|data| &mut data.limiter);
+        prepare_run(&mut store, config);
+
         let instance = linker
             .instantiate(&mut store, &module)
-            .and_then(|pre_instance| pre_instance.start(&mut store))
-            .map_err(|e| eco_format!("{e}"))?;
+            .map_err(|err| {
+                limit_error(&store, config, &err).unwrap_or_else(|| eco_format!("{err}"))
+            })?
+            .start(&mut store)
+            .map_err(|err| {
+                limit_error(&store, config, &err).unwrap_or_else(|| eco_format!("{err}"))
+            })?;
 
         // Ensure that the plugin exports its memory.
         if !matches!(
@@ -203,7 +348,7 @@ impl Plugin {
         }
 
         // Collect exported functions.
-        let functions = instance
+        let mut functions: Vec<(EcoString, wasmi::Func)> = instance
             .exports(&store)
             .filter_map(|export| {
                 let name = export.name().into();
@@ -211,12 +356,54 @@ impl Plugin {
             })
             .collect();
 
-        Ok(Plugin(Arc::new(Repr { bytes, functions, store: Mutex::new(store) })))
+        // Initialize WASI reactors. The initializer must only run once, so it
+        // is not callable from Typst.
+        if config.wasi {
+            if let Some(i) = functions.iter().position(|(name, _)| name == "_initialize")
+            {
+                let (_, func) = functions.remove(i);
+                prepare_run(&mut store, config);
+                func.call(&mut store, &[], &mut []).map_err(|err| {
+                    limit_error(&store, config, &err)
+                        .unwrap_or_else(|| eco_format!("plugin panicked: {err}"))
+                })?;
+                store.data_mut().printed.clear();
+            }
+        }
+
+        // Read the signatures of typed functions from the manifest.
+        let mut signatures = vec![];
+        if let Some(i) = functions.iter().position(|(name, _)| name == MANIFEST) {
+            let (_, func) = functions.remove(i);
+            let (output, _) = invoke(&mut store, config, MANIFEST, func, vec![])?;
+            signatures = decode_cbor(output.as_slice())
+                .and_then(parse_manifest)
+                .and_then(|signatures| {
+                    check_manifest(&store, &functions, &signatures)?;
+                    Ok(signatures)
+                })
+                .map_err(|err| eco_format!("plugin manifest is invalid ({err})"))?;
+        }
+
+        Ok(Plugin(Arc::new(Repr {
+            bytes,
+            config,
+            functions,
+            signatures,
+            store: Mutex::new(store),
+        })))
     }
 
     /// Call the plugin function with the given `name`.
+    ///
+    /// Returns the function's output along with the lines the plugin printed
+    /// through WASI.
     #[comemo::memoize]
-    pub fn call(&self, name: &str, args: Vec<Bytes>) -> StrResult<Bytes> {
+    pub fn call(
+        &self,
+        name: &str,
+        args: Vec<Bytes>,
+    ) -> StrResult<(Bytes, Vec<EcoString>)> {
         // Find the function with the given name.
         let func = self
             .0
@@ -229,67 +416,31 @@ impl Plugin {
             })?;
 
         let mut store = self.0.store.lock().unwrap();
-        let ty = func.ty(store.as_context());
-
-        // Check function signature.
-        if ty.params().iter().any(|&v| v != wasmi::core::ValueType::I32) {
-            bail!(
-                "plugin function `{name}` has a parameter that is not a 32-bit integer"
-            );
-        }
-        if ty.results() != [wasmi::core::ValueType::I32] {
-            bail!("plugin function `{name}` does not return exactly one 32-bit integer");
-        }
-
-        // Check inputs.
-        let expected = ty.params().len();
-        let given = args.len();
-        if expected != given {
-            bail!(
-                "plugin function takes {expected} argument{}, but {given} {} given",
-                if expected == 1 { "" } else { "s" },
-                if given == 1 { "was" } else { "were" },
-            );
-        }
-
-        // Collect the lengths of the argument buffers.
-        let lengths = args
-            .iter()
-            .map(|a| wasmi::Value::I32(a.len() as i32))
-            .collect::<Vec<_>>();
-
-        // Store the input data.
-        store.data_mut().args = args;
-
-        // Call the function.
-        let mut code = wasmi::Value::I32(-1);
-        func.call(store.as_context_mut(), &lengths, std::slice::from_mut(&mut code))
-            .map_err(|err| eco_format!("plugin panicked: {err}"))?;
-        if let Some(MemoryError { offset, length, write }) =
-            store.data_mut().memory_error.take()
-        {
-            return Err(eco_format!(
-                "plugin tried to {kind} out of bounds: pointer {offset:#x} is out of bounds for {kind} of length {length}",
-                kind = if write { "write" } else { "read" }
-            ));
-        }
-
-        // Extract the returned data.
-        let output = std::mem::take(&mut store.data_mut().output);
+        invoke(&mut store, self.0.config, name, func, args)
+    }
 
-        // Parse the functions return value.
-        match code {
-            wasmi::Value::I32(0) => {}
-            wasmi::Value::I32(1) => match std::str::from_utf8(&output) {
-                Ok(message) => bail!("plugin errored with: {message}"),
-                Err(_) => {
-                    bail!("plugin errored, but did not return a valid error message")
-                }
-            },
-            _ => bail!("plugin did not respect the protocol"),
-        };
+    /// Call the plugin function with the given `name`, transporting the
+    /// arguments and the result as CBOR.
+    ///
+    /// Returns the function's result along with the lines the plugin printed
+    /// through WASI.
+    pub fn call_typed(
+        &self,
+        name: &str,
+        args: Vec<Value>,
+    ) -> StrResult<(Value, Vec<EcoString>)> {
+        let args = args.iter().map(encode_cbor).collect::<StrResult<_>>()?;
+        let (output, printed) = self.call(name, args)?;
+        let value = decode_cbor(output.as_slice()).map_err(|err| {
+            eco_format!("plugin function `{name}` returned invalid data ({err})")
+        })?;
+        Ok((value, printed))
+    }
 
-        Ok(output.into())
+    /// The signature of the function with the given `name`, if the plugin
+    /// declares it in its manifest.
+    pub fn signature(&self, name: &str) -> Option<&PluginSignature> {
+        self.0.signatures.iter().find(|sig| sig.name == name)
     }
 
     /// An iterator over all the function names defined by the plugin.
@@ -312,13 +463,184 @@ impl repr::Repr for Plugin {
 
 impl PartialEq for Plugin {
     fn eq(&self, other: &Self) -> bool {
-        self.0.bytes == other.0.bytes
+        self.0.bytes == other.0.bytes && self.0.config == other.0.config
     }
 }
 
 impl Hash for Plugin {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.0.bytes.hash(state);
+        self.0.config.hash(state);
+    }
+}
+
+/// Call a plugin function according to the protocol.
+fn invoke(
+    store: &mut Store,
+    config: PluginConfig,
+    name: &str,
+    func: wasmi::Func,
+    args: Vec<Bytes>,
+) -> StrResult<(Bytes, Vec<EcoString>)> {
+    let ty = func.ty(store.as_context());
+
+    // Check function signature.
+    if ty.params().iter().any(|&v| v != wasmi::core::ValueType::I32) {
+        bail!("plugin function `{name}` has a parameter that is not a 32-bit integer");
+    }
+    if ty.results() != [wasmi::core::ValueType::I32] {
+        bail!("plugin function `{name}` does not return exactly one 32-bit integer");
+    }
+
+    // Check inputs.
+    let expected = ty.params().len();
+    let given = args.len();
+    if expected != given {
+        bail!(
+            "plugin function takes {expected} argument{}, but {given} {} given",
+            if expected == 1 { "" } else { "s" },
+            if given == 1 { "was" } else { "were" },
+        );
+    }
+
+    // Collect the lengths of the argument buffers.
+    let lengths = args
+        .iter()
+        .map(|a| wasmi::Value::I32(a.len() as i32))
+        .collect::<Vec<_>>();
+
+    // Store the input data.
+    store.data_mut().args = args;
+
+    // Call the function.
+    prepare_run(store, config);
+    let mut code = wasmi::Value::I32(-1);
+    func.call(store.as_context_mut(), &lengths, std::slice::from_mut(&mut code))
+        .map_err(|err| {
+            limit_error(store, config, &err)
+                .unwrap_or_else(|| eco_format!("plugin panicked: {err}"))
+        })?;
+    if let Some(MemoryError { offset, length, write }) =
+        store.data_mut().memory_error.take()
+    {
+        return Err(eco_format!(
+            "plugin tried to {kind} out of bounds: pointer {offset:#x} is out of bounds for {kind} of length {length}",
+            kind = if write { "write" } else { "read" }
+        ));
+    }
+
+    // Extract the returned data.
+    let output = std::mem::take(&mut store.data_mut().output);
+    let printed = std::mem::take(&mut store.data_mut().printed);
+
+    // Parse the functions return value.
+    match code {
+        wasmi::Value::I32(0) => {}
+        wasmi::Value::I32(1) => match std::str::from_utf8(&output) {
+            Ok(message) => bail!("plugin errored with: {message}"),
+            Err(_) => {
+                bail!("plugin errored, but did not return a valid error message")
+            }
+        },
+        _ => bail!("plugin did not respect the protocol"),
+    };
+
+    let printed = String::from_utf8_lossy(&printed)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(EcoString::from)
+        .collect();
+
+    Ok((output.into(), printed))
+}
+
+/// Parse the signatures from a plugin's manifest.
+fn parse_manifest(manifest: Value) -> StrResult<Vec<PluginSignature>> {
+    let mut manifest = manifest.cast::<Dict>()?;
+    let functions = manifest.take("functions")?.cast::<Array>()?;
+    manifest.finish(&["functions"])?;
+
+    functions
+        .into_iter()
+        .map(|function| {
+            let mut function = function.cast::<Dict>()?;
+            let name = function.take("name")?.cast()?;
+            let params = function.take("params").ok().map(Value::cast).transpose()?;
+            let docs = function.take("docs").ok().map(Value::cast).transpose()?;
+            let typed = function.take("typed").ok().map(Value::cast).transpose()?;
+            function.finish(&["name", "params", "docs", "typed"])?;
+            Ok(PluginSignature {
+                name,
+                params: params.unwrap_or_default(),
+                docs,
+                typed: typed.unwrap_or(true),
+            })
+        })
+        .collect()
+}
+
+/// Ensure that the manifest matches the functions exported by the plugin.
+fn check_manifest(
+    store: &Store,
+    functions: &[(EcoString, wasmi::Func)],
+    signatures: &[PluginSignature],
+) -> StrResult<()> {
+    for sig in signatures {
+        let name = &sig.name;
+        let Some((_, func)) = functions.iter().find(|(v, _)| v == name) else {
+            bail!("declares function `{name}`, which the plugin does not export");
+        };
+
+        let expected = func.ty(store).params().len();
+        let given = sig.params.len();
+        if expected != given {
+            bail!(
+                "declares {given} parameter{} for `{name}`, but the function takes {expected}",
+                if given == 1 { "" } else { "s" },
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Reset the per-run state of the store before running plugin code.
+fn prepare_run(store: &mut Store, config: PluginConfig) {
+    if let Some(fuel) = config.fuel {
+        let remaining = store.consume_fuel(0).unwrap_or(0);
+        store.add_fuel(fuel.saturating_sub(remaining)).ok();
+    }
+
+    let data = store.data_mut();
+    data.limiter.exceeded = false;
+    data.printed.clear();
+    data.rng = RNG_SEED;
+}
+
+/// Describe an error that happened because the plugin exceeded one of its
+/// limits or exited.
+fn limit_error(
+    store: &Store,
+    config: PluginConfig,
+    err: &wasmi::Error,
+) -> Option<EcoString> {
+    if store.data().limiter.exceeded {
+        if let Some(limit) = config.memory {
+            return Some(eco_format!(
+                "plugin exceeded its memory limit of {limit} bytes"
+            ));
+        }
+    }
+
+    let wasmi::Error::Trap(trap) = err else { return None };
+    if let Some(status) = trap.i32_exit_status() {
+        return Some(eco_format!("plugin exited with code {status}"));
+    }
+
+    match (trap.trap_code(), config.fuel) {
+        (Some(wasmi::core::TrapCode::OutOfFuel), Some(fuel)) => {
+            Some(eco_format!("plugin exceeded its fuel limit of {fuel}"))
+        }
+        _ => None,
     }
 }
 
@@ -359,3 +681,256 @@ fn wasm_minimal_protocol_send_result_to_host(
     }
     caller.data_mut().output = buffer;
 }
+
+/// The name of the function that returns the plugin's manifest.
+const MANIFEST: &str = "typst_manifest";
+
+/// The name of the module that WASI functions are imported from.
+const WASI_MODULE: &str = "wasi_snapshot_preview1";
+
+/// The seed of the random number generator, reset before each call.
+const RNG_SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+/// WASI error number: No error occurred.
+const ERRNO_SUCCESS: i32 = 0;
+
+/// WASI error number: Bad file descriptor.
+const ERRNO_BADF: i32 = 8;
+
+/// WASI error number: Bad address.
+const ERRNO_FAULT: i32 = 21;
+
+/// WASI error number: Function not supported.
+const ERRNO_NOSYS: i32 = 52;
+
+/// WASI error number: Invalid seek.
+const ERRNO_SPIPE: i32 = 70;
+
+/// Define the supported subset of WASI preview 1 and stubs for all other WASI
+/// functions imported by the module.
+fn define_wasi(linker: &mut wasmi::Linker<StoreData>, module: &wasmi::Module) {
+    linker
+        .func_wrap(WASI_MODULE, "fd_write", wasi_fd_write)
+        .unwrap()
+        .func_wrap(WASI_MODULE, "fd_read", wasi_fd_read)
+        .unwrap()
+        .func_wrap(WASI_MODULE, "fd_close", |_: wasmi::Caller<StoreData>, fd: i32| {
+            if is_stdio(fd) {
+                ERRNO_SUCCESS
+            } else {
+                ERRNO_BADF
+            }
+        })
+        .unwrap()
+        .func_wrap(
+            WASI_MODULE,
+            "fd_seek",
+            |_: wasmi::Caller<StoreData>, fd: i32, _: i64, _: i32, _: i32| {
+                if is_stdio(fd) {
+                    ERRNO_SPIPE
+                } else {
+                    ERRNO_BADF
+                }
+            },
+        )
+        .unwrap()
+        .func_wrap(WASI_MODULE, "fd_fdstat_get", wasi_fd_fdstat_get)
+        .unwrap()
+        .func_wrap(
+            WASI_MODULE,
+            "fd_prestat_get",
+            |_: wasmi::Caller<StoreData>, _: i32, _: i32| ERRNO_BADF,
+        )
+        .unwrap()
+        .func_wrap(
+            WASI_MODULE,
+            "fd_prestat_dir_name",
+            |_: wasmi::Caller<StoreData>, _: i32, _: i32, _: i32| ERRNO_BADF,
+        )
+        .unwrap()
+        .func_wrap(WASI_MODULE, "environ_sizes_get", wasi_sizes_get)
+        .unwrap()
+        .func_wrap(
+            WASI_MODULE,
+            "environ_get",
+            |_: wasmi::Caller<StoreData>, _: i32, _: i32| ERRNO_SUCCESS,
+        )
+        .unwrap()
+        .func_wrap(WASI_MODULE, "args_sizes_get", wasi_sizes_get)
+        .unwrap()
+        .func_wrap(
+            WASI_MODULE,
+            "args_get",
+            |_: wasmi::Caller<StoreData>, _: i32, _: i32| ERRNO_SUCCESS,
+        )
+        .unwrap()
+        .func_wrap(
+            WASI_MODULE,
+            "clock_time_get",
+            |mut caller: wasmi::Caller<StoreData>, _: i32, _: i64, ptr: i32| {
+                wasi_write(&mut caller, ptr, &0u64.to_le_bytes())
+            },
+        )
+        .unwrap()
+        .func_wrap(
+            WASI_MODULE,
+            "clock_res_get",
+            |mut caller: wasmi::Caller<StoreData>, _: i32, ptr: i32| {
+                wasi_write(&mut caller, ptr, &1u64.to_le_bytes())
+            },
+        )
+        .unwrap()
+        .func_wrap(WASI_MODULE, "random_get", wasi_random_get)
+        .unwrap()
+        .func_wrap(WASI_MODULE, "sched_yield", |_: wasmi::Caller<StoreData>| {
+            ERRNO_SUCCESS
+        })
+        .unwrap()
+        .func_wrap(
+            WASI_MODULE,
+            "proc_exit",
+            |_: wasmi::Caller<StoreData>, code: i32| -> Result<(), wasmi::core::Trap> {
+                Err(wasmi::core::Trap::i32_exit(code))
+            },
+        )
+        .unwrap();
+
+    // Let all other WASI functions report that they are not supported. Defining
+    // a stub fails for the functions defined above, which is fine.
+    for import in module.imports() {
+        if import.module() != WASI_MODULE {
+            continue;
+        }
+
+        let Some(ty) = import.ty().func() else { continue };
+        let returns_errno = ty.results() == [wasmi::core::ValueType::I32];
+        linker
+            .func_new(WASI_MODULE, import.name(), ty.clone(), move |_, _, results| {
+                if returns_errno {
+                    results[0] = wasmi::Value::I32(ERRNO_NOSYS);
+                }
+                Ok(())
+            })
+            .ok();
+    }
+}
+
+/// Whether the file descriptor is stdin, stdout, or stderr.
+fn is_stdio(fd: i32) -> bool {
+    (0..=2).contains(&fd)
+}
+
+/// Read from the plugin's memory for a WASI function.
+fn wasi_read(caller: &wasmi::Caller<StoreData>, ptr: i32, len: usize) -> Option<Vec<u8>> {
+    let memory = caller.get_export("memory")?.into_memory()?;
+    let mut buffer = vec![0; len];
+    memory.read(caller, ptr as u32 as usize, &mut buffer).ok()?;
+    Some(buffer)
+}
+
+/// Write into the plugin's memory for a WASI function and return the
+/// resulting error number.
+fn wasi_write(caller: &mut wasmi::Caller<StoreData>, ptr: i32, data: &[u8]) -> i32 {
+    let Some(memory) = caller.get_export("memory").and_then(|e| e.into_memory()) else {
+        return ERRNO_FAULT;
+    };
+    match memory.write(caller, ptr as u32 as usize, data) {
+        Ok(()) => ERRNO_SUCCESS,
+        Err(_) => ERRNO_FAULT,
+    }
+}
+
+/// Read a WASI I/O vector, i.e. a list of pointer-length pairs.
+fn wasi_read_iovs(
+    caller: &wasmi::Caller<StoreData>,
+    iovs: i32,
+    iovs_len: i32,
+) -> Option<Vec<(i32, usize)>> {
+    let raw = wasi_read(caller, iovs, 8 * iovs_len as u32 as usize)?;
+    Some(
+        raw.chunks_exact(8)
+            .map(|chunk| {
+                let ptr = i32::from_le_bytes(chunk[..4].try_into().unwrap());
+                let len = u32::from_le_bytes(chunk[4..].try_into().unwrap());
+                (ptr, len as usize)
+            })
+            .collect(),
+    )
+}
+
+/// Write to stdout or stderr, which are captured.
+fn wasi_fd_write(
+    mut caller: wasmi::Caller<StoreData>,
+    fd: i32,
+    iovs: i32,
+    iovs_len: i32,
+    nwritten: i32,
+) -> i32 {
+    if fd != 1 && fd != 2 {
+        return ERRNO_BADF;
+    }
+
+    let Some(iovs) = wasi_read_iovs(&caller, iovs, iovs_len) else {
+        return ERRNO_FAULT;
+    };
+
+    let mut written = 0u32;
+    for (ptr, len) in iovs {
+        let Some(data) = wasi_read(&caller, ptr, len) else { return ERRNO_FAULT };
+        caller.data_mut().printed.extend(data);
+        written += len as u32;
+    }
+
+    wasi_write(&mut caller, nwritten, &written.to_le_bytes())
+}
+
+/// Read from stdin, which is always empty.
+fn wasi_fd_read(
+    mut caller: wasmi::Caller<StoreData>,
+    fd: i32,
+    _: i32,
+    _: i32,
+    nread: i32,
+) -> i32 {
+    if fd != 0 {
+        return ERRNO_BADF;
+    }
+    wasi_write(&mut caller, nread, &0u32.to_le_bytes())
+}
+
+/// Describe stdin, stdout, and stderr as character devices.
+fn wasi_fd_fdstat_get(mut caller: wasmi::Caller<StoreData>, fd: i32, ptr: i32) -> i32 {
+    if !is_stdio(fd) {
+        return ERRNO_BADF;
+    }
+
+    // The layout is: filetype (u8), flags (u16), rights base (u64), and rights
+    // inheriting (u64). Filetype 2 is a character device.
+    let mut stat = [0; 24];
+    stat[0] = 2;
+    wasi_write(&mut caller, ptr, &stat)
+}
+
+/// Report that there are no environment variables or arguments.
+fn wasi_sizes_get(mut caller: wasmi::Caller<StoreData>, count: i32, size: i32) -> i32 {
+    match wasi_write(&mut caller, count, &0u32.to_le_bytes()) {
+        ERRNO_SUCCESS => wasi_write(&mut caller, size, &0u32.to_le_bytes()),
+        errno => errno,
+    }
+}
+
+/// Fill a buffer with deterministic pseudo-random bytes.
+fn wasi_random_get(mut caller: wasmi::Caller<StoreData>, ptr: i32, len: i32) -> i32 {
+    let mut data = Vec::with_capacity(len as u32 as usize);
+    while data.len() < len as u32 as usize {
+        // SplitMix64.
+        let state = &mut caller.data_mut().rng;
+        *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        data.extend((z ^ (z >> 31)).to_le_bytes());
+    }
+    data.truncate(len as u32 as usize);
+    wasi_write(&mut caller, ptr, &data)
+}
@@ -900,6 +900,25 @@ where
     }
 }
 
+/// A variant of fold for foldable optional (`Option<T>`) values where an inner
+/// `None` value isn't erased, but instead replaced by the outer value.
+pub trait AlternativeFold {
+    /// Attempts to fold this inner value with an outer value. However, if
+    /// either value is `None`, returns the other one instead of folding.
+    fn fold_or(self, outer: Self) -> Self;
+}
+
+impl<T: Fold<Output = T>> AlternativeFold for Option<T> {
+    fn fold_or(self, outer: Self) -> Self {
+        match (self, outer) {
+            (Some(inner), Some(outer)) => Some(inner.fold(outer)),
+            // If one of values is `None`, return the other one instead of
+            // folding.
+            (inner, outer) => inner.or(outer),
+        }
+    }
+}
+
 impl<T> Fold for Vec<T> {
     type Output = Vec<T>;
 
@@ -30,6 +30,7 @@ use crate::foundations::{
     Unlabellable,
 };
 use crate::layout::PdfPageLabel;
+use crate::model::form::Widget;
 use crate::model::{Destination, Numbering};
 
 /// Interactions between document parts.
@@ -83,6 +84,11 @@ pub enum Meta {
     PageNumbering(Option<Numbering>),
     /// A PDF page label of the current page.
     PdfPageLabel(PdfPageLabel),
+    /// A widget of an interactive form field.
+    Widget(Widget),
+    /// Indicates that content is an artifact of pagination (like a page header
+    /// or footer) rather than part of the document's logical structure.
+    Artifact,
     /// Indicates that content should be hidden. This variant doesn't appear
     /// in the final frames as it is removed alongside the content that should
     /// be hidden.
@@ -100,6 +106,8 @@ impl Debug for Meta {
             Self::Elem(content) => write!(f, "Elem({:?})", content.func()),
             Self::PageNumbering(value) => write!(f, "PageNumbering({value:?})"),
             Self::PdfPageLabel(label) => write!(f, "PdfPageLabel({label:?})"),
+            Self::Widget(widget) => write!(f, "Widget({:?})", widget.name),
+            Self::Artifact => f.pad("Artifact"),
             Self::Hide => f.pad("Hide"),
         }
     }
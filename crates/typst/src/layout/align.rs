@@ -327,6 +327,52 @@ cast! {
     }
 }
 
+/// Where to place something horizontally on the outside of an area.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum OuterHAlign {
+    #[default]
+    Start,
+    Left,
+    Right,
+    End,
+}
+
+impl OuterHAlign {
+    /// Resolve the side based on the horizontal direction into either
+    /// `Left` or `Right`.
+    pub const fn fix(self, dir: Dir) -> Self {
+        match (self, dir.is_positive()) {
+            (Self::Start, true) | (Self::End, false) => Self::Left,
+            (Self::Left, _) => Self::Left,
+            (Self::Right, _) => Self::Right,
+            (Self::End, true) | (Self::Start, false) => Self::Right,
+        }
+    }
+}
+
+impl From<OuterHAlign> for HAlign {
+    fn from(align: OuterHAlign) -> Self {
+        match align {
+            OuterHAlign::Start => Self::Start,
+            OuterHAlign::Left => Self::Left,
+            OuterHAlign::Right => Self::Right,
+            OuterHAlign::End => Self::End,
+        }
+    }
+}
+
+cast! {
+    OuterHAlign,
+    self => HAlign::from(self).into_value(),
+    align: HAlign => match align {
+        HAlign::Start => Self::Start,
+        HAlign::Left => Self::Left,
+        HAlign::Right => Self::Right,
+        HAlign::End => Self::End,
+        v => bail!("expected `start`, `left`, `right`, or `end`, found {}", v.repr()),
+    }
+}
+
 /// Where to align something vertically.
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum VAlign {
@@ -4,7 +4,8 @@ use crate::diag::SourceResult;
 use crate::engine::Engine;
 use crate::foundations::{elem, Behave, Behaviour, Content, StyleChain};
 use crate::layout::{
-    Abs, Axes, Dir, Fragment, Frame, Layout, Length, Point, Ratio, Regions, Rel, Size,
+    Abs, Axes, Columns, Dir, Fragment, Frame, Layout, Length, Point, Ratio, Regions, Rel,
+    Size,
 };
 use crate::text::TextElem;
 use crate::util::Numeric;
@@ -84,6 +85,11 @@ impl Layout for ColumnsElem {
             .skip(1)
             .collect();
 
+        // Parent-scoped floats are placed in the first column and span all of
+        // them.
+        let dir = TextElem::dir_in(styles);
+        let offset = if dir == Dir::LTR { Abs::zero() } else { width - regions.size.x };
+
         // Create the pod regions.
         let pod = Regions {
             size: Size::new(width, regions.size.y),
@@ -92,13 +98,13 @@ impl Layout for ColumnsElem {
             last: regions.last,
             expand: Axes::new(true, regions.expand.y),
             root: regions.root,
+            columns: Some(Columns { count: columns, width: regions.size.x, offset }),
         };
 
         // Layout the children.
         let mut frames = body.layout(engine, styles, pod)?.into_iter();
         let mut finished = vec![];
 
-        let dir = TextElem::dir_in(styles);
         let total_regions = (frames.len() as f32 / columns as f32).ceil() as usize;
 
         // Stitch together the columns for each region.
@@ -5,12 +5,12 @@ use crate::engine::Engine;
 use crate::foundations::{elem, Content, NativeElement, Resolve, Smart, StyleChain};
 use crate::introspection::{Meta, MetaElem};
 use crate::layout::{
-    Abs, AlignElem, Axes, BlockElem, ColbreakElem, ColumnsElem, FixedAlign, Fr, Fragment,
-    Frame, FrameItem, Layout, PlaceElem, Point, Regions, Rel, Size, Spacing, VAlign,
-    VElem,
+    Abs, AlignElem, Axes, BlockElem, ColbreakElem, Columns, ColumnsElem, FixedAlign, Fr,
+    Fragment, Frame, FrameItem, Layout, PlaceElem, PlacementScope, Point, Regions, Rel,
+    Size, Spacing, VAlign, VElem,
 };
-use crate::model::{FootnoteElem, FootnoteEntry, ParElem};
-use crate::util::Numeric;
+use crate::model::{DocumentElem, FootnoteElem, FootnoteEntry, ParElem};
+use crate::util::{hash128, Numeric};
 use crate::visualize::{
     CircleElem, EllipseElem, ImageElem, LineElem, PathElem, PolygonElem, RectElem,
     SquareElem,
@@ -112,6 +112,16 @@ struct FlowLayouter<'a> {
     items: Vec<FlowItem>,
     /// A queue of floats.
     pending_floats: Vec<FlowItem>,
+    /// How the regions are arranged if they are the columns of a parent
+    /// region.
+    columns: Option<Columns>,
+    /// The index of the current region's column within its parent region.
+    column: usize,
+    /// The heights at the top and bottom of the parent region that are taken
+    /// up by parent-scoped floats and must be kept free in each column.
+    reserved: (Abs, Abs),
+    /// A queue of parent-scoped floats for the next parent region.
+    pending_parent_floats: Vec<FlowItem>,
     /// Whether we have any footnotes in the current region.
     has_footnotes: bool,
     /// Footnote configuration.
@@ -138,7 +148,8 @@ enum FlowItem {
     /// item after it (for orphan prevention), and whether it is movable
     /// (to keep it together with its footnotes).
     Frame { frame: Frame, align: Axes<FixedAlign>, sticky: bool, movable: bool },
-    /// An absolutely placed frame.
+    /// An absolutely placed frame and whether it spans all columns of the
+    /// parent region.
     Placed {
         frame: Frame,
         x_align: FixedAlign,
@@ -146,6 +157,7 @@ enum FlowItem {
         delta: Axes<Rel<Abs>>,
         float: bool,
         clearance: Abs,
+        parent: bool,
     },
     /// A footnote frame (can also be the separator).
     Footnote(Frame),
@@ -180,9 +192,10 @@ impl<'a> FlowLayouter<'a> {
     fn new(mut regions: Regions<'a>, styles: StyleChain<'a>) -> Self {
         let expand = regions.expand;
 
-        // Disable vertical expansion & root for children.
+        // Disable vertical expansion, root & columns for children.
         regions.expand.y = false;
         let root = std::mem::replace(&mut regions.root, false);
+        let columns = regions.columns.take();
 
         Self {
             root,
@@ -193,6 +206,10 @@ impl<'a> FlowLayouter<'a> {
             last_was_par: false,
             items: vec![],
             pending_floats: vec![],
+            columns,
+            column: 0,
+            reserved: (Abs::zero(), Abs::zero()),
+            pending_parent_floats: vec![],
             has_footnotes: false,
             footnote_config: FootnoteConfig {
                 separator: FootnoteEntry::separator_in(styles),
@@ -263,11 +280,33 @@ impl<'a> FlowLayouter<'a> {
             }
         }
 
-        for (i, frame) in lines.into_iter().enumerate() {
+        // In tagged documents, mark all lines as belonging to the paragraph
+        // and its ancestors so that exporters can reconstruct the document
+        // structure. The paragraph isn't realized like other locatable
+        // elements because a trailing meta element would interfere with the
+        // flow's spacing and orphan handling.
+        let mut meta = vec![];
+        if DocumentElem::tagged_in(styles) {
+            let mut elem = par.clone().pack();
+            elem.set_location(engine.locator.locate(hash128(&elem)));
+            meta.push(Meta::Elem(elem));
+            meta.extend(
+                MetaElem::data_in(styles)
+                    .into_iter()
+                    .filter(|meta| matches!(meta, Meta::Elem(_))),
+            );
+        }
+
+        for (i, mut frame) in lines.into_iter().enumerate() {
             if i > 0 {
                 self.layout_item(engine, FlowItem::Absolute(leading, true))?;
             }
 
+            if !meta.is_empty() {
+                frame.meta_iter(meta.iter().cloned());
+                frame.group_tagged();
+            }
+
             self.layout_item(
                 engine,
                 FlowItem::Frame { frame, align, sticky: false, movable: true },
@@ -313,8 +352,28 @@ impl<'a> FlowLayouter<'a> {
             align.x().unwrap_or_default().resolve(styles)
         });
         let y_align = alignment.map(|align| align.y().map(VAlign::fix));
-        let frame = placed.layout(engine, styles, self.regions)?.into_frame();
-        let item = FlowItem::Placed { frame, x_align, y_align, delta, float, clearance };
+
+        // Parent-scoped floats are laid out at the width of the parent region.
+        let mut regions = self.regions;
+        let parent = placed.scope(styles) == PlacementScope::Parent;
+        let parent = match self.columns {
+            Some(columns) if parent => {
+                regions.size.x = columns.width;
+                true
+            }
+            _ => false,
+        };
+
+        let frame = placed.layout(engine, styles, regions)?.into_frame();
+        let item = FlowItem::Placed {
+            frame,
+            x_align,
+            y_align,
+            delta,
+            float,
+            clearance,
+            parent,
+        };
         self.layout_item(engine, item)
     }
 
@@ -423,12 +482,22 @@ impl<'a> FlowLayouter<'a> {
                 ref mut y_align,
                 float: true,
                 clearance,
+                parent,
                 ..
             } => {
+                let fits = self.regions.size.y.fits(frame.height() + clearance)
+                    || self.regions.in_last();
+
+                // A parent-scoped float can only be placed in the first column
+                // as the previous columns are already finished. Otherwise,
+                // queue it for the next parent region.
+                if parent && (self.column > 0 || !fits) {
+                    self.pending_parent_floats.push(item);
+                    return Ok(());
+                }
+
                 // If the float doesn't fit, queue it for the next region.
-                if !self.regions.size.y.fits(frame.height() + clearance)
-                    && !self.regions.in_last()
-                {
+                if !fits {
                     self.pending_floats.push(item);
                     return Ok(());
                 }
@@ -452,6 +521,15 @@ impl<'a> FlowLayouter<'a> {
 
                 self.regions.size.y -= frame.height();
 
+                // Keep the space free in the other columns.
+                if parent {
+                    if *y_align == Smart::Custom(Some(FixedAlign::End)) {
+                        self.reserved.1 += frame.height();
+                    } else {
+                        self.reserved.0 += frame.height();
+                    }
+                }
+
                 // Find footnotes in the frame.
                 if self.root {
                     let mut notes = vec![];
@@ -479,7 +557,7 @@ impl<'a> FlowLayouter<'a> {
             self.finished.push(Frame::soft(self.initial));
             self.regions.next();
             self.initial = self.regions.size;
-            return Ok(());
+            return self.next_column(engine);
         }
 
         // Trim weak spacing.
@@ -561,8 +639,22 @@ impl<'a> FlowLayouter<'a> {
                     offset += frame.height();
                     output.push_frame(pos, frame);
                 }
-                FlowItem::Placed { frame, x_align, y_align, delta, float, .. } => {
-                    let x = x_align.position(size.x - frame.width());
+                FlowItem::Placed {
+                    frame,
+                    x_align,
+                    y_align,
+                    delta,
+                    float,
+                    parent,
+                    ..
+                } => {
+                    let x = match self.columns {
+                        Some(columns) if parent => {
+                            columns.offset
+                                + x_align.position(columns.width - frame.width())
+                        }
+                        _ => x_align.position(size.x - frame.width()),
+                    };
                     let y = if float {
                         match y_align {
                             Smart::Custom(Some(FixedAlign::Start)) => {
@@ -606,6 +698,8 @@ impl<'a> FlowLayouter<'a> {
         self.initial = self.regions.size;
         self.has_footnotes = false;
 
+        self.next_column(engine)?;
+
         // Try to place floats.
         for item in std::mem::take(&mut self.pending_floats) {
             self.layout_item(engine, item)?;
@@ -614,6 +708,44 @@ impl<'a> FlowLayouter<'a> {
         Ok(())
     }
 
+    /// Keep track of the column after advancing to the next region.
+    fn next_column(&mut self, engine: &mut Engine) -> SourceResult<()> {
+        let Some(columns) = self.columns else { return Ok(()) };
+        self.column = (self.column + 1) % columns.count;
+        if self.column == 0 {
+            // Try to place the parent-scoped floats in the new parent region.
+            self.reserved = (Abs::zero(), Abs::zero());
+            for item in std::mem::take(&mut self.pending_parent_floats) {
+                self.layout_item(engine, item)?;
+            }
+        } else {
+            self.reserve();
+        }
+        Ok(())
+    }
+
+    /// Keep the space taken up by parent-scoped floats free in the current
+    /// column by adding empty floats of the same height.
+    fn reserve(&mut self) {
+        let (top, bottom) = self.reserved;
+        for (height, align) in [(top, FixedAlign::Start), (bottom, FixedAlign::End)] {
+            if height.is_zero() {
+                continue;
+            }
+
+            self.regions.size.y -= height;
+            self.items.push(FlowItem::Placed {
+                frame: Frame::soft(Size::with_y(height)),
+                x_align: FixedAlign::Start,
+                y_align: Smart::Custom(Some(align)),
+                delta: Axes::splat(Rel::zero()),
+                float: true,
+                clearance: Abs::zero(),
+                parent: false,
+            });
+        }
+    }
+
     /// Finish layouting and return the resulting fragment.
     fn finish(mut self, engine: &mut Engine) -> SourceResult<Fragment> {
         if self.expand.y {
@@ -623,7 +755,7 @@ impl<'a> FlowLayouter<'a> {
         }
 
         self.finish_region(engine, true)?;
-        while !self.items.is_empty() {
+        while !self.items.is_empty() || !self.pending_parent_floats.is_empty() {
             self.finish_region(engine, true)?;
         }
 
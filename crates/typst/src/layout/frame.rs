@@ -11,6 +11,7 @@ use crate::introspection::{Meta, MetaElem};
 use crate::layout::{
     Abs, Axes, Corners, FixedAlign, Length, Point, Rel, Sides, Size, Transform,
 };
+use crate::model::DocumentElem;
 use crate::syntax::Span;
 use crate::text::TextItem;
 use crate::util::Numeric;
@@ -283,6 +284,40 @@ impl Frame {
     pub fn meta(&mut self, styles: StyleChain, force: bool) {
         if force || !self.is_empty() {
             self.meta_iter(MetaElem::data_in(styles));
+            if DocumentElem::tagged_in(styles) {
+                self.group_tagged();
+            }
+        }
+    }
+
+    /// Move the items of a frame that belongs to an element or is marked as
+    /// an artifact into a group of their own.
+    ///
+    /// This is used for tagged documents: Exporters need to know which items
+    /// an element's metadata applies to, and the group keeps the items
+    /// together when the frame is inlined into its parent.
+    pub fn group_tagged(&mut self) {
+        let tagged = self
+            .items
+            .iter()
+            .map_while(|(_, item)| match item {
+                FrameItem::Meta(meta, _) => Some(meta),
+                _ => None,
+            })
+            .any(|meta| matches!(meta, Meta::Elem(_) | Meta::Artifact));
+
+        // Frames with nothing but metadata have no content to group, and
+        // layout treats them as out-of-flow.
+        let content = self
+            .items
+            .iter()
+            .any(|(_, item)| !matches!(item, FrameItem::Meta(..)));
+
+        if tagged && content && self.kind().is_soft() {
+            let mut outer = Frame::soft(self.size);
+            outer.baseline = self.baseline;
+            let inner = std::mem::replace(self, outer);
+            self.push(Point::zero(), FrameItem::Group(GroupItem::new(inner)));
         }
     }
 
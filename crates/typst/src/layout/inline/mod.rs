@@ -13,19 +13,21 @@ use self::shaping::{
 use crate::diag::{bail, SourceResult};
 use crate::engine::{Engine, Route};
 use crate::eval::Tracer;
-use crate::foundations::{Content, Resolve, Smart, StyleChain};
-use crate::introspection::{Introspector, Locator, MetaElem};
+use crate::foundations::{
+    Content, Label, NativeElement, Resolve, Smart, StyleChain, Styles,
+};
+use crate::introspection::{Introspector, Locator, Meta, MetaElem};
 use crate::layout::{
-    Abs, AlignElem, Axes, BoxElem, Dir, Em, FixedAlign, Fr, Fragment, Frame, HElem,
-    Layout, Point, Regions, Size, Sizing, Spacing,
+    Abs, AlignElem, Axes, BoxElem, Dir, Em, FixedAlign, Fr, Fragment, Frame, FrameItem,
+    HElem, Layout, Point, Regions, Size, Sizing, Spacing,
 };
 use crate::math::EquationElem;
-use crate::model::{Linebreaks, ParElem};
+use crate::model::{Linebreaks, ParElem, ParLine, ParLineMarker};
 use crate::syntax::Span;
 use crate::text::{
     Lang, LinebreakElem, SmartQuoteElem, SmartQuoter, SmartQuotes, SpaceElem, TextElem,
 };
-use crate::util::Numeric;
+use crate::util::{hash128, Numeric};
 use crate::World;
 
 /// Layouts content inline.
@@ -133,6 +135,8 @@ struct Preparation<'a> {
     linebreaks: Smart<Linebreaks>,
     /// The text size.
     size: Abs,
+    /// The marker to add to each line if lines are numbered.
+    line_marker: Option<ParLineMarker>,
 }
 
 impl<'a> Preparation<'a> {
@@ -547,6 +551,9 @@ fn prepare<'a>(
         },
     );
 
+    // Lines of inline content nested in a line are not numbered on their own.
+    let unnumbered = Styles::from(ParLine::set_numbering(None));
+
     let mut cursor = 0;
     let mut items = Vec::with_capacity(segments.len());
 
@@ -568,7 +575,8 @@ fn prepare<'a>(
             },
             Segment::Equation(equation) => {
                 let pod = Regions::one(region, Axes::splat(false));
-                let mut frame = equation.layout(engine, styles, pod)?.into_frame();
+                let mut frame =
+                    equation.layout(engine, styles.chain(&unnumbered), pod)?.into_frame();
                 frame.translate(Point::with_y(TextElem::baseline_in(styles)));
                 items.push(Item::Frame(frame));
             }
@@ -577,7 +585,8 @@ fn prepare<'a>(
                     items.push(Item::Fractional(v, Some((elem, styles))));
                 } else {
                     let pod = Regions::one(region, Axes::splat(false));
-                    let mut frame = elem.layout(engine, styles, pod)?.into_frame();
+                    let mut frame =
+                        elem.layout(engine, styles.chain(&unnumbered), pod)?.into_frame();
                     frame.translate(Point::with_y(TextElem::baseline_in(styles)));
                     items.push(Item::Frame(frame));
                 }
@@ -611,6 +620,7 @@ fn prepare<'a>(
         leading: ParElem::leading_in(styles),
         linebreaks: ParElem::linebreaks_in(styles),
         size: TextElem::size_in(styles),
+        line_marker: ParLineMarker::from_styles(styles),
     })
 }
 
@@ -1301,7 +1311,9 @@ fn commit(
                 if let Some((elem, styles)) = elem {
                     let region = Size::new(amount, full);
                     let pod = Regions::one(region, Axes::new(true, false));
-                    let mut frame = elem.layout(engine, *styles, pod)?.into_frame();
+                    let unnumbered = Styles::from(ParLine::set_numbering(None));
+                    let mut frame =
+                        elem.layout(engine, styles.chain(&unnumbered), pod)?.into_frame();
                     frame.translate(Point::with_y(TextElem::baseline_in(*styles)));
                     push(&mut offset, frame);
                 } else {
@@ -1335,9 +1347,36 @@ fn commit(
         output.push_frame(Point::new(x, y), frame);
     }
 
+    // Mark the line's baseline so that the page can number it.
+    if let Some(marker) = &p.line_marker {
+        let mut labels = vec![];
+        collect_labels(&mut labels, &output);
+        let mut marker = marker.clone().with_labels(labels).pack();
+        marker.set_location(engine.locator.locate(hash128(&marker)));
+        output
+            .push(Point::with_y(top), FrameItem::Meta(Meta::Elem(marker), Size::zero()));
+    }
+
     Ok(output)
 }
 
+/// Collect the labels of the elements in a line.
+fn collect_labels(labels: &mut Vec<Label>, frame: &Frame) {
+    for (_, item) in frame.items() {
+        match item {
+            FrameItem::Group(group) => collect_labels(labels, &group.frame),
+            FrameItem::Meta(Meta::Elem(elem), _) => {
+                if let Some(label) = elem.label() {
+                    if !labels.contains(&label) {
+                        labels.push(label);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
 /// Return a line's items in visual order.
 fn reorder<'a>(line: &'a Line<'a>) -> (Vec<&Item<'a>>, bool) {
     let mut reordered = vec![];
@@ -58,7 +58,7 @@ pub use self::page::*;
 pub use self::place::*;
 pub use self::point::*;
 pub use self::ratio::*;
-pub use self::regions::Regions;
+pub use self::regions::{Columns, Regions};
 pub use self::rel::*;
 pub use self::repeat::*;
 pub use self::sides::*;
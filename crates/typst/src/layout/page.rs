@@ -1,7 +1,13 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::num::NonZeroUsize;
+use std::ops::RangeInclusive;
 use std::ptr;
 use std::str::FromStr;
+use std::sync::Arc;
+
+use comemo::Tracked;
+use smallvec::smallvec;
 
 use crate::diag::{bail, SourceResult};
 use crate::engine::Engine;
@@ -9,15 +15,20 @@ use crate::foundations::{
     cast, elem, AutoValue, Cast, Content, Dict, Fold, Func, NativeElement, Resolve,
     Smart, StyleChain, Value,
 };
-use crate::introspection::{Counter, CounterKey, ManualPageCounter, Meta};
+use crate::introspection::{
+    Counter, CounterKey, Introspector, Location, ManualPageCounter, Meta, MetaElem,
+};
 use crate::layout::{
-    Abs, Align, AlignElem, Axes, ColumnsElem, Dir, Fragment, Frame, HAlign, Layout,
-    Length, Point, Ratio, Regions, Rel, Sides, Size, VAlign,
+    Abs, Align, AlignElem, Axes, ColumnsElem, Dir, Fragment, Frame, FrameItem, HAlign,
+    Layout, Length, OuterHAlign, Point, Ratio, Regions, Rel, Sides, Size, Transform,
+    VAlign,
 };
 
-use crate::model::Numbering;
+use crate::model::{
+    DocumentElem, LineNumberingScope, MarginNoteElem, Numbering, ParLine, ParLineMarker,
+};
 use crate::syntax::Spanned;
-use crate::text::TextElem;
+use crate::text::{SpaceElem, SuperElem, TextElem};
 use crate::util::{NonZeroExt, Numeric, Scalar};
 use crate::visualize::Paint;
 
@@ -453,7 +464,8 @@ impl PageElem {
             // Thus, for left-bound pages, we want to swap on even pages and
             // for right-bound pages, we want to swap on odd pages.
             let mut margin = margin;
-            if two_sided && binding.swap(page_counter.physical()) {
+            let swap = two_sided && binding.swap(page_counter.physical());
+            if swap {
                 std::mem::swap(&mut margin.left, &mut margin.right);
             }
 
@@ -462,6 +474,11 @@ impl PageElem {
             frame.translate(Point::new(margin.left, margin.top));
             frame.push_positionless_meta(numbering_meta.clone());
 
+            // Realize line numbers and margin notes in the margins.
+            layout_line_numbers(engine, styles, frame, margin)?;
+            let outer_right = if two_sided { !swap } else { binding == Binding::Left };
+            layout_marginnotes(engine, styles, frame, margin, outer_right)?;
+
             // The page size with margins.
             let size = frame.size();
 
@@ -494,11 +511,12 @@ impl PageElem {
                 };
 
                 let pod = Regions::one(area, Axes::splat(true));
-                let sub = content
+                let content = content
                     .clone()
                     .styled(AlignElem::set_alignment(align))
-                    .layout(engine, styles, pod)?
-                    .into_frame();
+                    .styled(ParLine::set_numbering(None));
+                let sub =
+                    artifact(content, styles).layout(engine, styles, pod)?.into_frame();
 
                 if ptr::eq(marginal, &header) || ptr::eq(marginal, &background) {
                     frame.prepend_frame(pos, sub);
@@ -527,6 +545,242 @@ impl PageElem {
     }
 }
 
+/// Lay out the margin notes of a page in its outer margin.
+///
+/// Each note is aligned with the baseline of the line that contains it,
+/// unless it would overlap with the previous note.
+fn layout_marginnotes(
+    engine: &mut Engine,
+    styles: StyleChain,
+    frame: &mut Frame,
+    margin: Sides<Abs>,
+    outer_right: bool,
+) -> SourceResult<()> {
+    let mut notes = vec![];
+    find_marginnotes(&mut notes, frame, Transform::identity());
+    notes.sort_by_key(|&(y, _)| y);
+
+    let outer = if outer_right { margin.right } else { margin.left };
+    let align = if outer_right { Align::LEFT } else { Align::RIGHT };
+    let mut cursor = Abs::zero();
+    for (y, note) in notes {
+        // The note's properties were resolved where it was called.
+        let shared = StyleChain::default();
+        let gap = note.gap(shared);
+        let spacing = note.spacing(shared);
+        let width = note.width(shared).unwrap_or(outer - 2.0 * gap).max(Abs::zero());
+
+        let mut body = note.body().clone();
+        if let Some(numbering) = note.numbering(shared) {
+            let num = note.number(engine, &numbering)?;
+            body = SuperElem::new(num).pack() + SpaceElem::new().pack() + body;
+        }
+
+        let pod = Regions::one(Size::new(width, Abs::inf()), Axes::new(true, false));
+        let sub = body
+            .styled(AlignElem::set_alignment(align))
+            .styled(ParLine::set_numbering(None))
+            .layout(engine, styles, pod)?
+            .into_frame();
+
+        let x = if outer_right {
+            frame.width() - margin.right + gap
+        } else {
+            margin.left - gap - width
+        };
+        let y = (y - first_baseline(&sub).unwrap_or_default()).max(cursor);
+        cursor = y + sub.height() + spacing;
+        frame.push_frame(Point::new(x, y), sub);
+    }
+
+    Ok(())
+}
+
+/// Find the margin notes in a frame together with the vertical positions of
+/// their markers.
+fn find_marginnotes(
+    notes: &mut Vec<(Abs, MarginNoteElem)>,
+    frame: &Frame,
+    ts: Transform,
+) {
+    for (pos, item) in frame.items() {
+        match item {
+            FrameItem::Group(group) => {
+                let ts = ts
+                    .pre_concat(Transform::translate(pos.x, pos.y))
+                    .pre_concat(group.transform);
+                find_marginnotes(notes, &group.frame, ts);
+            }
+            // The marker is zero-sized, while other content of a numbered
+            // note, like its number, also refers to the note.
+            FrameItem::Meta(Meta::Elem(content), size) if size.is_zero() => {
+                let Some(note) = content.to::<MarginNoteElem>() else { continue };
+                if !notes.iter().any(|(_, prev)| prev.location() == note.location()) {
+                    notes.push((pos.transform(ts).y, note.clone()));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Lay out the numbers of a page's numbered lines in the margins.
+///
+/// Each number is aligned with the baseline of its line. In multi-column
+/// layouts, where lines sit side by side, each number goes into the margin
+/// nearest to its column instead of the configured one. Numbers that would
+/// still overlap with an earlier one (in the middle columns of three or more)
+/// are left out.
+fn layout_line_numbers(
+    engine: &mut Engine,
+    styles: StyleChain,
+    frame: &mut Frame,
+    margin: Sides<Abs>,
+) -> SourceResult<()> {
+    let mut markers = vec![];
+    find_line_markers(&mut markers, frame, Transform::identity());
+    if markers.is_empty() {
+        return Ok(());
+    }
+
+    // Numbers continue across pages in the order in which the lines appear
+    // in the document.
+    let indices = line_indices(engine.introspector);
+
+    // Lines sit side by side if two of them share a baseline.
+    let mut positions: Vec<_> = markers.iter().map(|(pos, _)| (pos.y, pos.x)).collect();
+    positions.sort();
+    let side_by_side = positions
+        .windows(2)
+        .any(|pair| pair[0].0.approx_eq(pair[1].0) && !pair[0].1.approx_eq(pair[1].1));
+    let center = margin.left + (frame.width() - margin.left - margin.right) / 2.0;
+
+    let mut numbers: Vec<(Abs, OuterHAlign, ParLineMarker, Frame)> = vec![];
+    for (i, (pos, marker)) in markers.into_iter().enumerate() {
+        let index = match marker.numbering_scope() {
+            LineNumberingScope::Document => {
+                indices.get(&marker.location().unwrap()).copied().unwrap_or(i)
+            }
+            LineNumberingScope::Page => i,
+        };
+
+        let side = if !side_by_side {
+            *marker.number_margin()
+        } else if pos.x < center {
+            OuterHAlign::Left
+        } else {
+            OuterHAlign::Right
+        };
+
+        // Skip numbers that would overlap with an earlier one.
+        if numbers
+            .iter()
+            .any(|(y, other, _, _)| *other == side && y.approx_eq(pos.y))
+        {
+            continue;
+        }
+
+        // The number is displayed with the styles of its paragraph.
+        let styles = styles.chain(marker.styles());
+        let pod = Regions::one(Size::splat(Abs::inf()), Axes::splat(false));
+        let content = marker
+            .numbering()
+            .apply(engine, &[index + 1])?
+            .display()
+            .styled(ParLine::set_numbering(None));
+        let sub = artifact(content, styles).layout(engine, styles, pod)?.into_frame();
+
+        numbers.push((pos.y, side, marker, sub));
+    }
+
+    // The numbers in each margin are aligned among each other within the
+    // width of the widest one.
+    let column = |side| {
+        numbers
+            .iter()
+            .filter(|(_, other, _, _)| *other == side)
+            .map(|(_, _, _, sub)| sub.width())
+            .max()
+            .unwrap_or_default()
+    };
+    let (left, right) = (column(OuterHAlign::Left), column(OuterHAlign::Right));
+
+    for (y, side, marker, sub) in numbers {
+        // The clearance was already resolved with the paragraph's styles.
+        let clearance = marker.number_clearance().abs;
+        let (x, width, towards_text) = match side {
+            OuterHAlign::Right => {
+                (frame.width() - margin.right + clearance, right, HAlign::Left)
+            }
+            _ => (margin.left - clearance - left, left, HAlign::Right),
+        };
+        let align = marker.number_align().unwrap_or(towards_text).fix(Dir::LTR);
+        let x = x + align.position(width - sub.width());
+        let y = y - first_baseline(&sub).unwrap_or_default();
+        frame.push_frame(Point::new(x, y), sub);
+    }
+
+    Ok(())
+}
+
+/// The document-wide indices of all numbered lines, by their markers'
+/// locations.
+///
+/// This is memoized so that the index is only built once per introspection
+/// iteration instead of once per page.
+#[comemo::memoize]
+fn line_indices(introspector: Tracked<Introspector>) -> Arc<HashMap<Location, usize>> {
+    let indices = introspector
+        .query(&ParLineMarker::elem().select())
+        .iter()
+        .enumerate()
+        .map(|(i, marker)| (marker.location().unwrap(), i))
+        .collect();
+    Arc::new(indices)
+}
+
+/// Mark content as an artifact of pagination if the document is tagged.
+fn artifact(content: Content, styles: StyleChain) -> Content {
+    if DocumentElem::tagged_in(styles) {
+        content.styled(MetaElem::set_data(smallvec![Meta::Artifact]))
+    } else {
+        content
+    }
+}
+
+/// Find the line markers in a frame together with their positions.
+fn find_line_markers(
+    markers: &mut Vec<(Point, ParLineMarker)>,
+    frame: &Frame,
+    ts: Transform,
+) {
+    for (pos, item) in frame.items() {
+        match item {
+            FrameItem::Group(group) => {
+                let ts = ts
+                    .pre_concat(Transform::translate(pos.x, pos.y))
+                    .pre_concat(group.transform);
+                find_line_markers(markers, &group.frame, ts);
+            }
+            FrameItem::Meta(Meta::Elem(content), _) => {
+                if let Some(marker) = content.to::<ParLineMarker>() {
+                    markers.push((pos.transform(ts), marker.clone()));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The vertical position of the first baseline in a frame.
+fn first_baseline(frame: &Frame) -> Option<Abs> {
+    frame.items().find_map(|(pos, item)| match item {
+        FrameItem::Group(group) => first_baseline(&group.frame).map(|y| pos.y + y),
+        FrameItem::Text(_) => Some(pos.y),
+        _ => None,
+    })
+}
+
 /// Specification of the page's margins.
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Margin {
@@ -750,6 +1004,34 @@ impl Parity {
     }
 }
 
+/// A selection of pages to export.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PageRanges(Vec<PageRange>);
+
+/// An inclusive range of one-based page numbers. A missing start or end
+/// leaves the range open on that side.
+pub type PageRange = RangeInclusive<Option<NonZeroUsize>>;
+
+impl PageRanges {
+    /// Create a selection from a list of ranges.
+    pub fn new(ranges: Vec<PageRange>) -> Self {
+        Self(ranges)
+    }
+
+    /// Whether the page with the given one-based number is selected.
+    pub fn includes_page(&self, page: NonZeroUsize) -> bool {
+        self.0.iter().any(|range| {
+            range.start().map_or(true, |start| start <= page)
+                && range.end().map_or(true, |end| page <= end)
+        })
+    }
+
+    /// Whether the page with the given zero-based index is selected.
+    pub fn includes_page_index(&self, index: usize) -> bool {
+        self.includes_page(NonZeroUsize::new(index + 1).unwrap())
+    }
+}
+
 /// Specification of a paper.
 #[derive(Debug, Copy, Clone, Hash)]
 pub struct Paper {
@@ -1,7 +1,7 @@
 use crate::diag::{bail, At, Hint, SourceResult};
 use crate::engine::Engine;
 use crate::foundations::{
-    elem, Behave, Behaviour, Content, NativeElement, Smart, StyleChain,
+    elem, Behave, Behaviour, Cast, Content, NativeElement, Smart, StyleChain,
 };
 use crate::layout::{Align, Axes, Em, Fragment, Layout, Length, Regions, Rel, VAlign};
 
@@ -62,6 +62,29 @@ pub struct PlaceElem {
     /// ```
     pub float: bool,
 
+    /// Relative to which containing scope the element is placed.
+    ///
+    /// Within columns, the element can either float in its own column or span
+    /// the full width of the parent region, with all columns flowing around
+    /// it. A parent-scoped float lands at the top or bottom of the current
+    /// page if it is encountered in the first column and there is enough
+    /// space. Otherwise, it moves to the next page.
+    ///
+    /// Parent-scoped placement is only available for floating placement.
+    ///
+    /// ```example
+    /// #set page(height: 150pt, columns: 2)
+    /// #place(
+    ///   top + center,
+    ///   float: true,
+    ///   scope: "parent",
+    ///   clearance: 8pt,
+    ///   text(1.4em)[*A Wide Title*],
+    /// )
+    /// #lorem(40)
+    /// ```
+    pub scope: PlacementScope,
+
     /// The amount of clearance the placed element has in a floating layout.
     #[default(Em::new(1.5).into())]
     #[resolve]
@@ -109,6 +132,12 @@ impl Layout for PlaceElem {
             return Err("automatic positioning is only available for floating placement")
                 .hint("you can enable floating placement with `place(float: true, ..)`")
                 .at(self.span());
+        } else if !float && self.scope(styles) == PlacementScope::Parent {
+            return Err(
+                "parent-scoped positioning is only available for floating placement",
+            )
+            .hint("you can enable floating placement with `place(float: true, ..)`")
+            .at(self.span());
         }
 
         let child = self
@@ -127,3 +156,14 @@ impl Behave for PlaceElem {
         Behaviour::Ignorant
     }
 }
+
+/// Relative to which containing scope something is placed.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum PlacementScope {
+    /// Place into the current column.
+    #[default]
+    Column,
+    /// Place relative to the parent, letting the content span over all
+    /// columns.
+    Parent,
+}
@@ -22,6 +22,24 @@ pub struct Regions<'a> {
     /// True for the padded page regions and columns directly in the page,
     /// false otherwise.
     pub root: bool,
+    /// How the regions are arranged if they are the columns of a parent
+    /// region.
+    pub columns: Option<Columns>,
+}
+
+/// How regions are arranged into columns of a parent region.
+///
+/// The regions then cycle through the columns of one parent region after the
+/// other.
+#[derive(Debug, Copy, Clone, PartialEq, Hash)]
+pub struct Columns {
+    /// The number of columns in each parent region.
+    pub count: usize,
+    /// The width of the parent region.
+    pub width: Abs,
+    /// The horizontal offset of the parent region relative to the first
+    /// column.
+    pub offset: Abs,
 }
 
 impl Regions<'_> {
@@ -34,6 +52,7 @@ impl Regions<'_> {
             last: None,
             expand,
             root: false,
+            columns: None,
         }
     }
 
@@ -46,6 +65,7 @@ impl Regions<'_> {
             last: Some(size.y),
             expand,
             root: false,
+            columns: None,
         }
     }
 
@@ -75,6 +95,7 @@ impl Regions<'_> {
             last: self.last.map(|y| f(Size::new(x, y)).y),
             expand: self.expand,
             root: false,
+            columns: None,
         }
     }
 
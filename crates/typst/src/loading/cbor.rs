@@ -1,6 +1,6 @@
 use ecow::{eco_format, EcoString};
 
-use crate::diag::{At, SourceResult};
+use crate::diag::{At, SourceResult, StrResult};
 use crate::engine::Engine;
 use crate::foundations::{func, scope, Bytes, Value};
 use crate::syntax::Spanned;
@@ -36,9 +36,7 @@ impl cbor {
         data: Spanned<Bytes>,
     ) -> SourceResult<Value> {
         let Spanned { v: data, span } = data;
-        ciborium::from_reader(data.as_slice())
-            .map_err(|err| eco_format!("failed to parse CBOR ({err})"))
-            .at(span)
+        decode_cbor(data.as_slice()).at(span)
     }
 
     /// Encode structured data into CBOR bytes.
@@ -48,10 +46,19 @@ impl cbor {
         value: Spanned<Value>,
     ) -> SourceResult<Bytes> {
         let Spanned { v: value, span } = value;
-        let mut res = Vec::new();
-        ciborium::into_writer(&value, &mut res)
-            .map(|_| res.into())
-            .map_err(|err| eco_format!("failed to encode value as CBOR ({err})"))
-            .at(span)
+        encode_cbor(&value).at(span)
     }
 }
+
+/// Decode a value from CBOR bytes.
+pub(crate) fn decode_cbor(data: &[u8]) -> StrResult<Value> {
+    ciborium::from_reader(data).map_err(|err| eco_format!("failed to parse CBOR ({err})"))
+}
+
+/// Encode a value into CBOR bytes.
+pub(crate) fn encode_cbor(value: &Value) -> StrResult<Bytes> {
+    let mut res = Vec::new();
+    ciborium::into_writer(value, &mut res)
+        .map(|_| res.into())
+        .map_err(|err| eco_format!("failed to encode value as CBOR ({err})"))
+}
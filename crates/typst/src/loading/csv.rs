@@ -1,8 +1,10 @@
 use ecow::{eco_format, EcoString};
 
-use crate::diag::{bail, At, SourceResult};
+use crate::diag::{bail, At, SourceResult, StrResult};
 use crate::engine::Engine;
-use crate::foundations::{cast, func, scope, Array, Dict, IntoValue, Type, Value};
+use crate::foundations::{
+    cast, func, scope, Array, Dict, IntoValue, Repr, Str, Type, Value,
+};
 use crate::loading::Readable;
 use crate::syntax::Spanned;
 use crate::World;
@@ -12,7 +14,8 @@ use crate::World;
 /// The CSV file will be read and parsed into a 2-dimensional array of strings:
 /// Each row in the CSV file will be represented as an array of strings, and all
 /// rows will be collected into a single array. Header rows will not be
-/// stripped.
+/// stripped, unless the rows are read as dictionaries keyed by the header.
+/// Fields can optionally be converted to numbers and booleans.
 ///
 /// # Example
 /// ```example
@@ -24,6 +27,7 @@ use crate::World;
 ///   ..results.flatten(),
 /// )
 /// ```
+#[allow(clippy::too_many_arguments)]
 #[func(scope, title = "CSV")]
 pub fn csv(
     /// The engine.
@@ -45,11 +49,39 @@ pub fn csv(
     #[named]
     #[default(RowType::Array)]
     row_type: RowType,
+    /// The character used to quote fields that contain the delimiter, line
+    /// breaks, or quotes. Must be a single ASCII character.
+    #[named]
+    #[default]
+    quote: Quote,
+    /// A character that marks the start of a comment line. Lines starting
+    /// with it are skipped. Must be a single ASCII character.
+    #[named]
+    #[default]
+    comment: Option<Comment>,
+    /// Whether to trim leading and trailing whitespace from fields and header
+    /// keys.
+    #[named]
+    #[default(false)]
+    trim: bool,
+    /// Whether to convert fields that look like integers, floats, or booleans
+    /// into values of these types. All other fields remain strings.
+    #[named]
+    #[default(false)]
+    infer_types: bool,
 ) -> SourceResult<Array> {
     let Spanned { v: path, span } = path;
     let id = span.resolve_path(&path).at(span)?;
     let data = engine.world.file(id).at(span)?;
-    self::csv::decode(Spanned::new(Readable::Bytes(data), span), delimiter, row_type)
+    self::csv::decode(
+        Spanned::new(Readable::Bytes(data), span),
+        delimiter,
+        row_type,
+        quote,
+        comment,
+        trim,
+        infer_types,
+    )
 }
 
 #[scope]
@@ -74,6 +106,27 @@ impl csv {
         #[named]
         #[default(RowType::Array)]
         row_type: RowType,
+        /// The character used to quote fields that contain the delimiter, line
+        /// breaks, or quotes. Must be a single ASCII character.
+        #[named]
+        #[default]
+        quote: Quote,
+        /// A character that marks the start of a comment line. Lines starting
+        /// with it are skipped. Must be a single ASCII character.
+        #[named]
+        #[default]
+        comment: Option<Comment>,
+        /// Whether to trim leading and trailing whitespace from fields and
+        /// header keys.
+        #[named]
+        #[default(false)]
+        trim: bool,
+        /// Whether to convert fields that look like integers, floats, or
+        /// booleans into values of these types. All other fields remain
+        /// strings.
+        #[named]
+        #[default(false)]
+        infer_types: bool,
     ) -> SourceResult<Array> {
         let Spanned { v: data, span } = data;
         let has_headers = row_type == RowType::Dict;
@@ -81,6 +134,19 @@ impl csv {
         let mut builder = ::csv::ReaderBuilder::new();
         builder.has_headers(has_headers);
         builder.delimiter(delimiter.0 as u8);
+        builder.quote(quote.0 as u8);
+        builder.comment(comment.as_ref().map(|comment| comment.0 as u8));
+        if trim {
+            builder.trim(::csv::Trim::All);
+        }
+
+        let convert = |field: &str| {
+            if infer_types {
+                infer_value(field)
+            } else {
+                field.into_value()
+            }
+        };
 
         // Counting lines from 1 by default.
         let mut line_offset: usize = 1;
@@ -104,16 +170,26 @@ impl csv {
             // Original solution was to use line from error, but that is
             // incorrect with `has_headers` set to `false`. See issue:
             // https://github.com/BurntSushi/rust-csv/issues/184
+            // Skipped comment lines are not counted as records, so we need the
+            // error's position in that case.
             let line = line + line_offset;
-            let row = result.map_err(|err| format_csv_error(err, line)).at(span)?;
+            let row = result
+                .map_err(|err| {
+                    let line = match err.position() {
+                        Some(pos) if comment.is_some() => pos.line() as usize,
+                        _ => line,
+                    };
+                    format_csv_error(err, line)
+                })
+                .at(span)?;
             let item = if let Some(headers) = &headers {
                 let mut dict = Dict::new();
                 for (field, value) in headers.iter().zip(&row) {
-                    dict.insert(field.into(), value.into_value());
+                    dict.insert(field.into(), convert(value));
                 }
                 dict.into_value()
             } else {
-                let sub = row.into_iter().map(|field| field.into_value()).collect();
+                let sub = row.into_iter().map(convert).collect();
                 Value::Array(sub)
             };
             array.push(item);
@@ -121,6 +197,97 @@ impl csv {
 
         Ok(array)
     }
+
+    /// Encode structured data into a CSV string.
+    ///
+    /// ```example
+    /// #csv.encode((
+    ///   ("Name", "Weight"),
+    ///   ("Debby", 20),
+    /// ))
+    /// ```
+    #[func(title = "Encode CSV")]
+    pub fn encode(
+        /// The rows to encode.
+        ///
+        /// Either all rows are arrays of fields, or all rows are dictionaries
+        /// mapping from header keys to fields. For dictionaries, a header row
+        /// with the keys of the first row is written first. Fields can be
+        /// strings, numbers, booleans, or `{none}` for an empty field. Floats
+        /// must be finite, as `{calc.nan}` and `{calc.inf}` would be read back
+        /// as text.
+        rows: Spanned<Array>,
+        /// The delimiter that separates columns in the CSV file.
+        /// Must be a single ASCII character.
+        #[named]
+        #[default]
+        delimiter: Delimiter,
+    ) -> SourceResult<Str> {
+        let Spanned { v: rows, span } = rows;
+
+        let mut builder = ::csv::WriterBuilder::new();
+        builder.delimiter(delimiter.0 as u8);
+        let mut writer = builder.from_writer(vec![]);
+
+        // For dictionary rows, the keys of the first row make up the header.
+        let mut headers: Option<Vec<Str>> = None;
+        if let Some(Value::Dict(first)) = rows.as_slice().first() {
+            let keys: Vec<Str> = first.iter().map(|(key, _)| key.clone()).collect();
+            writer
+                .write_record(keys.iter().map(|key| key.as_str()))
+                .map_err(format_csv_encode_error)
+                .at(span)?;
+            headers = Some(keys);
+        }
+
+        for (i, row) in rows.into_iter().enumerate() {
+            let fields: Vec<Value> = match (row, &headers) {
+                (Value::Array(fields), None) => fields.into_iter().collect(),
+                (Value::Dict(mut dict), Some(keys)) => {
+                    let fields = keys
+                        .iter()
+                        .map(|key| dict.take(key).unwrap_or(Value::None))
+                        .collect();
+                    if let Some((key, _)) = dict.iter().next() {
+                        bail!(
+                            span,
+                            "row {} has key {} that is not in the header",
+                            i + 1,
+                            key.repr()
+                        );
+                    }
+                    fields
+                }
+                (v, _) => bail!(
+                    span,
+                    "expected all rows to be arrays or all to be dictionaries, \
+                     found {} in row {}",
+                    v.ty(),
+                    i + 1
+                ),
+            };
+
+            let fields = fields
+                .into_iter()
+                .map(format_csv_field)
+                .collect::<StrResult<Vec<_>>>()
+                .at(span)?;
+            writer
+                .write_record(fields.iter().map(|field| field.as_str()))
+                .map_err(format_csv_encode_error)
+                .at(span)?;
+        }
+
+        let data = writer
+            .into_inner()
+            .map_err(|err| eco_format!("failed to encode value as CSV ({err})"))
+            .at(span)?;
+        let string = std::str::from_utf8(&data)
+            .map_err(|_| "CSV is not valid utf-8")
+            .at(span)?;
+
+        Ok(string.into())
+    }
 }
 
 /// The delimiter to use when parsing CSV files.
@@ -135,19 +302,49 @@ impl Default for Delimiter {
 cast! {
     Delimiter,
     self => self.0.into_value(),
-    v: EcoString => {
-        let mut chars = v.chars();
-        let first = chars.next().ok_or("delimiter must not be empty")?;
-        if chars.next().is_some() {
-            bail!("delimiter must be a single character");
-        }
+    v: EcoString => Self(cast_ascii_char(&v, "delimiter")?),
+}
 
-        if !first.is_ascii() {
-            bail!("delimiter must be an ASCII character");
-        }
+/// The quote character to use when parsing CSV files.
+pub struct Quote(char);
 
-        Self(first)
-    },
+impl Default for Quote {
+    fn default() -> Self {
+        Self('"')
+    }
+}
+
+cast! {
+    Quote,
+    self => self.0.into_value(),
+    v: EcoString => Self(cast_ascii_char(&v, "quote")?),
+}
+
+/// The character that starts a comment line in CSV files.
+pub struct Comment(char);
+
+cast! {
+    Comment,
+    self => self.0.into_value(),
+    v: EcoString => Self(cast_ascii_char(&v, "comment")?),
+}
+
+/// Extract the single ASCII character of a CSV syntax option.
+fn cast_ascii_char(v: &str, name: &str) -> StrResult<char> {
+    let mut chars = v.chars();
+    let Some(first) = chars.next() else {
+        bail!("{name} must not be empty");
+    };
+
+    if chars.next().is_some() {
+        bail!("{name} must be a single character");
+    }
+
+    if !first.is_ascii() {
+        bail!("{name} must be an ASCII character");
+    }
+
+    Ok(first)
 }
 
 /// The type of parsed rows.
@@ -174,6 +371,31 @@ cast! {
     },
 }
 
+/// Convert a field into an integer, float, or boolean if it looks like one.
+fn infer_value(field: &str) -> Value {
+    match field {
+        "true" => return Value::Bool(true),
+        "false" => return Value::Bool(false),
+        _ => {}
+    }
+
+    if let Ok(int) = field.parse::<i64>() {
+        return Value::Int(int);
+    }
+
+    // Rust also parses things like `inf` and `NaN`, which should stay text.
+    let numeric = field
+        .bytes()
+        .all(|b| b.is_ascii_digit() || matches!(b, b'.' | b'e' | b'E' | b'+' | b'-'));
+    if numeric {
+        if let Ok(float) = field.parse::<f64>() {
+            return Value::Float(float);
+        }
+    }
+
+    field.into_value()
+}
+
 /// Format the user-facing CSV error message.
 fn format_csv_error(err: ::csv::Error, line: usize) -> EcoString {
     match err.kind() {
@@ -187,3 +409,31 @@ fn format_csv_error(err: ::csv::Error, line: usize) -> EcoString {
         _ => eco_format!("failed to parse CSV ({err})"),
     }
 }
+
+/// Format the user-facing CSV error message when encoding.
+fn format_csv_encode_error(err: ::csv::Error) -> EcoString {
+    match err.kind() {
+        ::csv::ErrorKind::UnequalLengths { expected_len, len, .. } => {
+            eco_format!(
+                "failed to encode value as CSV (found {len} instead of \
+                 {expected_len} fields in a row)"
+            )
+        }
+        _ => eco_format!("failed to encode value as CSV ({err})"),
+    }
+}
+
+/// Convert a value into the text of a CSV field.
+fn format_csv_field(value: Value) -> StrResult<EcoString> {
+    Ok(match value {
+        Value::None => EcoString::new(),
+        Value::Str(v) => v.into(),
+        Value::Int(v) => v.repr(),
+        Value::Float(v) if !v.is_finite() => {
+            bail!("cannot encode non-finite float {} as CSV", v.repr())
+        }
+        Value::Float(v) => v.repr(),
+        Value::Bool(v) => v.repr(),
+        v => bail!("expected string, number, boolean, or none, found {}", v.ty()),
+    })
+}
@@ -1,8 +1,8 @@
 use ecow::EcoString;
 
-use crate::diag::{format_xml_like_error, At, FileError, SourceResult};
+use crate::diag::{bail, format_xml_like_error, At, FileError, SourceResult, StrResult};
 use crate::engine::Engine;
-use crate::foundations::{dict, func, scope, Array, Dict, IntoValue, Str, Value};
+use crate::foundations::{dict, func, scope, Array, Dict, IntoValue, Repr, Str, Value};
 use crate::loading::Readable;
 use crate::syntax::Spanned;
 use crate::World;
@@ -84,6 +84,111 @@ impl xml {
             roxmltree::Document::parse(text).map_err(format_xml_error).at(span)?;
         Ok(convert_xml(document.root()))
     }
+
+    /// Encode structured data into an XML string.
+    ///
+    /// The value must have the same structure as the output of
+    /// [`xml.decode`]($xml.decode): A node or an array of nodes, where each
+    /// node is either a string or an element dictionary with a `tag` and,
+    /// optionally, `attrs` and `children`.
+    ///
+    /// ```example
+    /// #xml.encode((
+    ///   tag: "note",
+    ///   attrs: (lang: "en"),
+    ///   children: ("Hello & goodbye",),
+    /// ))
+    /// ```
+    #[func(title = "Encode XML")]
+    pub fn encode(
+        /// Value to be encoded.
+        value: Spanned<Value>,
+    ) -> SourceResult<Str> {
+        let Spanned { v: value, span } = value;
+        let mut buf = EcoString::new();
+        write_xml(&mut buf, value).at(span)?;
+        Ok(buf.into())
+    }
+}
+
+/// Write a Typst value as XML nodes into the buffer.
+fn write_xml(buf: &mut EcoString, value: Value) -> StrResult<()> {
+    match value {
+        Value::Str(text) => write_escaped(buf, &text, false),
+        Value::Array(nodes) => {
+            for node in nodes {
+                write_xml(buf, node)?;
+            }
+        }
+        Value::Dict(mut elem) => {
+            let tag = elem
+                .take("tag")
+                .map_err(|_| "XML element is missing a tag")?
+                .cast::<Str>()?;
+            let attrs = elem.take("attrs").ok().map(Value::cast::<Dict>).transpose()?;
+            let children =
+                elem.take("children").ok().map(Value::cast::<Array>).transpose()?;
+            elem.finish(&["tag", "attrs", "children"])?;
+
+            check_xml_name(&tag)?;
+            buf.push('<');
+            buf.push_str(&tag);
+            for (name, value) in attrs.unwrap_or_default() {
+                check_xml_name(&name)?;
+                let value = value.cast::<Str>()?;
+                buf.push(' ');
+                buf.push_str(&name);
+                buf.push_str("=\"");
+                write_escaped(buf, &value, true);
+                buf.push('"');
+            }
+
+            match children {
+                Some(children) if !children.is_empty() => {
+                    buf.push('>');
+                    write_xml(buf, Value::Array(children))?;
+                    buf.push_str("</");
+                    buf.push_str(&tag);
+                    buf.push('>');
+                }
+                _ => buf.push_str("/>"),
+            }
+        }
+        v => bail!("expected string, array, or dictionary, found {}", v.ty()),
+    }
+    Ok(())
+}
+
+/// Ensure that a string is usable as an XML tag or attribute name.
+fn check_xml_name(name: &str) -> StrResult<()> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == ':')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | ':' | '-' | '.'));
+    if !valid {
+        bail!("{} is not a valid XML name", name.repr());
+    }
+    Ok(())
+}
+
+/// Write text with XML special characters escaped.
+///
+/// Within attributes, whitespace other than spaces is escaped, too, so that it
+/// survives attribute value normalization.
+fn write_escaped(buf: &mut EcoString, text: &str, attr: bool) {
+    for c in text.chars() {
+        match c {
+            '&' => buf.push_str("&amp;"),
+            '<' => buf.push_str("&lt;"),
+            '>' => buf.push_str("&gt;"),
+            '"' if attr => buf.push_str("&quot;"),
+            '\n' if attr => buf.push_str("&#10;"),
+            '\t' if attr => buf.push_str("&#9;"),
+            '\r' => buf.push_str("&#13;"),
+            c => buf.push(c),
+        }
+    }
 }
 
 /// Convert an XML node to a Typst value.
@@ -6,6 +6,7 @@ use crate::layout::{Abs, Frame, FrameItem, Point, Size};
 use crate::math::{
     FrameFragment, LayoutMath, MathContext, MathFragment, MathSize, Scaled,
 };
+use crate::model::DocumentElem;
 use crate::text::TextElem;
 
 /// A base with optional attachments.
@@ -382,7 +383,7 @@ fn compute_shifts_up_and_down(
 
     let mut shift_up = Abs::zero();
     let mut shift_down = Abs::zero();
-    let is_char_box = is_character_box(base);
+    let is_char_box = is_character_box(base, DocumentElem::tagged_in(ctx.styles()));
 
     if tl.is_some() || tr.is_some() {
         let ascent = match &base {
@@ -431,22 +432,30 @@ fn is_integral_char(c: char) -> bool {
 }
 
 /// Whether the fragment consists of a single character or atomic piece of text.
-fn is_character_box(fragment: &MathFragment) -> bool {
+fn is_character_box(fragment: &MathFragment, tagged: bool) -> bool {
     match fragment {
         MathFragment::Glyph(_) | MathFragment::Variant(_) => {
             fragment.class() != Some(MathClass::Large)
         }
-        MathFragment::Frame(fragment) => is_atomic_text_frame(&fragment.frame),
+        MathFragment::Frame(fragment) => is_atomic_text_frame(&fragment.frame, tagged),
         _ => false,
     }
 }
 
 /// Handles e.g. "sin", "log", "exp", "CustomOperator".
-fn is_atomic_text_frame(frame: &Frame) -> bool {
+fn is_atomic_text_frame(frame: &Frame, tagged: bool) -> bool {
     // Meta information isn't visible or renderable, so we exclude it.
     let mut iter = frame
         .items()
         .map(|(_, item)| item)
         .filter(|item| !matches!(item, FrameItem::Meta(_, _)));
-    matches!(iter.next(), Some(FrameItem::Text(_))) && iter.next().is_none()
+    match (iter.next(), iter.next()) {
+        (Some(FrameItem::Text(_)), None) => true,
+        // In tagged documents, text that belongs to an element is kept in its
+        // own group.
+        (Some(FrameItem::Group(group)), None) if tagged => {
+            is_atomic_text_frame(&group.frame, tagged)
+        }
+        _ => false,
+    }
 }
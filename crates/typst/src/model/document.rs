@@ -1,12 +1,15 @@
 use ecow::EcoString;
+use smallvec::smallvec;
 
 use crate::diag::{bail, SourceResult, StrResult};
 use crate::engine::Engine;
 use crate::foundations::{
-    cast, elem, Args, Array, Construct, Content, Datetime, Smart, StyleChain, Value,
+    cast, elem, Args, Array, Construct, Content, Datetime, Dict, Smart, StyleChain,
+    Styles, Value,
 };
-use crate::introspection::{Introspector, ManualPageCounter};
+use crate::introspection::{Introspector, ManualPageCounter, Meta, MetaElem};
 use crate::layout::{Frame, LayoutRoot, PageElem};
+use crate::util::hash128;
 
 /// The root element of a document and its metadata.
 ///
@@ -38,10 +41,46 @@ pub struct DocumentElem {
     #[ghost]
     pub author: Author,
 
+    /// The document's subject, that is, a short description of what it is
+    /// about. PDF viewers show it in the document properties.
+    #[ghost]
+    pub subject: Option<EcoString>,
+
     /// The document's keywords.
     #[ghost]
     pub keywords: Keywords,
 
+    /// The application the document was created with.
+    ///
+    /// If this is `{auto}` (default), Typst names itself. Setting it to
+    /// `{none}` leaves the creator out of the PDF metadata. Tools that generate
+    /// Typst markup can set this to their own name.
+    #[ghost]
+    pub creator: Smart<Option<EcoString>>,
+
+    /// The application that produced the PDF file.
+    ///
+    /// If this is `{auto}` (default), Typst names itself. Setting it to
+    /// `{none}` leaves the producer out of the PDF metadata.
+    #[ghost]
+    pub producer: Smart<Option<EcoString>>,
+
+    /// Custom metadata as a dictionary of strings.
+    ///
+    /// The entries are embedded both into the PDF's document information and
+    /// its XMP metadata, where PDF viewers list them as custom properties. Keys
+    /// must start with an ASCII letter and may only contain ASCII letters,
+    /// digits, hyphens, underscores, and dots.
+    ///
+    /// ```example
+    /// #set document(custom: (
+    ///   department: "Research",
+    ///   revision: "4",
+    /// ))
+    /// ```
+    #[ghost]
+    pub custom: CustomMetadata,
+
     /// The document's creation date.
     ///
     /// If this is `{auto}` (default), Typst uses the current date and time.
@@ -53,6 +92,24 @@ pub struct DocumentElem {
     #[ghost]
     pub date: Smart<Option<Datetime>>,
 
+    /// Whether to record the document's logical structure, like its headings,
+    /// paragraphs, lists, tables, and figures, so that it can be exported as a
+    /// tagged PDF. Assistive technology like screen readers relies on this
+    /// structure to read a PDF in the right order.
+    ///
+    /// Tagging keeps the content of each element in a group of its own, so it
+    /// is disabled by default.
+    ///
+    /// ```example
+    /// #set document(tagged: true)
+    ///
+    /// = Introduction
+    /// This is read as a paragraph.
+    /// ```
+    #[ghost]
+    #[default(false)]
+    pub tagged: bool,
+
     /// The page runs.
     #[internal]
     #[variadic]
@@ -108,13 +165,33 @@ impl LayoutRoot for DocumentElem {
             pages,
             title: self.title(styles).map(|content| content.plain_text()),
             author: self.author(styles).0,
+            subject: self.subject(styles),
             keywords: self.keywords(styles).0,
+            creator: self.creator(styles),
+            producer: self.producer(styles),
+            custom: self.custom(styles).0,
             date: self.date(styles),
+            tagged: self.tagged(styles),
             introspector: Introspector::default(),
         })
     }
 }
 
+/// Styles that attach content to an item of a list, enumeration, or term list
+/// in a tagged document so that exporters can tell the items apart.
+///
+/// The items are laid out by their list instead of being realized, so they
+/// are located here. In untagged documents, the styles are empty.
+pub(crate) fn item_tag(engine: &mut Engine, item: Content, styles: StyleChain) -> Styles {
+    let mut tag = Styles::new();
+    if DocumentElem::tagged_in(styles) {
+        let mut item = item;
+        item.set_location(engine.locator.locate(hash128(&item)));
+        tag.set(MetaElem::set_data(smallvec![Meta::Elem(item)]));
+    }
+    tag
+}
+
 /// A list of authors.
 #[derive(Debug, Default, Clone, PartialEq, Hash)]
 pub struct Author(Vec<EcoString>);
@@ -137,6 +214,54 @@ cast! {
     v: Array => Self(v.into_iter().map(Value::cast).collect::<StrResult<_>>()?),
 }
 
+/// Custom metadata entries.
+#[derive(Debug, Default, Clone, PartialEq, Hash)]
+pub struct CustomMetadata(Vec<(EcoString, EcoString)>);
+
+cast! {
+    CustomMetadata,
+    self => Dict::from_iter(
+        self.0.into_iter().map(|(key, value)| (key.into(), value.into_value())),
+    ).into_value(),
+    v: Dict => Self(
+        v.into_iter()
+            .map(|(key, value)| {
+                check_custom_key(&key)?;
+                Ok((key.into(), value.cast()?))
+            })
+            .collect::<StrResult<_>>()?,
+    ),
+}
+
+/// Check that a custom metadata key is usable as both a PDF name and an XML
+/// element name and does not shadow built-in metadata.
+fn check_custom_key(key: &str) -> StrResult<()> {
+    let valid = key.starts_with(|c: char| c.is_ascii_alphabetic())
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        bail!("`{key}` is not a valid metadata key");
+    }
+
+    const BUILT_IN: &[&str] = &[
+        "Title",
+        "Author",
+        "Subject",
+        "Keywords",
+        "Creator",
+        "Producer",
+        "CreationDate",
+        "ModDate",
+        "Trapped",
+    ];
+    if BUILT_IN.contains(&key) {
+        bail!("`{key}` is reserved for built-in metadata");
+    }
+
+    Ok(())
+}
+
 /// A finished document with metadata and page frames.
 #[derive(Debug, Default, Clone)]
 pub struct Document {
@@ -146,10 +271,22 @@ pub struct Document {
     pub title: Option<EcoString>,
     /// The document's author.
     pub author: Vec<EcoString>,
+    /// The document's subject.
+    pub subject: Option<EcoString>,
     /// The document's keywords.
     pub keywords: Vec<EcoString>,
+    /// The application the document was created with. If `Auto`, this is
+    /// Typst.
+    pub creator: Smart<Option<EcoString>>,
+    /// The application that produced the exported file. If `Auto`, this is
+    /// Typst.
+    pub producer: Smart<Option<EcoString>>,
+    /// Custom metadata as key-value pairs.
+    pub custom: Vec<(EcoString, EcoString)>,
     /// The document's creation date.
     pub date: Smart<Option<Datetime>>,
+    /// Whether the document's logical structure was recorded during layout.
+    pub tagged: bool,
     /// Provides the ability to execute queries on the document.
     pub introspector: Introspector,
 }
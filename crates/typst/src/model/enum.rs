@@ -6,10 +6,10 @@ use crate::foundations::{
     cast, elem, scope, Array, Content, Fold, NativeElement, Smart, StyleChain,
 };
 use crate::layout::{
-    Align, Axes, BlockElem, Celled, Em, Fragment, GridLayouter, HAlign, Layout, Length,
-    Regions, Sizing, Spacing, VAlign,
+    Align, Axes, BlockElem, Cell, CellGrid, Em, Fragment, GridLayouter, HAlign, Layout,
+    Length, Regions, Sizing, Spacing, VAlign,
 };
-use crate::model::{Numbering, NumberingPattern, ParElem};
+use crate::model::{item_tag, Numbering, NumberingPattern, ParElem};
 use crate::text::TextElem;
 
 /// A numbered list.
@@ -259,16 +259,20 @@ impl Layout for EnumElem {
             let resolved =
                 resolved.aligned(number_align).styled(TextElem::set_overhang(false));
 
+            let tag = item_tag(engine, item.clone().pack(), styles);
             cells.push(Content::empty());
-            cells.push(resolved);
+            cells.push(resolved.styled_with_map(tag.clone()));
             cells.push(Content::empty());
-            cells.push(item.body().clone().styled(Self::set_parents(Parent(number))));
+            cells.push(
+                item.body()
+                    .clone()
+                    .styled(Self::set_parents(Parent(number)))
+                    .styled_with_map(tag),
+            );
             number = number.saturating_add(1);
         }
 
-        let fill = Celled::Value(None);
-        let stroke = None;
-        let layouter = GridLayouter::new(
+        let grid = CellGrid::new(
             Axes::with_x(&[
                 Sizing::Rel(indent.into()),
                 Sizing::Auto,
@@ -276,15 +280,11 @@ impl Layout for EnumElem {
                 Sizing::Auto,
             ]),
             Axes::with_y(&[gutter.into()]),
-            &cells,
-            &fill,
-            &stroke,
-            regions,
-            styles,
-            self.span(),
+            cells.into_iter().map(Cell::from),
         );
+        let layouter = GridLayouter::new(&grid, regions, styles, self.span());
 
-        Ok(layouter.layout(engine)?.fragment)
+        layouter.layout(engine)
     }
 }
 
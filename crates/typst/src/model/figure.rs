@@ -13,7 +13,9 @@ use crate::foundations::{
 use crate::introspection::{
     Count, Counter, CounterKey, CounterUpdate, Locatable, Location,
 };
-use crate::layout::{Align, BlockElem, Em, HAlign, Length, PlaceElem, VAlign, VElem};
+use crate::layout::{
+    Align, BlockElem, Em, HAlign, Length, PlaceElem, PlacementScope, VAlign, VElem,
+};
 use crate::model::{Numbering, NumberingPattern, Outlinable, Refable, Supplement};
 use crate::syntax::Spanned;
 use crate::text::{Lang, Region, TextElem};
@@ -131,6 +133,26 @@ pub struct FigureElem {
     /// ```
     pub placement: Option<Smart<VAlign>>,
 
+    /// Relative to which containing scope the figure is placed.
+    ///
+    /// Set this to `{"parent"}` to create a full-width figure in a
+    /// multi-column document. This only works for floating figures, that is,
+    /// if [`placement`]($figure.placement) is not `{none}`.
+    ///
+    /// ```example
+    /// #set page(height: 250pt, columns: 2)
+    ///
+    /// = Introduction
+    /// #figure(
+    ///   placement: bottom,
+    ///   scope: "parent",
+    ///   caption: [A glacier],
+    ///   image("glacier.jpg", width: 60%),
+    /// )
+    /// #lorem(60)
+    /// ```
+    pub scope: PlacementScope,
+
     /// The figure's caption.
     pub caption: Option<FigureCaption>,
 
@@ -325,7 +347,14 @@ impl Show for FigureElem {
             realized = PlaceElem::new(realized)
                 .with_float(true)
                 .with_alignment(align.map(|align| HAlign::Center + align))
+                .with_scope(self.scope(styles))
                 .pack();
+        } else if self.scope(styles) == PlacementScope::Parent {
+            bail!(
+                self.span(),
+                "parent-scoped placement is only available for floating figures";
+                hint: "you can enable floating placement with `figure(placement: auto, ..)`"
+            );
         }
 
         Ok(realized)
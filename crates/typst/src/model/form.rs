@@ -0,0 +1,325 @@
+//! Interactive form fields.
+
+use std::num::NonZeroUsize;
+
+use ecow::EcoString;
+use smallvec::smallvec;
+
+use crate::diag::{bail, SourceResult};
+use crate::engine::Engine;
+use crate::foundations::{
+    elem, Content, Module, NativeElement, Scope, Show, Smart, StyleChain,
+};
+use crate::introspection::{Meta, MetaElem};
+use crate::layout::{
+    Abs, BoxElem, Corners, Em, HElem, Length, Rel, Sides, Sizing, Spacing,
+};
+use crate::syntax::Span;
+use crate::text::TextElem;
+use crate::visualize::{Color, Paint, Stroke};
+
+/// A module with interactive form fields.
+///
+/// Form fields can be filled in by the reader of an exported PDF. Each field
+/// is identified by its name, under which its value is submitted or extracted
+/// from the filled-in file. Fields with the same name share their value.
+///
+/// Readers visit the fields in document order when tabbing through them. In
+/// formats other than PDF, only the outline of the fields is visible.
+pub fn module() -> Module {
+    let mut scope = Scope::new();
+    scope.define_elem::<TextFieldElem>();
+    scope.define_elem::<CheckboxElem>();
+    scope.define_elem::<RadioGroupElem>();
+    scope.define_elem::<DropdownElem>();
+    Module::new("form", scope)
+}
+
+/// A field that the reader can type text into.
+///
+/// # Example
+/// ```example
+/// Name: #form.text-field("name", width: 1fr)
+///
+/// Comments:
+/// #form.text-field(
+///   "comments",
+///   multiline: true,
+///   width: 100%,
+/// )
+/// ```
+#[elem(Show)]
+pub struct TextFieldElem {
+    /// The field's name.
+    #[required]
+    pub name: EcoString,
+
+    /// The text the field initially contains.
+    #[default]
+    pub value: EcoString,
+
+    /// Whether the field accepts multiple lines of text.
+    #[default(false)]
+    pub multiline: bool,
+
+    /// The maximum number of characters the field accepts.
+    pub max_length: Option<NonZeroUsize>,
+
+    /// The field's width.
+    #[default(Sizing::Rel(Em::new(10.0).into()))]
+    pub width: Sizing,
+
+    /// The field's height. Defaults to one line of text or, for multiline
+    /// fields, four lines.
+    pub height: Smart<Rel<Length>>,
+}
+
+impl Show for TextFieldElem {
+    #[tracing::instrument(name = "TextFieldElem::show", skip_all)]
+    fn show(&self, _: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        let multiline = self.multiline(styles);
+        let height = self
+            .height(styles)
+            .unwrap_or_else(|| Em::new(if multiline { 4.8 } else { 1.4 }).into());
+
+        let widget = Widget {
+            name: self.name().clone(),
+            kind: WidgetKind::Text {
+                value: self.value(styles),
+                multiline,
+                max_length: self.max_length(styles),
+            },
+            span: self.span(),
+        };
+
+        Ok(field(widget, self.width(styles), height, false))
+    }
+}
+
+/// A box that the reader can check.
+///
+/// # Example
+/// ```example
+/// #form.checkbox("newsletter", checked: true)
+/// Subscribe to the newsletter
+/// ```
+#[elem(Show)]
+pub struct CheckboxElem {
+    /// The field's name.
+    #[required]
+    pub name: EcoString,
+
+    /// Whether the box is initially checked.
+    #[default(false)]
+    pub checked: bool,
+
+    /// The size of the box.
+    #[default(Em::new(0.8).into())]
+    pub size: Length,
+}
+
+impl Show for CheckboxElem {
+    #[tracing::instrument(name = "CheckboxElem::show", skip_all)]
+    fn show(&self, _: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        let widget = Widget {
+            name: self.name().clone(),
+            kind: WidgetKind::Checkbox { checked: self.checked(styles) },
+            span: self.span(),
+        };
+
+        let size = self.size(styles);
+        Ok(field(widget, Sizing::Rel(size.into()), size.into(), false))
+    }
+}
+
+/// A group of options of which the reader can select one.
+///
+/// Each option is shown as a button followed by the option's text.
+///
+/// # Example
+/// ```example
+/// Shirt size:
+/// #form.radio-group(
+///   "size",
+///   ("S", "M", "L"),
+///   value: "M",
+/// )
+/// ```
+#[elem(Show)]
+pub struct RadioGroupElem {
+    /// The field's name.
+    #[required]
+    pub name: EcoString,
+
+    /// The options to choose from.
+    #[required]
+    pub options: Vec<EcoString>,
+
+    /// The initially selected option.
+    pub value: Option<EcoString>,
+
+    /// The size of the buttons.
+    #[default(Em::new(0.8).into())]
+    pub size: Length,
+}
+
+impl Show for RadioGroupElem {
+    #[tracing::instrument(name = "RadioGroupElem::show", skip_all)]
+    fn show(&self, _: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        let value = self.value(styles);
+        check_value(value.as_ref(), self.options(), self.span())?;
+
+        let size = self.size(styles);
+        let mut seq = vec![];
+        for (i, option) in self.options().iter().enumerate() {
+            if i > 0 {
+                seq.push(HElem::new(Spacing::Rel(Em::new(1.0).into())).pack());
+            }
+
+            let widget = Widget {
+                name: self.name().clone(),
+                kind: WidgetKind::Radio {
+                    option: option.clone(),
+                    selected: value.as_ref() == Some(option),
+                },
+                span: self.span(),
+            };
+
+            seq.push(field(widget, Sizing::Rel(size.into()), size.into(), true));
+            seq.push(HElem::new(Spacing::Rel(Em::new(0.3).into())).pack());
+            seq.push(TextElem::packed(option.clone()));
+        }
+
+        Ok(Content::sequence(seq))
+    }
+}
+
+/// A field that the reader can select one of several options in.
+///
+/// # Example
+/// ```example
+/// Country:
+/// #form.dropdown(
+///   "country",
+///   ("Germany", "France", "Italy"),
+///   value: "France",
+/// )
+/// ```
+#[elem(Show)]
+pub struct DropdownElem {
+    /// The field's name.
+    #[required]
+    pub name: EcoString,
+
+    /// The options to choose from.
+    #[required]
+    pub options: Vec<EcoString>,
+
+    /// The initially selected option. Defaults to the first one.
+    pub value: Option<EcoString>,
+
+    /// The field's width.
+    #[default(Sizing::Rel(Em::new(10.0).into()))]
+    pub width: Sizing,
+
+    /// The field's height. Defaults to one line of text.
+    pub height: Smart<Rel<Length>>,
+}
+
+impl Show for DropdownElem {
+    #[tracing::instrument(name = "DropdownElem::show", skip_all)]
+    fn show(&self, _: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        let value = self.value(styles);
+        check_value(value.as_ref(), self.options(), self.span())?;
+
+        let widget = Widget {
+            name: self.name().clone(),
+            kind: WidgetKind::Dropdown {
+                options: self.options().clone(),
+                value: value.or_else(|| self.options().first().cloned()),
+            },
+            span: self.span(),
+        };
+
+        let height = self.height(styles).unwrap_or_else(|| Em::new(1.4).into());
+        Ok(field(widget, self.width(styles), height, false))
+    }
+}
+
+/// A widget of an interactive form field, attached to the area the widget
+/// occupies.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct Widget {
+    /// The name of the field the widget belongs to.
+    pub name: EcoString,
+    /// The kind of field and its initial state.
+    pub kind: WidgetKind,
+    /// The span of the element that created the widget.
+    pub span: Span,
+}
+
+/// The kind of field a widget belongs to.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub enum WidgetKind {
+    /// A text field.
+    Text {
+        /// The initial text.
+        value: EcoString,
+        /// Whether the field accepts multiple lines.
+        multiline: bool,
+        /// The maximum number of characters.
+        max_length: Option<NonZeroUsize>,
+    },
+    /// A checkbox.
+    Checkbox {
+        /// Whether the box is initially checked.
+        checked: bool,
+    },
+    /// One of the buttons of a radio group.
+    Radio {
+        /// The option the button stands for.
+        option: EcoString,
+        /// Whether the button is initially selected.
+        selected: bool,
+    },
+    /// A dropdown.
+    Dropdown {
+        /// The options to choose from.
+        options: Vec<EcoString>,
+        /// The initially selected option.
+        value: Option<EcoString>,
+    },
+}
+
+/// Lay out a widget as an outlined box.
+fn field(widget: Widget, width: Sizing, height: Rel<Length>, round: bool) -> Content {
+    let stroke = Stroke {
+        paint: Smart::Custom(Paint::Solid(Color::GRAY)),
+        thickness: Smart::Custom(Abs::pt(0.5).into()),
+        ..Stroke::default()
+    };
+
+    let radius = if round { height / 2.0 } else { Rel::zero() };
+    BoxElem::new()
+        .with_width(width)
+        .with_height(Smart::Custom(height))
+        .with_baseline(Em::new(0.2).into())
+        .with_stroke(Sides::splat(Some(Some(stroke))))
+        .with_radius(Corners::splat(Some(radius)))
+        .pack()
+        .styled(MetaElem::set_data(smallvec![Meta::Widget(widget)]))
+}
+
+/// Ensure that the initial value is one of the options.
+fn check_value(
+    value: Option<&EcoString>,
+    options: &[EcoString],
+    span: Span,
+) -> SourceResult<()> {
+    if let Some(value) = value {
+        if !options.contains(value) {
+            bail!(span, "value must be one of the options");
+        }
+    }
+    Ok(())
+}
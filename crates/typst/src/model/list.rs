@@ -5,10 +5,10 @@ use crate::foundations::{
     Value,
 };
 use crate::layout::{
-    Axes, BlockElem, Celled, Em, Fragment, GridLayouter, HAlign, Layout, Length, Regions,
-    Sizing, Spacing, VAlign,
+    Axes, BlockElem, Cell, CellGrid, Em, Fragment, GridLayouter, HAlign, Layout, Length,
+    Regions, Sizing, Spacing, VAlign,
 };
-use crate::model::ParElem;
+use crate::model::{item_tag, ParElem};
 use crate::text::TextElem;
 
 /// A bullet list.
@@ -160,15 +160,19 @@ impl Layout for ListElem {
 
         let mut cells = vec![];
         for item in self.children() {
+            let tag = item_tag(engine, item.clone().pack(), styles);
             cells.push(Content::empty());
-            cells.push(marker.clone());
+            cells.push(marker.clone().styled_with_map(tag.clone()));
             cells.push(Content::empty());
-            cells.push(item.body().clone().styled(Self::set_depth(Depth)));
+            cells.push(
+                item.body()
+                    .clone()
+                    .styled(Self::set_depth(Depth))
+                    .styled_with_map(tag),
+            );
         }
 
-        let fill = Celled::Value(None);
-        let stroke = None;
-        let layouter = GridLayouter::new(
+        let grid = CellGrid::new(
             Axes::with_x(&[
                 Sizing::Rel(indent.into()),
                 Sizing::Auto,
@@ -176,15 +180,11 @@ impl Layout for ListElem {
                 Sizing::Auto,
             ]),
             Axes::with_y(&[gutter.into()]),
-            &cells,
-            &fill,
-            &stroke,
-            regions,
-            styles,
-            self.span(),
+            cells.into_iter().map(Cell::from),
         );
+        let layouter = GridLayouter::new(&grid, regions, styles, self.span());
 
-        Ok(layouter.layout(engine)?.fragment)
+        layouter.layout(engine)
     }
 }
 
@@ -0,0 +1,127 @@
+use std::num::NonZeroUsize;
+
+use crate::diag::SourceResult;
+use crate::engine::Engine;
+use crate::foundations::{
+    elem, Content, NativeElement, Show, Smart, StyleChain, Synthesize,
+};
+use crate::introspection::{Count, Counter, CounterUpdate, Locatable, MetaElem};
+use crate::layout::{Em, HElem, Length};
+use crate::model::Numbering;
+use crate::text::SuperElem;
+use crate::util::NonZeroExt;
+
+/// A note in the page margin.
+///
+/// The note is placed in the outer margin of the page, next to the line that
+/// contains it. For two-sided documents, the outer margin alternates between
+/// the left and right side depending on the page's
+/// [binding]($page.binding). When notes would overlap, later notes are moved
+/// down.
+///
+/// Make sure to set a margin that is wide enough to hold the notes.
+///
+/// # Example
+/// ```example
+/// #set page(margin: (right: 100pt))
+/// Edward Tufte popularized notes
+/// in the margin.#marginnote[
+///   Instead of at the bottom of the
+///   page.
+/// ] They are close to the text they
+/// refer to.
+/// ```
+///
+/// Margin notes can also be numbered, in which case the number appears both
+/// in the text and in front of the note.
+///
+/// ```example
+/// #set page(margin: (right: 100pt))
+/// #set marginnote(numbering: "1")
+/// Sidenotes are numbered
+/// like footnotes.#marginnote[
+///   But in the margin.
+/// ]
+/// ```
+///
+/// _Note:_ Like for footnotes, set and show rules in the scope where
+/// `marginnote` is called may not apply to the note's content.
+#[elem(name = "marginnote", title = "Margin Note", Locatable, Synthesize, Show, Count)]
+pub struct MarginNoteElem {
+    /// How to number the note, if at all.
+    ///
+    /// Numbered notes share one counter, which continues throughout the
+    /// document.
+    pub numbering: Option<Numbering>,
+
+    /// The horizontal gap between the note and the page's content.
+    #[resolve]
+    #[default(Em::new(1.0).into())]
+    pub gap: Length,
+
+    /// The width of the note.
+    ///
+    /// If set to `{auto}`, the note takes up the outer margin except for the
+    /// gap on both sides.
+    #[resolve]
+    pub width: Smart<Length>,
+
+    /// The minimum vertical space between two notes.
+    #[resolve]
+    #[default(Em::new(0.65).into())]
+    pub spacing: Length,
+
+    /// The content to put into the margin.
+    #[required]
+    pub body: Content,
+}
+
+impl Synthesize for MarginNoteElem {
+    fn synthesize(&mut self, _: &mut Engine, styles: StyleChain) -> SourceResult<()> {
+        // The note is laid out with the page, so the properties are resolved
+        // where the note is called.
+        self.push_numbering(self.numbering(styles));
+        self.push_gap(self.gap(styles).into());
+        self.push_width(self.width(styles).map(Into::into));
+        self.push_spacing(self.spacing(styles).into());
+        Ok(())
+    }
+}
+
+impl Show for MarginNoteElem {
+    #[tracing::instrument(name = "MarginNoteElem::show", skip_all)]
+    fn show(&self, engine: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        // The page finds the note through this marker, which sits on the
+        // baseline of the line that contains it.
+        let marker = MetaElem::new().pack();
+        let Some(numbering) = self.numbering(styles) else {
+            return Ok(marker);
+        };
+
+        Ok(engine.delayed(|engine| {
+            let num = self.number(engine, &numbering)?;
+            // Add zero-width weak spacing to make the number "sticky".
+            Ok(HElem::hole().pack() + SuperElem::new(num).pack() + marker)
+        }))
+    }
+}
+
+impl MarginNoteElem {
+    /// Display the note's number.
+    pub fn number(
+        &self,
+        engine: &mut Engine,
+        numbering: &Numbering,
+    ) -> SourceResult<Content> {
+        let loc = self.location().unwrap();
+        Counter::of(Self::elem()).at(engine, loc)?.display(engine, numbering)
+    }
+}
+
+impl Count for MarginNoteElem {
+    fn update(&self) -> Option<CounterUpdate> {
+        self.numbering(StyleChain::default())
+            .is_some()
+            .then(|| CounterUpdate::Step(NonZeroUsize::ONE))
+    }
+}
@@ -8,13 +8,16 @@ mod emph;
 mod enum_;
 mod figure;
 mod footnote;
+pub mod form;
 mod heading;
 mod link;
 mod list;
+mod marginnote;
 #[path = "numbering.rs"]
 mod numbering_;
 mod outline;
 mod par;
+pub mod pdf;
 mod quote;
 mod reference;
 mod strong;
@@ -31,6 +34,7 @@ pub use self::footnote::*;
 pub use self::heading::*;
 pub use self::link::*;
 pub use self::list::*;
+pub use self::marginnote::*;
 pub use self::numbering_::*;
 pub use self::outline::*;
 pub use self::par::*;
@@ -60,6 +64,7 @@ pub fn define(global: &mut Scope) {
     global.define_elem::<HeadingElem>();
     global.define_elem::<FigureElem>();
     global.define_elem::<FootnoteElem>();
+    global.define_elem::<MarginNoteElem>();
     global.define_elem::<QuoteElem>();
     global.define_elem::<CiteElem>();
     global.define_elem::<BibliographyElem>();
@@ -72,4 +77,6 @@ pub fn define(global: &mut Scope) {
     global.define_elem::<EmphElem>();
     global.define_elem::<StrongElem>();
     global.define_func::<numbering>();
+    global.define_module(form::module());
+    global.define_module(pdf::module());
 }
@@ -1,12 +1,15 @@
-use comemo::Prehashed;
+use comemo::{Prehashed, Tracked};
 
-use crate::diag::SourceResult;
+use crate::diag::{bail, SourceResult};
 use crate::engine::Engine;
 use crate::foundations::{
-    elem, Args, Cast, Construct, Content, NativeElement, Set, Smart, StyleChain,
-    Unlabellable,
+    elem, scope, Args, Cast, Construct, Content, Label, NativeElement, Set, Smart,
+    StyleChain, Styles, Unlabellable,
 };
-use crate::layout::{Em, Fragment, Length, Size};
+use crate::introspection::{Introspector, Locatable};
+use crate::layout::{Em, FixedAlign, Fragment, HAlign, Length, OuterHAlign, Size};
+use crate::model::Numbering;
+use crate::text::{Lang, LocalName, Region, TextElem};
 
 /// Arranges text, spacing and inline-level elements into a paragraph.
 ///
@@ -33,7 +36,7 @@ use crate::layout::{Em, Fragment, Length, Size};
 /// let $a$ be the smallest of the
 /// three integers. Then, we ...
 /// ```
-#[elem(title = "Paragraph", Construct)]
+#[elem(scope, title = "Paragraph", Construct)]
 pub struct ParElem {
     /// The spacing between lines.
     #[resolve]
@@ -107,6 +110,12 @@ pub struct ParElem {
     pub children: Vec<Prehashed<Content>>,
 }
 
+#[scope]
+impl ParElem {
+    #[elem]
+    type ParLine;
+}
+
 impl Construct for ParElem {
     fn construct(engine: &mut Engine, args: &mut Args) -> SourceResult<Content> {
         // The paragraph constructor is special: It doesn't create a paragraph
@@ -156,6 +165,258 @@ pub enum Linebreaks {
     Optimized,
 }
 
+/// Numbers the lines of paragraphs.
+///
+/// Line numbers are displayed in the page margin, next to each line of text.
+/// They are commonly used in contracts, manuscripts submitted for review, and
+/// code listings. This function is only used with
+/// [set rules]($styling/#set-rules).
+///
+/// # Example
+/// ```example
+/// #set page(margin: (left: 3em))
+/// #set par.line(numbering: "1")
+///
+/// Roses are red. \
+/// Violets are blue. \
+/// Typst is there for you.
+/// ```
+///
+/// Lines in headers and footers are never numbered. To exclude other content,
+/// disable the numbering with a show-set rule:
+///
+/// ```example
+/// #set page(margin: (left: 3em))
+/// #set par.line(numbering: "1")
+/// #show table: set par.line(numbering: none)
+///
+/// Numbered.
+/// #table(columns: 2)[Not][numbered]
+/// ```
+///
+/// # Referencing lines
+/// A label on content within a numbered line can be
+/// [referenced]($ref) to refer to that line. If the content spans several
+/// lines, the reference points to the first one.
+///
+/// ```example
+/// #set page(margin: (left: 3em))
+/// #set par.line(numbering: "1")
+///
+/// The parties agree to the
+/// following terms. _All terms
+/// are final._ <line-final>
+///
+/// As stated in @line-final, no
+/// changes can be made.
+/// ```
+#[elem(name = "line", title = "Paragraph Line", Construct)]
+pub struct ParLine {
+    /// How to number each line. Accepts a
+    /// [numbering pattern or function]($numbering).
+    ///
+    /// ```example
+    /// #set page(margin: (left: 3em))
+    /// #set par.line(numbering: "I")
+    ///
+    /// Roses are red. \
+    /// Violets are blue. \
+    /// Typst is there for you.
+    /// ```
+    #[ghost]
+    pub numbering: Option<Numbering>,
+
+    /// How to align line numbers among each other.
+    ///
+    /// If set to `{auto}`, numbers are aligned towards the text, that is,
+    /// to the right in the left margin and vice versa.
+    ///
+    /// ```example
+    /// #set page(margin: (left: 3em))
+    /// #set par.line(numbering: "1", number-align: left)
+    ///
+    /// #for _ in range(12) [Line. \]
+    /// ```
+    #[ghost]
+    pub number_align: Smart<HAlign>,
+
+    /// The margin in which line numbers are displayed.
+    ///
+    /// ```example
+    /// #set page(margin: (right: 3em))
+    /// #set par.line(numbering: "1", number-margin: right)
+    ///
+    /// Roses are red. \
+    /// Violets are blue.
+    /// ```
+    #[ghost]
+    pub number_margin: OuterHAlign,
+
+    /// The distance between line numbers and the text.
+    #[ghost]
+    #[resolve]
+    #[default(Em::new(1.0).into())]
+    pub number_clearance: Length,
+
+    /// Whether line numbers continue throughout the `{"document"}` or
+    /// restart on each `{"page"}`.
+    ///
+    /// ```example
+    /// #set page(height: 60pt, margin: (left: 3em))
+    /// #set par.line(
+    ///   numbering: "1",
+    ///   numbering-scope: "page",
+    /// )
+    ///
+    /// First page. \
+    /// Still first page. \
+    /// Second page.
+    /// ```
+    #[ghost]
+    #[default(LineNumberingScope::Document)]
+    pub numbering_scope: LineNumberingScope,
+}
+
+impl Construct for ParLine {
+    fn construct(_: &mut Engine, args: &mut Args) -> SourceResult<Content> {
+        bail!(args.span, "can only be used in set rules")
+    }
+}
+
+/// Whether line numbers continue throughout the document or restart on each
+/// page.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum LineNumberingScope {
+    /// Line numbers continue throughout the document.
+    Document,
+    /// Line numbers restart on each page.
+    Page,
+}
+
+/// A marker for a numbered line.
+///
+/// Paragraph layout adds one of these to each line when line numbering is
+/// enabled. The page then displays the numbers next to the markers, and
+/// references to labels within a line resolve to the line through it.
+#[elem(name = "line-marker", Locatable)]
+pub struct ParLineMarker {
+    /// How to number the line.
+    #[required]
+    pub numbering: Numbering,
+
+    /// How to align the number, with start and end resolved.
+    #[required]
+    pub number_align: Smart<HAlign>,
+
+    /// The margin in which the number is displayed, either `left` or
+    /// `right`.
+    #[required]
+    pub number_margin: OuterHAlign,
+
+    /// The distance between the number and the text, resolved against the
+    /// paragraph's styles.
+    #[required]
+    pub number_clearance: Length,
+
+    /// Whether the number continues throughout the document or restarts on
+    /// each page.
+    #[required]
+    pub numbering_scope: LineNumberingScope,
+
+    /// The styles of the paragraph, with which the number is displayed.
+    #[required]
+    pub styles: Styles,
+
+    /// The labels attached to content within the line.
+    pub labels: Vec<Label>,
+}
+
+impl ParLineMarker {
+    /// Create a marker from the line numbering properties in the style chain,
+    /// if lines should be numbered.
+    pub fn from_styles(styles: StyleChain) -> Option<Self> {
+        let numbering = ParLine::numbering_in(styles)?;
+        let dir = TextElem::dir_in(styles);
+        let align = ParLine::number_align_in(styles).map(|align| match align.fix(dir) {
+            FixedAlign::Start => HAlign::Left,
+            FixedAlign::Center => HAlign::Center,
+            FixedAlign::End => HAlign::Right,
+        });
+        Some(Self::new(
+            numbering,
+            align,
+            ParLine::number_margin_in(styles).fix(dir),
+            ParLine::number_clearance_in(styles).into(),
+            ParLine::numbering_scope_in(styles),
+            styles.to_map(),
+        ))
+    }
+
+    /// Find the first numbered line that contains content with the given
+    /// label.
+    pub fn find(
+        introspector: Tracked<Introspector>,
+        label: Label,
+    ) -> Option<Prehashed<Content>> {
+        introspector.query(&Self::elem().select()).into_iter().find(|marker| {
+            marker.to::<Self>().is_some_and(|marker| {
+                marker.labels(StyleChain::default()).contains(&label)
+            })
+        })
+    }
+
+    /// Determine the number of this line.
+    pub fn number(&self, introspector: Tracked<Introspector>) -> usize {
+        let loc = self.location().unwrap();
+        let markers = introspector.query(&Self::elem().select());
+        let before = markers.iter().take_while(|marker| marker.location() != Some(loc));
+        match self.numbering_scope() {
+            LineNumberingScope::Document => before.count() + 1,
+            LineNumberingScope::Page => {
+                let page = introspector.page(loc);
+                before
+                    .filter(|marker| {
+                        introspector.page(marker.location().unwrap()) == page
+                    })
+                    .count()
+                    + 1
+            }
+        }
+    }
+}
+
+impl LocalName for ParLineMarker {
+    fn local_name(lang: Lang, _: Option<Region>) -> &'static str {
+        match lang {
+            Lang::ALBANIAN => "Rreshti",
+            Lang::BOKMÅL => "Linje",
+            Lang::CHINESE => "行",
+            Lang::CZECH => "Řádek",
+            Lang::DANISH => "Linje",
+            Lang::DUTCH => "Regel",
+            Lang::ESTONIAN => "Rida",
+            Lang::FINNISH => "Rivi",
+            Lang::FRENCH => "Ligne",
+            Lang::GERMAN => "Zeile",
+            Lang::GREEK => "Γραμμή",
+            Lang::HUNGARIAN => "Sor",
+            Lang::ITALIAN => "Riga",
+            Lang::NYNORSK => "Linje",
+            Lang::POLISH => "Wiersz",
+            Lang::PORTUGUESE => "Linha",
+            Lang::ROMANIAN => "Linia",
+            Lang::RUSSIAN => "Строка",
+            Lang::SLOVENIAN => "Vrstica",
+            Lang::SPANISH => "Línea",
+            Lang::SWEDISH => "Rad",
+            Lang::TURKISH => "Satır",
+            Lang::UKRAINIAN => "Рядок",
+            Lang::JAPANESE => "行",
+            Lang::ENGLISH | _ => "Line",
+        }
+    }
+}
+
 /// A paragraph break.
 ///
 /// This starts a new paragraph. Especially useful when used within code like
@@ -0,0 +1,222 @@
+//! PDF-specific functionality.
+
+use std::num::NonZeroUsize;
+
+use ecow::EcoString;
+
+use crate::diag::{At, SourceResult};
+use crate::engine::Engine;
+use crate::foundations::{
+    elem, Behave, Behaviour, Bytes, Cast, Content, Module, Scope, Show, Smart,
+    StyleChain, Synthesize,
+};
+use crate::introspection::Locatable;
+use crate::syntax::Spanned;
+use crate::World;
+
+/// A module with functionality that is specific to PDF export.
+///
+/// The elements in this module have no effect on other export formats.
+pub fn module() -> Module {
+    let mut scope = Scope::new();
+    scope.define_elem::<EmbedElem>();
+    scope.define_elem::<ViewerElem>();
+    Module::new("pdf", scope)
+}
+
+/// A file that is attached to the exported PDF.
+///
+/// PDF readers list embedded files in a sidebar, from where they can be opened
+/// or saved. This is useful to ship the data a chart was built from or the
+/// Typst sources along with a document. Some formats even require an embedded
+/// file: A ZUGFeRD or Factur-X invoice, for instance, carries its
+/// machine-readable counterpart as an embedded XML file.
+///
+/// The file is embedded under the last component of its path. Where the
+/// element is placed in the document does not matter.
+///
+/// # Example
+/// ```typ
+/// #pdf.embed(
+///   "data.csv",
+///   relationship: "data",
+///   mime-type: "text/csv",
+///   description: "The measurements shown in the chart",
+/// )
+/// ```
+///
+/// # Archival standards
+/// PDF/A-2b does not permit arbitrary embedded files, so exporting a document
+/// with embedded files in conformance with it fails. PDF/A-3b permits them, but
+/// requires each file to have a [MIME type]($pdf.embed.mime-type).
+#[elem(Behave, Show, Locatable, Synthesize)]
+pub struct EmbedElem {
+    /// Path to the file to embed.
+    #[required]
+    #[parse(
+        let Spanned { v: path, span } =
+            args.expect::<Spanned<EcoString>>("path to the file to embed")?;
+        let id = span.resolve_path(&path).at(span)?;
+        let data = engine.world.file(id).at(span)?;
+        path
+    )]
+    #[borrowed]
+    pub path: EcoString,
+
+    /// The raw file data.
+    #[internal]
+    #[required]
+    #[parse(data)]
+    pub data: Bytes,
+
+    /// How the file relates to the document. If `{none}`, the relationship is
+    /// left unspecified.
+    pub relationship: Option<EmbeddedFileRelationship>,
+
+    /// The MIME type of the file, for example `{"text/xml"}`.
+    pub mime_type: Option<EcoString>,
+
+    /// A description of the file that readers show alongside its name.
+    pub description: Option<EcoString>,
+}
+
+impl EmbedElem {
+    /// The name under which the file is embedded, that is, the last component
+    /// of its path.
+    pub fn file_name(&self) -> &str {
+        self.path().rsplit('/').next().unwrap_or_default()
+    }
+}
+
+impl Synthesize for EmbedElem {
+    fn synthesize(&mut self, _: &mut Engine, styles: StyleChain) -> SourceResult<()> {
+        // The exporter reads these from the element itself, so set rules must
+        // be applied here.
+        self.push_relationship(self.relationship(styles));
+        self.push_mime_type(self.mime_type(styles));
+        self.push_description(self.description(styles));
+        Ok(())
+    }
+}
+
+impl Show for EmbedElem {
+    fn show(&self, _: &mut Engine, _styles: StyleChain) -> SourceResult<Content> {
+        Ok(Content::empty())
+    }
+}
+
+impl Behave for EmbedElem {
+    fn behaviour(&self) -> Behaviour {
+        Behaviour::Invisible
+    }
+}
+
+/// How an embedded file relates to the document.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum EmbeddedFileRelationship {
+    /// The file is the original source of the document's content, like the
+    /// Typst file it was compiled from.
+    Source,
+    /// The file contains data that the document visualizes, like the numbers
+    /// behind a chart or the XML of an e-invoice.
+    Data,
+    /// The file is an alternative representation of the document.
+    Alternative,
+    /// The file supplements the document with additional material.
+    Supplement,
+}
+
+/// Settings for how PDF viewers present the document when opening it.
+///
+/// Without these settings, viewers show the document the way they were
+/// configured to. Where the element is placed in the document does not matter,
+/// but it may only appear once.
+///
+/// # Example
+/// ```typ
+/// // Open as a book with facing pages
+/// // and the outline panel shown.
+/// #pdf.viewer(
+///   page-layout: "two-page-right",
+///   page-mode: "outline",
+///   open-to: 3,
+/// )
+/// ```
+#[elem(Behave, Show, Locatable, Synthesize)]
+pub struct ViewerElem {
+    /// How pages are laid out. If `{none}`, the viewer decides.
+    pub page_layout: Option<PageLayout>,
+
+    /// Which panel the viewer shows next to the pages. If `{none}`, the viewer
+    /// decides.
+    pub page_mode: Option<PageMode>,
+
+    /// The number of the page to open the document at. If `{none}`, the
+    /// document opens at its first page.
+    ///
+    /// This is the physical page number, starting at one, regardless of how
+    /// pages are [numbered]($page.numbering). If the page is not exported
+    /// because only some pages are, the document opens at its first page.
+    pub open_to: Option<NonZeroUsize>,
+
+    /// Whether the viewer's window shows the document's
+    /// [title]($document.title) instead of its file name.
+    ///
+    /// If this is `{auto}` (default), the title is shown if the document has
+    /// one.
+    pub display_title: Smart<bool>,
+}
+
+impl Synthesize for ViewerElem {
+    fn synthesize(&mut self, _: &mut Engine, styles: StyleChain) -> SourceResult<()> {
+        // The exporter reads these from the element itself, so set rules must
+        // be applied here.
+        self.push_page_layout(self.page_layout(styles));
+        self.push_page_mode(self.page_mode(styles));
+        self.push_open_to(self.open_to(styles));
+        self.push_display_title(self.display_title(styles));
+        Ok(())
+    }
+}
+
+impl Show for ViewerElem {
+    fn show(&self, _: &mut Engine, _styles: StyleChain) -> SourceResult<Content> {
+        Ok(Content::empty())
+    }
+}
+
+impl Behave for ViewerElem {
+    fn behaviour(&self) -> Behaviour {
+        Behaviour::Invisible
+    }
+}
+
+/// How a PDF viewer lays out the document's pages.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum PageLayout {
+    /// One page at a time.
+    SinglePage,
+    /// A single, continuously scrolling column of pages.
+    OneColumn,
+    /// Two continuously scrolling columns with odd pages on the left.
+    TwoColumnLeft,
+    /// Two continuously scrolling columns with odd pages on the right.
+    TwoColumnRight,
+    /// Two pages at a time with odd pages on the left.
+    TwoPageLeft,
+    /// Two pages at a time with odd pages on the right, like an open book.
+    TwoPageRight,
+}
+
+/// Which panel a PDF viewer shows next to the document's pages.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Cast)]
+pub enum PageMode {
+    /// The document's outline, that is, its bookmarks.
+    Outline,
+    /// Thumbnails of the pages.
+    Thumbnails,
+    /// The [embedded files]($pdf.embed).
+    Attachments,
+    /// No panel, with the document shown in full screen mode.
+    FullScreen,
+}
@@ -10,8 +10,9 @@ use crate::introspection::{Counter, Locatable};
 use crate::math::EquationElem;
 use crate::model::{
     BibliographyElem, CiteElem, Destination, Figurable, FootnoteElem, Numbering,
+    ParLineMarker,
 };
-use crate::text::TextElem;
+use crate::text::{LocalName, TextElem};
 
 /// A reference to a label or bibliography.
 ///
@@ -181,6 +182,15 @@ impl Show for RefElem {
             }
 
             let elem = elem.clone();
+
+            // Labels on content within a numbered line refer to the line.
+            if !elem.can::<dyn Refable>() {
+                if let Some(line) = ParLineMarker::find(engine.introspector, target) {
+                    let line = line.to::<ParLineMarker>().unwrap();
+                    return self.to_line_ref(engine, styles, elem.into_inner(), line);
+                }
+            }
+
             let refable = elem
                 .with::<dyn Refable>()
                 .ok_or_else(|| {
@@ -253,6 +263,31 @@ impl RefElem {
 
         Ok(elem)
     }
+
+    /// Turn the reference into a reference to a numbered line.
+    fn to_line_ref(
+        &self,
+        engine: &mut Engine,
+        styles: StyleChain,
+        elem: Content,
+        line: &ParLineMarker,
+    ) -> SourceResult<Content> {
+        let number = line.number(engine.introspector);
+        let mut content =
+            line.numbering().clone().trimmed().apply(engine, &[number])?.display();
+
+        let supplement = match self.supplement(styles).as_ref() {
+            Smart::Auto => TextElem::packed(ParLineMarker::local_name_in(styles)),
+            Smart::Custom(None) => Content::empty(),
+            Smart::Custom(Some(supplement)) => supplement.resolve(engine, [elem])?,
+        };
+
+        if !supplement.is_empty() {
+            content = supplement + TextElem::packed("\u{a0}") + content;
+        }
+
+        Ok(content.linked(Destination::Location(line.location().unwrap())))
+    }
 }
 
 /// Additional content for a reference.
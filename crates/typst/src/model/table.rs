@@ -1,12 +1,23 @@
-use crate::diag::SourceResult;
+use std::num::NonZeroUsize;
+
+use ecow::{eco_format, EcoString};
+
+use crate::diag::{bail, SourceResult, StrResult, Trace, Tracepoint};
 use crate::engine::Engine;
-use crate::foundations::{elem, Content, NativeElement, Smart, StyleChain};
+use crate::foundations::{
+    cast, elem, scope, AlternativeFold, Content, Fold, FromValue, IntoValue,
+    NativeElement, Show, Smart, StyleChain,
+};
 use crate::layout::{
-    apply_align_inset_to_cells, Abs, Align, Axes, Celled, Fragment, GridLayouter, Layout,
-    Length, Regions, Rel, Sides, TrackSizings,
+    show_grid_cell, Abs, Align, Axes, Cell, CellGrid, Celled, Dir, Fragment, GridCell,
+    GridFooter, GridHLine, GridHeader, GridLayouter, GridVLine, HAlign, Layout, Length,
+    LinePosition, Regions, Rel, ResolvableCell, ResolvableGridChild, ResolvableGridItem,
+    ResolvedCelled, Sides, TrackSizings, VAlign,
 };
 use crate::model::Figurable;
-use crate::text::{Lang, LocalName, Region};
+use crate::syntax::Spanned;
+use crate::text::{Lang, LocalName, Region, TextElem};
+use crate::util::NonZeroExt;
 use crate::visualize::{Paint, Stroke};
 
 /// A table of items.
@@ -26,7 +37,9 @@ use crate::visualize::{Paint, Stroke};
 ///   columns: (1fr, auto, auto),
 ///   inset: 10pt,
 ///   align: horizon,
-///   [], [*Area*], [*Parameters*],
+///   table.header(
+///     [], [*Area*], [*Parameters*],
+///   ),
 ///   image("cylinder.svg"),
 ///   $ pi h (D^2 - d^2) / 4 $,
 ///   [
@@ -39,7 +52,35 @@ use crate::visualize::{Paint, Stroke};
 ///   [$a$: edge length]
 /// )
 /// ```
-#[elem(Layout, LocalName, Figurable)]
+///
+/// Individual cells can be customized with [`table.cell`]($table.cell), for
+/// example to make them span multiple columns or rows, or to override the
+/// table's fill, alignment, inset or stroke for that cell alone.
+///
+/// Long tables which break across pages can repeat their heading rows and
+/// closing rows on every page by wrapping them in
+/// [`table.header`]($table.header) and [`table.footer`]($table.footer).
+///
+/// Lines between cells can be configured per cell through a `stroke` function,
+/// or placed explicitly with [`table.hline`]($table.hline) and
+/// [`table.vline`]($table.vline). For example, a booktabs-style table with
+/// thick top and bottom rules, a thin rule below the header and no vertical
+/// lines looks like this:
+///
+/// ```example
+/// #table(
+///   columns: 3,
+///   stroke: none,
+///   table.hline(stroke: 1pt),
+///   table.header[*Metal*][*Density*][*Melting point*],
+///   table.hline(stroke: 0.5pt),
+///   [Aluminium], [2.70], [660 °C],
+///   [Copper], [8.96], [1085 °C],
+///   [Iron], [7.87], [1538 °C],
+///   table.hline(stroke: 1pt),
+/// )
+/// ```
+#[elem(scope, Layout, LocalName, Figurable)]
 pub struct TableElem {
     /// The column sizes. See the [grid documentation]($grid) for more
     /// information on track sizing.
@@ -114,15 +155,37 @@ pub struct TableElem {
 
     /// How to [stroke]($stroke) the cells.
     ///
-    /// Strokes can be disabled by setting this to `{none}`.
+    /// Strokes can be disabled by setting this to `{none}`. The stroke of
+    /// individual cells can be overridden with [`table.cell`]($table.cell).
+    ///
+    /// Like the fill, the stroke can also be a function that receives the
+    /// cells' column and row indices, starting from zero, and returns a stroke
+    /// or a dictionary of strokes for each side of the cell.
     ///
-    /// _Note:_ Richer stroke customization for individual cells is not yet
-    /// implemented, but will be in the future. In the meantime, you can use the
-    /// third-party [tablex library](https://github.com/PgBiel/typst-tablex/).
+    /// If it is necessary to place lines which can cross spacing between cells
+    /// produced by the `gutter` option, or to override the stroke between
+    /// multiple specific cells, consider specifying one or more of
+    /// [`table.hline`]($table.hline) and [`table.vline`]($table.vline)
+    /// alongside your table cells.
+    ///
+    /// ```example
+    /// #table(
+    ///   columns: 3,
+    ///   stroke: (x, y) => (
+    ///     left: if x > 0 { 0.5pt + gray },
+    ///     bottom: if y == 0 { 1pt },
+    ///   ),
+    ///   [*Name*], [*Role*], [*Since*],
+    ///   [Alex], [Engineer], [2019],
+    ///   [Sam], [Designer], [2021],
+    /// )
+    /// ```
     #[resolve]
     #[fold]
-    #[default(Some(Stroke::default()))]
-    pub stroke: Option<Stroke>,
+    #[default(ResolvedCelled(Celled::Value(Sides::splat(Some(Some(
+        Stroke::default()
+    ))))))]
+    pub stroke: Celled<Sides<Option<Option<Stroke>>>>,
 
     /// How much to pad the cells' content.
     ///
@@ -147,9 +210,31 @@ pub struct TableElem {
     #[default(Sides::splat(Abs::pt(5.0).into()))]
     pub inset: Sides<Option<Rel<Length>>>,
 
-    /// The contents of the table cells.
+    /// The contents of the table cells, plus an optional header and footer
+    /// specified with the [`table.header`]($table.header) and
+    /// [`table.footer`]($table.footer) elements, and any extra table lines
+    /// specified with the [`table.hline`]($table.hline) and
+    /// [`table.vline`]($table.vline) elements.
     #[variadic]
-    pub children: Vec<Content>,
+    pub children: Vec<TableChild>,
+}
+
+#[scope]
+impl TableElem {
+    #[elem]
+    type TableCell;
+
+    #[elem]
+    type TableHLine;
+
+    #[elem]
+    type TableVLine;
+
+    #[elem]
+    type TableHeader;
+
+    #[elem]
+    type TableFooter;
 }
 
 impl Layout for TableElem {
@@ -167,26 +252,46 @@ impl Layout for TableElem {
         let column_gutter = self.column_gutter(styles);
         let row_gutter = self.row_gutter(styles);
         let fill = self.fill(styles);
-        let stroke = self.stroke(styles).map(Stroke::unwrap_or_default);
+        let stroke = self.stroke(styles);
 
         let tracks = Axes::new(columns.0.as_slice(), rows.0.as_slice());
         let gutter = Axes::new(column_gutter.0.as_slice(), row_gutter.0.as_slice());
-        let cells =
-            apply_align_inset_to_cells(engine, &tracks, self.children(), align, inset)?;
-
-        // Prepare grid layout by unifying content and gutter tracks.
-        let layouter = GridLayouter::new(
+        let resolve_item = |item: &TableItem| item.to_resolvable(styles);
+        let children = self.children().iter().map(|child| match child {
+            TableChild::Header(header) => ResolvableGridChild::Header {
+                repeat: header.repeat(styles),
+                span: header.span(),
+                items: header.children().iter().map(resolve_item),
+            },
+            TableChild::Footer(footer) => ResolvableGridChild::Footer {
+                repeat: footer.repeat(styles),
+                span: footer.span(),
+                items: footer.children().iter().map(resolve_item),
+            },
+            TableChild::Item(item) => ResolvableGridChild::Item(resolve_item(item)),
+        });
+        let grid = CellGrid::resolve(
             tracks,
             gutter,
-            &cells,
+            children,
             fill,
+            align,
+            inset,
             &stroke,
-            regions,
+            engine,
             styles,
             self.span(),
-        );
+        )
+        .trace(
+            engine.world,
+            || Tracepoint::Call(Some(eco_format!("table"))),
+            self.span(),
+        )?;
+
+        // Prepare grid layout by unifying content and gutter tracks.
+        let layouter = GridLayouter::new(&grid, regions, styles, self.span());
 
-        Ok(layouter.layout(engine)?.fragment)
+        layouter.layout(engine)
     }
 }
 
@@ -227,3 +332,590 @@ impl LocalName for TableElem {
 }
 
 impl Figurable for TableElem {}
+
+/// Any child of a table element.
+#[derive(Debug, PartialEq, Clone, Hash)]
+#[allow(clippy::large_enum_variant)]
+pub enum TableChild {
+    Header(TableHeader),
+    Footer(TableFooter),
+    Item(TableItem),
+}
+
+cast! {
+    TableChild,
+    self => match self {
+        Self::Header(header) => header.into_value(),
+        Self::Footer(footer) => footer.into_value(),
+        Self::Item(item) => item.into_value(),
+    },
+    v: Content => {
+        v.try_into()?
+    },
+}
+
+impl TryFrom<Content> for TableChild {
+    type Error = EcoString;
+
+    fn try_from(value: Content) -> StrResult<Self> {
+        if value.is::<GridHeader>() {
+            bail!(
+                "cannot use `grid.header` as a table header; use `table.header` instead"
+            )
+        }
+        if value.is::<GridFooter>() {
+            bail!(
+                "cannot use `grid.footer` as a table footer; use `table.footer` instead"
+            )
+        }
+
+        if let Some(header) = value.to::<TableHeader>() {
+            return Ok(Self::Header(header.clone()));
+        }
+        if let Some(footer) = value.to::<TableFooter>() {
+            return Ok(Self::Footer(footer.clone()));
+        }
+
+        TableItem::try_from(value).map(Self::Item)
+    }
+}
+
+/// A table item, which is the basic unit of table specification.
+#[derive(Debug, PartialEq, Clone, Hash)]
+#[allow(clippy::large_enum_variant)]
+pub enum TableItem {
+    HLine(TableHLine),
+    VLine(TableVLine),
+    Cell(TableCell),
+}
+
+impl TableItem {
+    fn to_resolvable(&self, styles: StyleChain) -> ResolvableGridItem<TableCell> {
+        match self {
+            Self::HLine(hline) => ResolvableGridItem::HLine {
+                y: hline.y(styles),
+                start: hline.start(styles),
+                end: hline.end(styles),
+                stroke: hline.stroke(styles),
+                span: hline.span(),
+                position: match hline.position(styles) {
+                    VAlign::Bottom => LinePosition::After,
+                    _ => LinePosition::Before,
+                },
+            },
+            Self::VLine(vline) => ResolvableGridItem::VLine {
+                x: vline.x(styles),
+                start: vline.start(styles),
+                end: vline.end(styles),
+                stroke: vline.stroke(styles),
+                span: vline.span(),
+                position: match vline.position(styles) {
+                    HAlign::Left if TextElem::dir_in(styles) == Dir::RTL => {
+                        LinePosition::After
+                    }
+                    HAlign::Right if TextElem::dir_in(styles) == Dir::RTL => {
+                        LinePosition::Before
+                    }
+                    HAlign::End | HAlign::Right => LinePosition::After,
+                    _ => LinePosition::Before,
+                },
+            },
+            Self::Cell(cell) => ResolvableGridItem::Cell(cell.clone()),
+        }
+    }
+}
+
+cast! {
+    TableItem,
+    self => match self {
+        Self::HLine(hline) => hline.into_value(),
+        Self::VLine(vline) => vline.into_value(),
+        Self::Cell(cell) => cell.into_value(),
+    },
+    v: Content => {
+        v.try_into()?
+    },
+}
+
+impl TryFrom<Content> for TableItem {
+    type Error = EcoString;
+
+    fn try_from(value: Content) -> StrResult<Self> {
+        if value.is::<GridHLine>() {
+            bail!("cannot use `grid.hline` as a table line; use `table.hline` instead");
+        }
+        if value.is::<GridVLine>() {
+            bail!("cannot use `grid.vline` as a table line; use `table.vline` instead");
+        }
+
+        if let Some(hline) = value.to::<TableHLine>() {
+            return Ok(Self::HLine(hline.clone()));
+        }
+        if let Some(vline) = value.to::<TableVLine>() {
+            return Ok(Self::VLine(vline.clone()));
+        }
+
+        TableCell::from_value(value.into_value()).map(Self::Cell)
+    }
+}
+
+/// A repeatable table header.
+///
+/// You can use the `repeat` parameter to control whether your table's header
+/// will be repeated across pages.
+///
+/// ```example
+/// #set page(height: 11.5em)
+/// #set table(
+///   fill: (x, y) =>
+///     if x == 0 or y == 0 {
+///       gray.lighten(40%)
+///     },
+///   align: right,
+/// )
+///
+/// #show table.cell.where(x: 0): strong
+/// #show table.cell.where(y: 0): strong
+///
+/// #table(
+///   columns: 4,
+///   table.header(
+///     [], [Blue chip],
+///     [Fresh IPO], [Penny st'k],
+///   ),
+///   table.cell(
+///     rowspan: 6,
+///     align: horizon,
+///     rotate(-90deg, reflow: true)[
+///       *USD / day*
+///     ],
+///   ),
+///   [0.20], [104], [5],
+///   [3.17], [108], [4],
+///   [1.59], [84],  [1],
+///   [0.26], [98],  [15],
+///   [0.01], [195], [4],
+///   [7.34], [57],  [2],
+/// )
+/// ```
+#[elem(name = "header", title = "Table Header")]
+pub struct TableHeader {
+    /// Whether this header should be repeated across pages.
+    #[default(true)]
+    pub repeat: bool,
+
+    /// The cells and lines within the header.
+    #[variadic]
+    pub children: Vec<TableItem>,
+}
+
+/// A repeatable table footer.
+///
+/// Just like the [`table.header`]($table.header) element, the footer can repeat
+/// itself on every page of the table. This is useful for improving legibility
+/// by adding the column labels in both the header and footer of a large table,
+/// totals, or other information that should be visible on every page.
+///
+/// No other table cells may be placed after the footer.
+#[elem(name = "footer", title = "Table Footer")]
+pub struct TableFooter {
+    /// Whether this footer should be repeated across pages.
+    #[default(true)]
+    pub repeat: bool,
+
+    /// The cells and lines within the footer.
+    #[variadic]
+    pub children: Vec<TableItem>,
+}
+
+/// A horizontal line in the table. See the docs for
+/// [`grid.hline`]($grid.hline) for more information regarding how to use this
+/// element's fields.
+///
+/// Overrides any per-cell stroke, including stroke specified through the
+/// table's `stroke` field. Can cross spacing between cells created through the
+/// table's [`column-gutter`]($table.column-gutter) option.
+///
+/// Use this function instead of the table's `stroke` field if you want to
+/// manually place a horizontal line at a specific position in a single table.
+/// Consider using [table's `stroke`]($table.stroke) field or [`table.cell`'s
+/// `stroke`]($table.cell.stroke) field instead if the line you want to place is
+/// part of all your tables' designs.
+///
+/// ```example
+/// #set table.hline(stroke: .6pt)
+///
+/// #table(
+///   stroke: none,
+///   columns: (auto, 1fr),
+///   [09:00], [Badge pick up],
+///   [09:45], [Opening Keynote],
+///   [10:30], [Talk: Typst's Future],
+///   [11:15], [Session: Good PRs],
+///   table.hline(start: 1),
+///   [Noon], [_Lunch break_],
+///   table.hline(start: 1),
+///   [14:00], [Talk: Tracked Layout],
+///   [15:00], [Talk: Automations],
+///   [16:00], [Workshop: Tables],
+///   table.hline(),
+///   [19:00], [Day 1 Attendee Mixer],
+/// )
+/// ```
+#[elem(name = "hline", title = "Table Horizontal Line")]
+pub struct TableHLine {
+    /// The row above which the horizontal line is placed (zero-indexed).
+    /// Functions identically to the `y` field in [`grid.hline`]($grid.hline).
+    pub y: Smart<usize>,
+
+    /// The column at which the horizontal line starts (zero-indexed, inclusive).
+    pub start: usize,
+
+    /// The column before which the horizontal line ends (zero-indexed,
+    /// exclusive).
+    pub end: Option<NonZeroUsize>,
+
+    /// The line's stroke.
+    ///
+    /// Specifying `{none}` removes any lines previously placed across this
+    /// line's range, including hlines or per-cell stroke below it.
+    #[resolve]
+    #[fold]
+    #[default(Some(Stroke::default()))]
+    pub stroke: Option<Stroke>,
+
+    /// The position at which the line is placed, given its row (`y`) - either
+    /// `{top}` to draw above it or `{bottom}` to draw below it.
+    ///
+    /// This setting is only relevant when row gutter is enabled (and
+    /// shouldn't be used otherwise - prefer just increasing the `y` field by
+    /// one instead), since then the position below a row becomes different
+    /// from the position above the next row due to the spacing between both.
+    #[default(VAlign::Top)]
+    #[parse({
+        let option: Option<Spanned<VAlign>> = args.named("position")?;
+        if let Some(Spanned { v: align, span }) = option {
+            if align == VAlign::Horizon {
+                bail!(span, "expected `top` or `bottom`");
+            }
+        }
+        option.map(|spanned| spanned.v)
+    })]
+    pub position: VAlign,
+}
+
+/// A vertical line in the table. See the docs for [`grid.vline`]($grid.vline)
+/// for more information regarding how to use this element's fields.
+///
+/// Overrides any per-cell stroke, including stroke specified through the
+/// table's `stroke` field. Can cross spacing between cells created through the
+/// table's [`row-gutter`]($table.row-gutter) option.
+///
+/// Similar to [`table.hline`]($table.hline), use this function if you want to
+/// manually place a vertical line at a specific position in a single table and
+/// use the [table's `stroke`]($table.stroke) field or [`table.cell`'s
+/// `stroke`]($table.cell.stroke) field instead if the line you want to place is
+/// part of all your tables' designs.
+#[elem(name = "vline", title = "Table Vertical Line")]
+pub struct TableVLine {
+    /// The column before which the vertical line is placed (zero-indexed).
+    /// Functions identically to the `x` field in [`grid.vline`]($grid.vline).
+    pub x: Smart<usize>,
+
+    /// The row at which the vertical line starts (zero-indexed, inclusive).
+    pub start: usize,
+
+    /// The row on top of which the vertical line ends (zero-indexed,
+    /// exclusive).
+    pub end: Option<NonZeroUsize>,
+
+    /// The line's stroke.
+    ///
+    /// Specifying `{none}` removes any lines previously placed across this
+    /// line's range, including vlines or per-cell stroke below it.
+    #[resolve]
+    #[fold]
+    #[default(Some(Stroke::default()))]
+    pub stroke: Option<Stroke>,
+
+    /// The position at which the line is placed, given its column (`x`) -
+    /// either `{start}` to draw before it or `{end}` to draw after it.
+    ///
+    /// The values `{left}` and `{right}` are also accepted, but discouraged as
+    /// they cause your table to be inconsistent between left-to-right and
+    /// right-to-left documents.
+    ///
+    /// This setting is only relevant when column gutter is enabled (and
+    /// shouldn't be used otherwise - prefer just increasing the `x` field by
+    /// one instead), since then the position after a column becomes different
+    /// from the position before the next column due to the spacing between
+    /// both.
+    #[default(HAlign::Start)]
+    #[parse({
+        let option: Option<Spanned<HAlign>> = args.named("position")?;
+        if let Some(Spanned { v: align, span }) = option {
+            if align == HAlign::Center {
+                bail!(span, "expected `start`, `left`, `right`, or `end`");
+            }
+        }
+        option.map(|spanned| spanned.v)
+    })]
+    pub position: HAlign,
+}
+
+/// A cell in the table. Use this to position a cell manually or to apply
+/// styling. To do the latter, you can either use the function to override the
+/// properties for a particular cell, or use it in show rules to apply certain
+/// styles to multiple cells at once.
+///
+/// Perhaps the most important use case of `{table.cell}` is to make a cell span
+/// multiple columns and/or rows with the `colspan` and `rowspan` fields.
+///
+/// ```example
+/// >>> #set page(width: auto)
+/// >>> #show table.cell.where(y: 0): strong
+/// #table(
+///   columns: 3,
+///   [Substance],
+///   [Subcritical °C],
+///   [Supercritical °C],
+///   [Hydrochloric Acid],
+///   [12.0], [92.1],
+///   [Sodium Myreth Sulfate],
+///   [16.6], [104],
+///   [Potassium Hydroxide],
+///   table.cell(colspan: 2)[24.7],
+/// )
+/// ```
+///
+/// For example, you can override the fill, alignment or inset for a single
+/// cell:
+///
+/// ```example
+/// >>> #set page(width: auto)
+/// // You can also import it.
+/// #import table: cell
+///
+/// #table(
+///   columns: 2,
+///   align: center,
+///   [*Trip progress*],
+///   [*Itinerary*],
+///   cell(
+///     align: right,
+///     fill: fuchsia.lighten(80%),
+///     [🚗],
+///   ),
+///   [Get in, folks!],
+///   [🚗], [Eat curbside hotdog],
+///   cell(align: left)[🌴🚗],
+///   cell(
+///     inset: 0.06em,
+///     text(1.62em)[🛖🌅🌊],
+///   ),
+/// )
+/// ```
+///
+/// You may also apply a show rule on `table.cell` to style all cells at once.
+/// Combined with selectors, this allows you to apply styles based on a cell's
+/// position:
+///
+/// ```example
+/// #show table.cell.where(x: 0): strong
+///
+/// #table(
+///   columns: 3,
+///   gutter: 3pt,
+///   [Name], [Age], [Strength],
+///   [Hannes], [36], [Grace],
+///   [Irma], [50], [Resourcefulness],
+///   [Vikram], [49], [Perseverance],
+/// )
+/// ```
+#[elem(name = "cell", title = "Table Cell", Show)]
+pub struct TableCell {
+    /// The cell's body.
+    #[required]
+    pub body: Content,
+
+    /// The cell's column (zero-indexed).
+    /// Functions identically to the `x` field in [`grid.cell`]($grid.cell).
+    pub x: Smart<usize>,
+
+    /// The cell's row (zero-indexed).
+    /// Functions identically to the `y` field in [`grid.cell`]($grid.cell).
+    pub y: Smart<usize>,
+
+    /// The amount of columns spanned by this cell.
+    #[default(NonZeroUsize::ONE)]
+    pub colspan: NonZeroUsize,
+
+    /// The amount of rows spanned by this cell.
+    #[default(NonZeroUsize::ONE)]
+    pub rowspan: NonZeroUsize,
+
+    /// The cell's [fill]($table.fill) override.
+    pub fill: Smart<Option<Paint>>,
+
+    /// The cell's [alignment]($table.align) override.
+    pub align: Smart<Align>,
+
+    /// The cell's [inset]($table.inset) override.
+    pub inset: Smart<Sides<Option<Rel<Length>>>>,
+
+    /// The cell's [stroke]($table.stroke) override.
+    ///
+    /// Functions identically to the `stroke` field in
+    /// [`grid.cell`]($grid.cell).
+    #[resolve]
+    pub stroke: Sides<Option<Option<Stroke>>>,
+
+    /// Whether rows spanned by this cell can be placed in different pages.
+    /// When equal to `{auto}`, a cell spanning only fixed-size rows is
+    /// unbreakable, while a cell spanning at least one `{auto}`-sized row is
+    /// breakable.
+    pub breakable: Smart<bool>,
+
+    /// Whether the cell is part of the table's header.
+    #[internal]
+    #[default(false)]
+    pub header: bool,
+}
+
+cast! {
+    TableCell,
+    v: Content => {
+        if v.is::<GridHeader>() {
+            bail!("cannot place a grid header within another header or footer");
+        }
+        if v.is::<TableHeader>() {
+            bail!("cannot place a table header within another header or footer");
+        }
+        if v.is::<GridFooter>() {
+            bail!("cannot place a grid footer within another footer or header");
+        }
+        if v.is::<TableFooter>() {
+            bail!("cannot place a table footer within another footer or header");
+        }
+        if v.is::<GridCell>() {
+            bail!("cannot use `grid.cell` as a table cell; use `table.cell` instead");
+        }
+        v.into()
+    },
+}
+
+impl Default for TableCell {
+    fn default() -> Self {
+        Self::new(Content::default())
+    }
+}
+
+impl ResolvableCell for TableCell {
+    fn resolve_cell(
+        mut self,
+        x: usize,
+        y: usize,
+        fill: &Option<Paint>,
+        align: Smart<Align>,
+        inset: Sides<Rel<Length>>,
+        stroke: Sides<Option<Option<Stroke<Abs>>>>,
+        breakable: bool,
+        styles: StyleChain,
+    ) -> Cell {
+        let colspan = self.colspan(styles);
+        let rowspan = self.rowspan(styles);
+        let breakable = self.breakable(styles).unwrap_or(breakable);
+        let fill = self.fill(styles).unwrap_or_else(|| fill.clone());
+
+        let cell_stroke = self.stroke(styles);
+        let stroke_overridden =
+            cell_stroke.as_ref().map(|side| matches!(side, Some(Some(_))));
+
+        // Using a typical 'Sides' fold, an unspecified side loses to a
+        // specified side. Additionally, when both are specified, an inner
+        // None wins over the outer Some, and vice-versa. When both are
+        // specified and Some, fold occurs.
+        //
+        // In the end, we flatten because, for layout purposes, an unspecified
+        // cell stroke is the same as specifying 'none', so we equate the two
+        // concepts.
+        let stroke = cell_stroke
+            .zip(stroke)
+            .map(|(inner, outer)| inner.fold_or(outer).flatten());
+        self.push_x(Smart::Custom(x));
+        self.push_y(Smart::Custom(y));
+        self.push_fill(Smart::Custom(fill.clone()));
+        self.push_align(match align {
+            Smart::Custom(align) => {
+                Smart::Custom(self.align(styles).map_or(align, |inner| inner.fold(align)))
+            }
+            // Don't fold if the table is using outer alignment. Use the
+            // cell's alignment instead (which, in the end, will fold with
+            // the outer alignment when it is effectively displayed).
+            Smart::Auto => self.align(styles),
+        });
+        self.push_inset(Smart::Custom(
+            self.inset(styles).map_or(inset, |inner| inner.fold(inset)).map(Some),
+        ));
+        self.push_stroke(
+            // Here we convert the resolved stroke to a regular stroke, however
+            // with resolved units (that is, 'em' converted to absolute units).
+            // We also convert any stroke unspecified by both the cell and the
+            // outer stroke ('None' in the folded stroke) to 'none', that is,
+            // all sides are present in the resulting Sides object accessible
+            // by show rules on table cells.
+            stroke
+                .clone()
+                .map(|side| Some(side.map(|stroke| stroke.map(Length::from)))),
+        );
+        self.push_breakable(Smart::Custom(breakable));
+        self.push_colspan(colspan);
+        self.push_rowspan(rowspan);
+        Cell {
+            body: self.pack(),
+            fill,
+            colspan,
+            rowspan,
+            stroke,
+            stroke_overridden,
+            breakable,
+        }
+    }
+
+    fn x(&self, styles: StyleChain) -> Smart<usize> {
+        self.x(styles)
+    }
+
+    fn y(&self, styles: StyleChain) -> Smart<usize> {
+        self.y(styles)
+    }
+
+    fn colspan(&self, styles: StyleChain) -> NonZeroUsize {
+        self.colspan(styles)
+    }
+
+    fn rowspan(&self, styles: StyleChain) -> NonZeroUsize {
+        self.rowspan(styles)
+    }
+
+    fn mark_header(body: &mut Content) {
+        if let Some(cell) = body.to_mut::<Self>() {
+            cell.push_header(true);
+        }
+    }
+}
+
+impl Show for TableCell {
+    fn show(&self, _engine: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        show_grid_cell(self.body().clone(), self.inset(styles), self.align(styles))
+    }
+}
+
+impl From<Content> for TableCell {
+    fn from(value: Content) -> Self {
+        value
+            .to::<Self>()
+            .cloned()
+            .unwrap_or_else(|| Self::new(value.clone()).spanned(value.span()))
+    }
+}
@@ -6,7 +6,7 @@ use crate::foundations::{
 use crate::layout::{
     BlockElem, Em, Fragment, HElem, Layout, Length, Regions, Spacing, VElem,
 };
-use crate::model::ParElem;
+use crate::model::{item_tag, ParElem};
 use crate::util::Numeric;
 
 /// A list of terms and their descriptions.
@@ -133,9 +133,16 @@ impl Layout for TermsElem {
             if !indent.is_zero() {
                 seq.push(HElem::new(indent.into()).pack());
             }
-            seq.push(child.term().clone().strong());
-            seq.push((*separator).clone());
-            seq.push(child.description().clone());
+
+            let tag = item_tag(engine, child.clone().pack(), styles);
+            seq.push(
+                Content::sequence([
+                    child.term().clone().strong(),
+                    (*separator).clone(),
+                    child.description().clone(),
+                ])
+                .styled_with_map(tag),
+            );
         }
 
         Content::sequence(seq)
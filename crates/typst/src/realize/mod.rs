@@ -23,8 +23,8 @@ use crate::layout::{
 };
 use crate::math::{EquationElem, LayoutMath};
 use crate::model::{
-    CiteElem, CiteGroup, DocumentElem, EnumElem, EnumItem, ListElem, ListItem, ParElem,
-    ParbreakElem, TermItem, TermsElem,
+    CiteElem, CiteGroup, DocumentElem, EnumElem, EnumItem, FootnoteEntry, LinkElem,
+    ListElem, ListItem, ParElem, ParbreakElem, TableCell, TableElem, TermItem, TermsElem,
 };
 use crate::syntax::Span;
 use crate::text::{LinebreakElem, SmartQuoteElem, SpaceElem, TextElem};
@@ -88,7 +88,7 @@ pub fn realize_block<'a>(
 
 /// Whether the target is affected by show rules in the given style chain.
 pub fn applicable(target: &Content, styles: StyleChain) -> bool {
-    if target.needs_preparation() {
+    if needs_preparation(target, styles) {
         return true;
     }
 
@@ -110,6 +110,30 @@ pub fn applicable(target: &Content, styles: StyleChain) -> bool {
     false
 }
 
+/// Whether the target still needs to be located or synthesized.
+fn needs_preparation(target: &Content, styles: StyleChain) -> bool {
+    target.needs_preparation()
+        || (!target.is_prepared()
+            && is_structural(target)
+            && DocumentElem::tagged_in(styles))
+}
+
+/// Whether an element is part of a tagged document's logical structure
+/// without being locatable on its own.
+///
+/// Such elements are only located in tagged documents so that exporters can
+/// find out which content belongs to them.
+fn is_structural(target: &Content) -> bool {
+    target.is::<ListElem>()
+        || target.is::<EnumElem>()
+        || target.is::<TermsElem>()
+        || target.is::<TableElem>()
+        || target.is::<TableCell>()
+        || target.is::<LinkElem>()
+        || target.is::<ImageElem>()
+        || target.is::<FootnoteEntry>()
+}
+
 /// Apply the show rules in the given style chain to a target.
 pub fn realize(
     engine: &mut Engine,
@@ -117,9 +141,12 @@ pub fn realize(
     styles: StyleChain,
 ) -> SourceResult<Option<Content>> {
     // Pre-process.
-    if target.needs_preparation() {
+    if needs_preparation(target, styles) {
         let mut elem = target.clone();
-        if target.can::<dyn Locatable>() || target.label().is_some() {
+        if target.can::<dyn Locatable>()
+            || target.label().is_some()
+            || is_structural(target)
+        {
             let location = engine.locator.locate(hash128(target));
             elem.set_location(location);
         }
@@ -1,26 +1,31 @@
 use std::hash::Hash;
+use std::num::NonZeroUsize;
 use std::ops::Range;
 use std::sync::Arc;
 
 use ecow::{eco_format, EcoString, EcoVec};
 use once_cell::sync::Lazy;
 use once_cell::unsync::Lazy as UnsyncLazy;
+use smallvec::smallvec;
 use syntect::highlighting as synt;
 use syntect::parsing::{SyntaxDefinition, SyntaxSet, SyntaxSetBuilder};
 use unicode_segmentation::UnicodeSegmentation;
 
-use crate::diag::{At, FileError, SourceResult, StrResult};
+use crate::diag::{bail, At, FileError, SourceResult, StrResult};
 use crate::engine::Engine;
 use crate::foundations::{
-    cast, elem, scope, Args, Array, Bytes, Content, Finalize, Fold, NativeElement,
+    array, cast, elem, scope, Args, Array, Bytes, Content, Finalize, Fold, NativeElement,
     PlainText, Show, Smart, StyleChain, Styles, Synthesize, Value,
 };
-use crate::layout::{BlockElem, Em, HAlign};
-use crate::model::Figurable;
+use crate::layout::{
+    Align, BlockElem, Em, Fr, GridCell, GridChild, GridElem, GridItem, HAlign, Length,
+    Rel, Sides, Sizing, TrackSizings,
+};
+use crate::model::{Figurable, ParElem};
 use crate::syntax::{split_newlines, LinkedNode, Spanned};
 use crate::text::{
-    FontFamily, FontList, Hyphenate, Lang, LinebreakElem, LocalName, Region,
-    SmartQuoteElem, TextElem, TextSize,
+    FontFamily, FontList, HighlightElem, Hyphenate, Lang, LinebreakElem, LocalName,
+    Region, SmartQuoteElem, TextElem, TextSize,
 };
 use crate::util::option_eq;
 use crate::visualize::Color;
@@ -142,7 +147,8 @@ pub struct RawElem {
     ///
     /// Apart from typical language tags known from Markdown, this supports the
     /// `{"typ"}` and `{"typc"}` tags for Typst markup and Typst code,
-    /// respectively.
+    /// respectively. The `{"ansi"}` tag renders terminal output with the
+    /// colors and text styles of its ANSI escape sequences.
     ///
     /// ````example
     /// ```typ
@@ -256,6 +262,74 @@ pub struct RawElem {
     #[default(2)]
     pub tab_size: usize,
 
+    /// Whether to show line numbers next to the lines of a raw block.
+    ///
+    /// The numbers use the theme's gutter color, if it defines one. This
+    /// option is ignored if this is not a raw block.
+    ///
+    /// ````example
+    /// #set raw(numbers: true)
+    ///
+    /// ```rust
+    /// fn main() {
+    ///     println!("Hello World!");
+    /// }
+    /// ```
+    /// ````
+    #[default(false)]
+    pub numbers: bool,
+
+    /// The number of the raw text's first line.
+    ///
+    /// ````example
+    /// #set raw(numbers: true)
+    ///
+    /// #raw(
+    ///   block: true,
+    ///   lang: "rust",
+    ///   start-line: 42,
+    ///   "let answer = 42;",
+    /// )
+    /// ````
+    #[default(1)]
+    pub start_line: i64,
+
+    /// The range of lines to display, given as the positions of the first
+    /// and the last line within the raw text, starting at one.
+    ///
+    /// This is useful to show an excerpt of a file that is
+    /// [read]($read) in its entirety. The excerpt is highlighted with the
+    /// context of the whole text and keeps the original line numbers.
+    ///
+    /// ````example
+    /// #raw(
+    ///   block: true,
+    ///   lang: "rust",
+    ///   numbers: true,
+    ///   line-range: (2, 3),
+    ///   "fn main() {\n    let x = 1;\n    println!(\"{x}\");\n}",
+    /// )
+    /// ````
+    // Not called `lines`, as that name is taken by the synthesized lines
+    // below, which show rules access through `it.lines`.
+    pub line_range: Option<LineRange>,
+
+    /// The numbers of the lines to highlight by shading them.
+    ///
+    /// The shading uses the theme's line highlight color, if it defines one.
+    /// This option is ignored if this is not a raw block.
+    ///
+    /// ````example
+    /// #set raw(highlight-lines: (2,))
+    ///
+    /// ```rust
+    /// fn main() {
+    ///     println!("Hello World!");
+    /// }
+    /// ```
+    /// ````
+    pub highlight_lines: Vec<i64>,
+
     /// The stylized lines of raw text.
     ///
     /// Made accessible for the [`raw.line` element]($raw.line).
@@ -282,7 +356,11 @@ impl RawElem {
                     syntax.file_extensions.iter().map(|s| s.as_str()).collect(),
                 )
             })
-            .chain([("Typst", vec!["typ"]), ("Typst (code)", vec!["typc"])])
+            .chain([
+                ("Typst", vec!["typ"]),
+                ("Typst (code)", vec!["typc"]),
+                ("ANSI", vec!["ansi"]),
+            ])
             .collect()
     }
 }
@@ -299,6 +377,7 @@ impl Synthesize for RawElem {
 
         let lines = split_newlines(&text);
         let count = lines.len() as i64;
+        let start = self.start_line(styles);
 
         let lang = self
             .lang(styles)
@@ -334,7 +413,7 @@ impl Synthesize for RawElem {
                 &mut |i, range, line| {
                     seq.push(
                         RawLine::new(
-                            i + 1,
+                            start + i,
                             count,
                             EcoString::from(&text[range]),
                             Content::sequence(line.drain(..)),
@@ -344,6 +423,15 @@ impl Synthesize for RawElem {
                 },
             )
             .highlight();
+        } else if lang.as_deref() == Some("ansi") {
+            let mut highlighter = AnsiHighlighter::new(foreground);
+            for (i, line) in lines.into_iter().enumerate() {
+                let (plain, body) = highlighter.highlight_line(line);
+                seq.push(
+                    RawLine::new(start + i as i64, count, plain, body)
+                        .spanned(self.span()),
+                );
+            }
         } else if let Some((syntax_set, syntax)) = lang.and_then(|token| {
             RAW_SYNTAXES
                 .find_syntax_by_token(&token)
@@ -365,7 +453,7 @@ impl Synthesize for RawElem {
 
                 seq.push(
                     RawLine::new(
-                        i as i64 + 1,
+                        start + i as i64,
                         count,
                         EcoString::from(line),
                         Content::sequence(line_content),
@@ -376,7 +464,7 @@ impl Synthesize for RawElem {
         } else {
             seq.extend(lines.into_iter().enumerate().map(|(i, line)| {
                 RawLine::new(
-                    i as i64 + 1,
+                    start + i as i64,
                     count,
                     EcoString::from(line),
                     TextElem::packed(line),
@@ -385,6 +473,21 @@ impl Synthesize for RawElem {
             }));
         };
 
+        // Highlight the whole text before selecting the lines, so that the
+        // highlighting of the excerpt has the correct context.
+        if let Some(LineRange { start, end }) = self.line_range(styles) {
+            if start.get() > seq.len() {
+                bail!(
+                    self.span(),
+                    "line range starts at line {start}, but the text only has {} {}",
+                    seq.len(),
+                    if seq.len() == 1 { "line" } else { "lines" },
+                );
+            }
+            seq.truncate(end.get());
+            seq.drain(..start.get() - 1);
+        }
+
         self.push_lines(seq);
 
         Ok(())
@@ -394,6 +497,13 @@ impl Synthesize for RawElem {
 impl Show for RawElem {
     #[tracing::instrument(name = "RawElem::show", skip_all)]
     fn show(&self, _: &mut Engine, styles: StyleChain) -> SourceResult<Content> {
+        if self.block(styles)
+            && (self.numbers(styles) || !self.highlight_lines(styles).is_empty())
+        {
+            let grid = self.grid(styles);
+            return Ok(BlockElem::new().with_body(Some(grid)).pack());
+        }
+
         let mut lines = EcoVec::with_capacity((2 * self.lines().len()).saturating_sub(1));
         for (i, line) in self.lines().iter().enumerate() {
             if i != 0 {
@@ -414,6 +524,64 @@ impl Show for RawElem {
     }
 }
 
+impl RawElem {
+    /// Arrange the lines of a raw block in a grid, with their numbers in a
+    /// separate column and highlighted lines shaded.
+    fn grid(&self, styles: StyleChain) -> Content {
+        let theme = self.theme(styles).as_ref().as_ref().map(|theme_path| {
+            load_theme(theme_path, self.theme_data(styles).as_ref().as_ref().unwrap())
+                .unwrap()
+        });
+        // Fall back to the default theme's colors if the theme lacks them.
+        let settings = &theme.as_deref().unwrap_or(&RAW_THEME).settings;
+        let defaults = &RAW_THEME.settings;
+        let gutter = settings.gutter_foreground.or(defaults.gutter_foreground);
+        let shade = settings.line_highlight.or(defaults.line_highlight);
+        let (gutter, shade) = (to_typst(gutter.unwrap()), to_typst(shade.unwrap()));
+
+        // Half of the leading goes above and below each line, so that the
+        // shading of consecutive lines is continuous. It is converted to ems
+        // to scale with the raw text's size, like the leading itself.
+        let half = Em::new(ParElem::leading_in(styles) / TextElem::size_in(styles) / 2.0);
+        let inset = |right: Em| {
+            let y = Some(Length::from(half).into());
+            Sides::new(Some(Rel::zero()), y, Some(Length::from(right).into()), y)
+        };
+
+        let numbers = self.numbers(styles);
+        let highlighted = self.highlight_lines(styles);
+        let align = Align::from(self.align(styles));
+
+        let mut cells = vec![];
+        for line in self.lines() {
+            let mut cell = |body: Content, align: Align, right: Em| {
+                let mut cell = GridCell::new(body)
+                    .with_align(Smart::Custom(align))
+                    .with_inset(Smart::Custom(inset(right)));
+                if highlighted.contains(line.number()) {
+                    cell.push_fill(Smart::Custom(Some(shade.into())));
+                }
+                cells.push(GridChild::Item(GridItem::Cell(cell)));
+            };
+
+            if numbers {
+                let number = TextElem::packed(eco_format!("{}", line.number()))
+                    .styled(TextElem::set_fill(gutter.into()));
+                cell(number, HAlign::End.into(), Em::one());
+            }
+
+            cell(line.clone().pack(), align, Em::zero());
+        }
+
+        let mut columns = smallvec![Sizing::Fr(Fr::one())];
+        if numbers {
+            columns.insert(0, Sizing::Auto);
+        }
+
+        GridElem::new(cells).with_columns(TrackSizings(columns)).pack()
+    }
+}
+
 impl Finalize for RawElem {
     fn finalize(&self, realized: Content, _: StyleChain) -> Content {
         let mut styles = Styles::new();
@@ -470,6 +638,33 @@ impl PlainText for RawElem {
     }
 }
 
+/// A range of lines, given by the positions of its first and last line.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct LineRange {
+    /// The position of the first line, starting at one.
+    pub start: NonZeroUsize,
+    /// The position of the last line, inclusive.
+    pub end: NonZeroUsize,
+}
+
+cast! {
+    LineRange,
+    self => array![self.start, self.end].into_value(),
+    array: Array => {
+        let mut iter = array.into_iter();
+        match (iter.next(), iter.next(), iter.next()) {
+            (Some(a), Some(b), None) => {
+                let (start, end) = (a.cast()?, b.cast()?);
+                if end < start {
+                    bail!("line range must not end before it starts");
+                }
+                Self { start, end }
+            }
+            _ => bail!("line range must contain exactly two line numbers"),
+        }
+    },
+}
+
 /// A highlighted line of raw text.
 ///
 /// This is a helper element that is synthesized by [`raw`]($raw) elements.
@@ -630,6 +825,197 @@ fn styled(piece: &str, foreground: synt::Color, style: synt::Style) -> Content {
     body
 }
 
+/// Interprets the SGR escape sequences in terminal output.
+///
+/// The styling carries over from one line to the next, like in a terminal.
+/// Other escape sequences are removed.
+struct AnsiHighlighter {
+    /// The default foreground color.
+    foreground: synt::Color,
+    /// The current style, with the default foreground if no color is set.
+    style: synt::Style,
+    /// The current background color, if any.
+    background: Option<synt::Color>,
+}
+
+impl AnsiHighlighter {
+    /// Create a new highlighter with the theme's foreground color.
+    fn new(foreground: synt::Color) -> Self {
+        Self {
+            foreground,
+            style: synt::Style { foreground, ..synt::Style::default() },
+            background: None,
+        }
+    }
+
+    /// Highlight a line, returning its text without escape sequences and the
+    /// styled text.
+    fn highlight_line(&mut self, line: &str) -> (EcoString, Content) {
+        let mut plain = EcoString::new();
+        let mut pieces = vec![];
+        let mut rest = line;
+
+        while !rest.is_empty() {
+            let end = rest.find('\x1b').unwrap_or(rest.len());
+            if end > 0 {
+                let piece = &rest[..end];
+                plain.push_str(piece);
+                pieces.push(self.styled(piece));
+            }
+
+            rest = &rest[end..];
+            if let Some(seq) = rest.strip_prefix('\x1b') {
+                rest = self.escape(seq);
+            }
+        }
+
+        (plain, Content::sequence(pieces))
+    }
+
+    /// Style a piece of text with the current style.
+    fn styled(&self, piece: &str) -> Content {
+        let body = styled(piece, self.foreground, self.style);
+        match self.background {
+            Some(background) => {
+                HighlightElem::new(body).with_fill(to_typst(background).into()).pack()
+            }
+            None => body,
+        }
+    }
+
+    /// Process an escape sequence, given the text after the escape character,
+    /// and return the text after the sequence.
+    fn escape<'a>(&mut self, seq: &'a str) -> &'a str {
+        let mut chars = seq.chars();
+        match chars.next() {
+            // A control sequence, which ends with a byte in `@..=~`.
+            Some('[') => {
+                let body = chars.as_str();
+                let end = body.find(|c| matches!(c, '@'..='~')).unwrap_or(body.len());
+                if body[end..].starts_with('m') {
+                    self.select(&body[..end]);
+                }
+                body.get(end + 1..).unwrap_or_default()
+            }
+            // An operating system command, which ends with BEL or ST.
+            Some(']') => {
+                let body = chars.as_str();
+                match body.find(['\x07', '\x1b']) {
+                    Some(i) if body[i..].starts_with('\x07') => &body[i + 1..],
+                    Some(i) => body[i + 1..].strip_prefix('\\').unwrap_or(&body[i..]),
+                    None => "",
+                }
+            }
+            _ => chars.as_str(),
+        }
+    }
+
+    /// Apply the parameters of a select graphic rendition sequence.
+    fn select(&mut self, params: &str) {
+        // Empty parameters default to zero. Invalid ones are yielded as `None`
+        // and skipped.
+        let mut params = params.split(';').map(|param| match param {
+            "" => Some(0),
+            _ => param.parse::<u16>().ok(),
+        });
+
+        while let Some(param) = params.next() {
+            let Some(param) = param else { continue };
+            let font_style = &mut self.style.font_style;
+            match param {
+                0 => {
+                    self.style = synt::Style {
+                        foreground: self.foreground,
+                        ..synt::Style::default()
+                    };
+                    self.background = None;
+                }
+                1 => font_style.insert(synt::FontStyle::BOLD),
+                3 => font_style.insert(synt::FontStyle::ITALIC),
+                4 => font_style.insert(synt::FontStyle::UNDERLINE),
+                22 => font_style.remove(synt::FontStyle::BOLD),
+                23 => font_style.remove(synt::FontStyle::ITALIC),
+                24 => font_style.remove(synt::FontStyle::UNDERLINE),
+                30..=37 => self.style.foreground = ansi_color(param as u8 - 30),
+                90..=97 => self.style.foreground = ansi_color(param as u8 - 90 + 8),
+                39 => self.style.foreground = self.foreground,
+                40..=47 => self.background = Some(ansi_color(param as u8 - 40)),
+                100..=107 => self.background = Some(ansi_color(param as u8 - 100 + 8)),
+                49 => self.background = None,
+                38 | 48 => {
+                    let color = match params.next().flatten() {
+                        Some(5) => next_byte(&mut params).map(ansi_color),
+                        Some(2) => match (
+                            next_byte(&mut params),
+                            next_byte(&mut params),
+                            next_byte(&mut params),
+                        ) {
+                            (Some(r), Some(g), Some(b)) => {
+                                Some(synt::Color { r, g, b, a: 0xff })
+                            }
+                            _ => None,
+                        },
+                        _ => None,
+                    };
+                    if let Some(color) = color {
+                        if param == 38 {
+                            self.style.foreground = color;
+                        } else {
+                            self.background = Some(color);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The next parameter of a select graphic rendition sequence as a color
+/// component or palette index, if it is valid and fits into a byte.
+fn next_byte(params: &mut impl Iterator<Item = Option<u16>>) -> Option<u8> {
+    params.next().flatten().and_then(|param| u8::try_from(param).ok())
+}
+
+/// The color with the given index in the 256-color palette of xterm.
+fn ansi_color(index: u8) -> synt::Color {
+    const BASIC: [(u8, u8, u8); 16] = [
+        (0x00, 0x00, 0x00),
+        (0xcd, 0x00, 0x00),
+        (0x00, 0xcd, 0x00),
+        (0xcd, 0xcd, 0x00),
+        (0x00, 0x00, 0xee),
+        (0xcd, 0x00, 0xcd),
+        (0x00, 0xcd, 0xcd),
+        (0xe5, 0xe5, 0xe5),
+        (0x7f, 0x7f, 0x7f),
+        (0xff, 0x00, 0x00),
+        (0x00, 0xff, 0x00),
+        (0xff, 0xff, 0x00),
+        (0x5c, 0x5c, 0xff),
+        (0xff, 0x00, 0xff),
+        (0x00, 0xff, 0xff),
+        (0xff, 0xff, 0xff),
+    ];
+
+    let (r, g, b) = match index {
+        0..=15 => BASIC[index as usize],
+        // A 6x6x6 color cube.
+        16..=231 => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + 40 * v };
+            let i = index - 16;
+            (level(i / 36), level(i / 6 % 6), level(i % 6))
+        }
+        // A grayscale ramp.
+        232..=255 => {
+            let v = 8 + 10 * (index - 232);
+            (v, v, v)
+        }
+    };
+
+    synt::Color { r, g, b, a: 0xff }
+}
+
 fn to_typst(synt::Color { r, g, b, a }: synt::Color) -> Color {
     Color::from_u8(r, g, b, a)
 }
@@ -744,7 +1130,11 @@ pub static RAW_SYNTAXES: Lazy<syntect::parsing::SyntaxSet> =
 pub static RAW_THEME: Lazy<synt::Theme> = Lazy::new(|| synt::Theme {
     name: Some("Typst Light".into()),
     author: Some("The Typst Project Developers".into()),
-    settings: synt::ThemeSettings::default(),
+    settings: synt::ThemeSettings {
+        gutter_foreground: Some(to_syn(Color::from_u8(0x8a, 0x8a, 0x8a, 0xff))),
+        line_highlight: Some(to_syn(Color::from_u8(0xee, 0xee, 0xee, 0xff))),
+        ..synt::ThemeSettings::default()
+    },
     scopes: vec![
         item("comment", Some("#8a8a8a"), None),
         item("constant.character.escape", Some("#1d6c76"), None),
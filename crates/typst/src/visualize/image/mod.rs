@@ -17,7 +17,7 @@ use crate::diag::{bail, At, SourceResult, StrResult};
 use crate::engine::Engine;
 use crate::foundations::{
     cast, elem, func, scope, Bytes, Cast, Content, NativeElement, Resolve, Smart,
-    StyleChain,
+    StyleChain, Synthesize,
 };
 use crate::layout::{
     Abs, Axes, FixedAlign, Fragment, Frame, FrameItem, Layout, Length, Point, Regions,
@@ -51,7 +51,7 @@ use crate::World;
 /// ```
 ///
 /// [gh-svg]: https://github.com/typst/typst/issues?q=is%3Aopen+is%3Aissue+label%3Asvg
-#[elem(scope, Layout, LocalName, Figurable)]
+#[elem(scope, Synthesize, Layout, LocalName, Figurable)]
 pub struct ImageElem {
     /// Path to an image file.
     #[required]
@@ -142,18 +142,13 @@ impl ImageElem {
     }
 }
 
-impl Layout for ImageElem {
-    #[tracing::instrument(name = "ImageElem::layout", skip_all)]
-    fn layout(
-        &self,
-        engine: &mut Engine,
-        styles: StyleChain,
-        regions: Regions,
-    ) -> SourceResult<Fragment> {
-        // Take the format that was explicitly defined, or parse the extension,
-        // or try to detect the format.
-        let data = self.data();
-        let format = match self.format(styles) {
+impl ImageElem {
+    /// Determine the image's format.
+    ///
+    /// Takes the format that was explicitly defined, or parses the extension,
+    /// or tries to detect the format.
+    pub fn determine_format(&self, styles: StyleChain) -> SourceResult<ImageFormat> {
+        Ok(match self.format(styles) {
             Smart::Custom(v) => v,
             Smart::Auto => {
                 let ext = std::path::Path::new(self.path().as_str())
@@ -167,7 +162,7 @@ impl Layout for ImageElem {
                     "jpg" | "jpeg" => ImageFormat::Raster(RasterFormat::Jpg),
                     "gif" => ImageFormat::Raster(RasterFormat::Gif),
                     "svg" | "svgz" => ImageFormat::Vector(VectorFormat::Svg),
-                    _ => match &data {
+                    _ => match self.data() {
                         Readable::Str(_) => ImageFormat::Vector(VectorFormat::Svg),
                         Readable::Bytes(bytes) => match RasterFormat::detect(bytes) {
                             Some(f) => ImageFormat::Raster(f),
@@ -176,7 +171,28 @@ impl Layout for ImageElem {
                     },
                 }
             }
-        };
+        })
+    }
+}
+
+impl Synthesize for ImageElem {
+    fn synthesize(&mut self, _: &mut Engine, styles: StyleChain) -> SourceResult<()> {
+        // Exporters read the alternative text from the element itself.
+        self.push_alt(self.alt(styles));
+        Ok(())
+    }
+}
+
+impl Layout for ImageElem {
+    #[tracing::instrument(name = "ImageElem::layout", skip_all)]
+    fn layout(
+        &self,
+        engine: &mut Engine,
+        styles: StyleChain,
+        regions: Regions,
+    ) -> SourceResult<Fragment> {
+        let data = self.data();
+        let format = self.determine_format(styles)?;
 
         let image = Image::with_fonts(
             data.clone().into(),
@@ -4,6 +4,7 @@ use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
 use std::fmt::{self, Display, Formatter, Write as _};
 use std::io::{self, IsTerminal, Write};
+use std::num::NonZeroUsize;
 use std::ops::Range;
 use std::path::{Path, PathBuf, MAIN_SEPARATOR_STR};
 use std::sync::{OnceLock, RwLock};
@@ -21,12 +22,14 @@ use typst::foundations::{
     eco_format, func, Bytes, Datetime, NoneValue, Repr, Smart, Value,
 };
 use typst::introspection::Meta;
-use typst::layout::{Abs, Frame, FrameItem, Margin, PageElem, Transform};
+use typst::layout::{Abs, Frame, FrameItem, Margin, PageElem, PageRanges, Transform};
 use typst::model::Document;
 use typst::syntax::{FileId, PackageVersion, Source, SyntaxNode, VirtualPath};
 use typst::text::{Font, FontBook, TextElem, TextSize};
+use typst::util::NonZeroExt;
 use typst::visualize::Color;
 use typst::{Library, World, WorldExt};
+use typst_pdf::{PdfOptions, PdfStandard};
 use unscanny::Scanner;
 use walkdir::WalkDir;
 
@@ -36,6 +39,7 @@ const REF_DIR: &str = "ref";
 const PNG_DIR: &str = "png";
 const PDF_DIR: &str = "pdf";
 const SVG_DIR: &str = "svg";
+const HTML_DIR: &str = "html";
 const FONT_DIR: &str = "../assets/fonts";
 const ASSET_DIR: &str = "../assets";
 
@@ -384,10 +388,12 @@ fn test(
     let mut output = String::new();
     let mut ok = true;
     let mut updated = false;
+    let mut updated_html = false;
     let mut frames = vec![];
     let mut line = 0;
     let mut compare_ref = None;
     let mut validate_hints = None;
+    let mut html = None::<String>;
     let mut compare_ever = false;
     let mut rng = LinearShift::new();
 
@@ -417,6 +423,9 @@ fn test(
             for line in part.lines() {
                 compare_ref = get_flag_metadata(line, "Ref").or(compare_ref);
                 validate_hints = get_flag_metadata(line, "Hints").or(validate_hints);
+                if get_flag_metadata(line, "HTML") == Some(true) {
+                    html = Some(String::new());
+                }
             }
         } else {
             let (part_ok, compare_here, part_frames) = test_part(
@@ -429,6 +438,7 @@ fn test(
                 validate_hints.unwrap_or(true),
                 line,
                 &mut rng,
+                html.as_mut(),
             );
 
             ok &= part_ok;
@@ -439,14 +449,41 @@ fn test(
         line += part.lines().count() + 1;
     }
 
+    if let Some(html) = &html {
+        let html = &normalize_html(html);
+        let html_path = Path::new(HTML_DIR).join(name).with_extension("html");
+        let ref_path = ref_path.with_extension("html");
+        fs::create_dir_all(html_path.parent().unwrap()).unwrap();
+        fs::write(&html_path, html).unwrap();
+
+        match fs::read_to_string(&ref_path) {
+            Ok(ref_html) if ref_html == *html => {}
+            result => {
+                if args.update {
+                    fs::create_dir_all(ref_path.parent().unwrap()).unwrap();
+                    fs::write(&ref_path, html).unwrap();
+                    updated_html = true;
+                } else if result.is_ok() {
+                    writeln!(output, "  Does not match reference HTML.").unwrap();
+                    ok = false;
+                } else {
+                    writeln!(output, "  Failed to open reference HTML.").unwrap();
+                    ok = false;
+                }
+            }
+        }
+    }
+
     let document = Document { pages: frames, ..Default::default() };
     if compare_ever {
         if let Some(pdf_path) = pdf_path {
-            let pdf_data = typst_pdf::pdf(
-                &document,
-                Some(&format!("typst-test: {}", name.display())),
-                world.today(Some(0)),
-            );
+            let ident = format!("typst-test: {}", name.display());
+            let options = PdfOptions {
+                ident: Some(&ident),
+                timestamp: world.today(Some(0)),
+                ..PdfOptions::default()
+            };
+            let pdf_data = typst_pdf::pdf(&document, &options).unwrap();
             fs::create_dir_all(pdf_path.parent().unwrap()).unwrap();
             fs::write(pdf_path, pdf_data).unwrap();
         }
@@ -461,7 +498,7 @@ fn test(
         fs::create_dir_all(png_path.parent().unwrap()).unwrap();
         canvas.save_png(png_path).unwrap();
 
-        let svg = typst_svg::svg_merged(&document.pages, Abs::pt(5.0));
+        let svg = typst_svg::svg_merged(&document, Abs::pt(5.0), NonZeroUsize::ONE, None);
         fs::create_dir_all(svg_path.parent().unwrap()).unwrap();
         std::fs::write(svg_path, svg.as_bytes()).unwrap();
 
@@ -500,7 +537,7 @@ fn test(
             writeln!(stdout, " ✔").unwrap();
             // Don't clear the line when the reference image was updated, to
             // show in the output which test had its image updated.
-            if !updated && stdout.is_terminal() {
+            if !updated && !updated_html && stdout.is_terminal() {
                 // ANSI escape codes: cursor moves up and clears the line.
                 write!(stdout, "\x1b[1A\x1b[2K").unwrap();
             }
@@ -510,6 +547,9 @@ fn test(
         if updated {
             writeln!(stdout, "  Updated reference image.").unwrap();
         }
+        if updated_html {
+            writeln!(stdout, "  Updated reference HTML.").unwrap();
+        }
         if !output.is_empty() {
             stdout.write_all(output.as_bytes()).unwrap();
         }
@@ -546,6 +586,7 @@ fn test_part(
     validate_hints: bool,
     line: usize,
     rng: &mut LinearShift,
+    html: Option<&mut String>,
 ) -> (bool, bool, Vec<Frame>) {
     let mut ok = true;
 
@@ -574,7 +615,25 @@ fn test_part(
 
     let mut tracer = Tracer::new();
     let (mut frames, diagnostics) = match typst::compile(world, &mut tracer) {
-        Ok(document) => (document.pages, tracer.warnings()),
+        Ok(document) => {
+            let mut diagnostics = tracer.warnings();
+            if let Some(pdf) = &metadata.pdf {
+                match typst_pdf::pdf(&document, &pdf.options(world)) {
+                    Ok(data) => ok &= test_pdf(output, i, &data, pdf),
+                    Err(errors) => diagnostics.extend(errors),
+                }
+            }
+            if let Some(svg) = &metadata.svg {
+                ok &= test_svg(output, i, &document, svg);
+            }
+            if let Some(html) = html {
+                match typst_html::html(world, &document) {
+                    Ok(text) => html.push_str(&text),
+                    Err(errors) => diagnostics.extend(errors),
+                }
+            }
+            (document.pages, diagnostics)
+        }
         Err(errors) => {
             let mut warnings = tracer.warnings();
             warnings.extend(errors);
@@ -680,6 +739,46 @@ struct TestConfiguration {
 struct TestPartMetadata {
     part_configuration: TestConfiguration,
     annotations: HashSet<Annotation>,
+    pdf: Option<PdfExpectations>,
+    svg: Option<SvgExpectations>,
+}
+
+/// How to export a part to PDF and what the exported file must contain.
+///
+/// Set up with `// PDF-Standard: a-2b` and `// PDF-Pages: 2-3`. Each
+/// `// PDF: ..` line names a snippet the file must contain and each
+/// `// PDF-Lacks: ..` line one it must not contain. Snippets are matched
+/// against the file with inflated streams and whitespace collapsed to single
+/// spaces. Errors from the export are checked like any other diagnostics.
+#[derive(Default)]
+struct PdfExpectations {
+    standard: Option<PdfStandard>,
+    page_ranges: Option<PageRanges>,
+    contains: Vec<String>,
+    lacks: Vec<String>,
+}
+
+impl PdfExpectations {
+    fn options(&self, world: &TestWorld) -> PdfOptions<'static> {
+        PdfOptions {
+            ident: Some("typst-test"),
+            timestamp: world.today(Some(0)),
+            standard: self.standard,
+            page_ranges: self.page_ranges.clone(),
+        }
+    }
+}
+
+/// What the part's pages exported to a single SVG must contain.
+///
+/// Each `// SVG: ..` line names a snippet the file must contain and each
+/// `// SVG-Lacks: ..` line one it must not contain. Snippets are matched
+/// against the file with whitespace collapsed to single spaces. In addition,
+/// all references within the file must point to an element that exists.
+#[derive(Default)]
+struct SvgExpectations {
+    contains: Vec<String>,
+    lacks: Vec<String>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -720,12 +819,38 @@ fn parse_part_metadata(source: &Source) -> TestPartMetadata {
     let mut compare_ref = None;
     let mut validate_hints = None;
     let mut annotations = HashSet::default();
+    let mut pdf = None::<PdfExpectations>;
+    let mut svg = None::<SvgExpectations>;
 
     let lines: Vec<_> = source.text().lines().map(str::trim).collect();
     for (i, line) in lines.iter().enumerate() {
         compare_ref = get_flag_metadata(line, "Ref").or(compare_ref);
         validate_hints = get_flag_metadata(line, "Hints").or(validate_hints);
 
+        if let Some(standard) = get_metadata(line, "PDF-Standard") {
+            pdf.get_or_insert_with(Default::default).standard = Some(match standard {
+                "a-2b" => PdfStandard::A2b,
+                "a-3b" => PdfStandard::A3b,
+                _ => panic!("unknown PDF standard {standard}"),
+            });
+        }
+        if let Some(pages) = get_metadata(line, "PDF-Pages") {
+            pdf.get_or_insert_with(Default::default).page_ranges =
+                Some(parse_page_ranges(pages));
+        }
+        if let Some(snippet) = get_metadata(line, "PDF") {
+            pdf.get_or_insert_with(Default::default).contains.push(snippet.into());
+        }
+        if let Some(snippet) = get_metadata(line, "PDF-Lacks") {
+            pdf.get_or_insert_with(Default::default).lacks.push(snippet.into());
+        }
+        if let Some(snippet) = get_metadata(line, "SVG") {
+            svg.get_or_insert_with(Default::default).contains.push(snippet.into());
+        }
+        if let Some(snippet) = get_metadata(line, "SVG-Lacks") {
+            svg.get_or_insert_with(Default::default).lacks.push(snippet.into());
+        }
+
         fn num(s: &mut Scanner) -> Option<isize> {
             let mut first = true;
             let n = &s.eat_while(|c: char| {
@@ -769,7 +894,142 @@ fn parse_part_metadata(source: &Source) -> TestPartMetadata {
     TestPartMetadata {
         part_configuration: TestConfiguration { compare_ref, validate_hints },
         annotations,
+        pdf,
+        svg,
+    }
+}
+
+/// Parse page ranges like `1,3-4,6-`.
+fn parse_page_ranges(text: &str) -> PageRanges {
+    let number = |s: &str| (!s.is_empty()).then(|| s.parse().unwrap());
+    PageRanges::new(
+        text.split(',')
+            .map(|range| match range.split_once('-') {
+                Some((start, end)) => number(start)..=number(end),
+                None => number(range)..=number(range),
+            })
+            .collect(),
+    )
+}
+
+/// Check an exported PDF against a part's expectations.
+fn test_pdf(output: &mut String, i: usize, data: &[u8], pdf: &PdfExpectations) -> bool {
+    let text = pdf_text(data);
+    let mut ok = true;
+
+    for snippet in &pdf.contains {
+        if !text.contains(snippet.as_str()) {
+            writeln!(output, "  Subtest {i} PDF does not contain `{snippet}`.").unwrap();
+            ok = false;
+        }
     }
+
+    for snippet in &pdf.lacks {
+        if text.contains(snippet.as_str()) {
+            writeln!(output, "  Subtest {i} PDF unexpectedly contains `{snippet}`.")
+                .unwrap();
+            ok = false;
+        }
+    }
+
+    ok
+}
+
+/// Check a document exported to SVG against a part's expectations.
+fn test_svg(
+    output: &mut String,
+    i: usize,
+    document: &Document,
+    svg: &SvgExpectations,
+) -> bool {
+    let data = typst_svg::svg_merged(document, Abs::pt(5.0), NonZeroUsize::ONE, None);
+    let text = data.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut ok = true;
+
+    for snippet in &svg.contains {
+        if !text.contains(snippet.as_str()) {
+            writeln!(output, "  Subtest {i} SVG does not contain `{snippet}`.").unwrap();
+            ok = false;
+        }
+    }
+
+    for snippet in &svg.lacks {
+        if text.contains(snippet.as_str()) {
+            writeln!(output, "  Subtest {i} SVG unexpectedly contains `{snippet}`.")
+                .unwrap();
+            ok = false;
+        }
+    }
+
+    for reference in text.split("href=\"#").skip(1) {
+        let id = reference.split('"').next().unwrap_or_default();
+        if !text.contains(&format!("id=\"{id}\"")) {
+            writeln!(output, "  Subtest {i} SVG refers to missing element `{id}`.")
+                .unwrap();
+            ok = false;
+        }
+    }
+
+    ok
+}
+
+/// Turn a PDF file into text that expectations can be matched against:
+/// Compressed streams are inflated and all whitespace is collapsed into single
+/// spaces.
+fn pdf_text(data: &[u8]) -> String {
+    const START: &[u8] = b">>\nstream\n";
+    const END: &[u8] = b"\nendstream";
+
+    let find = |haystack: &[u8], needle: &[u8]| {
+        haystack.windows(needle.len()).position(|window| window == needle)
+    };
+
+    let mut plain = vec![];
+    let mut rest = data;
+    while let Some(start) = find(rest, START) {
+        let (head, tail) = rest.split_at(start + START.len());
+        let end = find(tail, END).unwrap_or(tail.len());
+        plain.extend_from_slice(head);
+        match miniz_oxide::inflate::decompress_to_vec_zlib(&tail[..end]) {
+            Ok(inflated) => plain.extend(inflated),
+            Err(_) => plain.extend_from_slice(&tail[..end]),
+        }
+        rest = &tail[end..];
+    }
+    plain.extend_from_slice(rest);
+
+    String::from_utf8_lossy(&plain)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Replace the location-based ids in exported HTML with sequential ones. The
+/// location hashes depend on file ids, which differ between test runs.
+fn normalize_html(html: &str) -> String {
+    const PREFIX: &str = "loc-";
+    const LEN: usize = 32;
+
+    let mut ids = HashMap::new();
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find(PREFIX) {
+        let (head, tail) = rest.split_at(start + PREFIX.len());
+        out.push_str(head);
+        let hash = tail
+            .get(..LEN)
+            .filter(|hash| hash.bytes().all(|b| b.is_ascii_hexdigit()));
+        rest = match hash {
+            Some(hash) => {
+                let next = ids.len() + 1;
+                write!(out, "{}", ids.entry(hash).or_insert(next)).unwrap();
+                &tail[LEN..]
+            }
+            None => tail,
+        };
+    }
+    out.push_str(rest);
+    out
 }
 
 /// Pseudorandomly edit the source file and test whether a reparse produces the
@@ -940,7 +1200,15 @@ fn render(frames: &[Frame]) -> sk::Pixmap {
         Color::WHITE,
         padding,
         Color::BLACK,
-    );
+        NonZeroUsize::ONE,
+    )
+    .unwrap_or_else(|| {
+        // Tests whose parts all fail have no pages and show just the padding.
+        let padding = (pixel_per_pt * padding.to_pt() as f32).round() as u32;
+        let mut pixmap = sk::Pixmap::new(2 * padding, padding).unwrap();
+        pixmap.fill(sk::Color::BLACK);
+        pixmap
+    });
 
     let padding = (pixel_per_pt * padding.to_pt() as f32).round();
     let [x, mut y] = [padding; 2];
@@ -0,0 +1,39 @@
+// Test plugin functions that take and return values.
+// Ref: false
+
+---
+#let p = plugin("/files/plugin-typed.wasm")
+#test(p.answer(), 42)
+#test(p.echo((a: 1, b: ("x", 2.5, none))), (a: 1, b: ("x", 2.5, none)))
+#test(p.second("first", true), true)
+
+// Functions that are not typed still use bytes.
+#test(p.raw(bytes("hi")), bytes("hi"))
+
+---
+#let p = plugin("/files/plugin-typed.wasm")
+
+// Error: 2-20 plugin does not contain a function called typst_manifest
+#p.typst_manifest()
+
+---
+#let p = plugin("/files/plugin-typed.wasm")
+
+// Error: 2-14 plugin function takes 1 argument, but 2 were given
+#p.echo(1, 2)
+
+---
+#let p = plugin("/files/plugin-typed.wasm")
+
+// Error: 2-13 plugin function `garbage` returned invalid data (failed to parse CBOR (Semantic(None, "invalid type: break, expected non-break")))
+#p.garbage()
+
+---
+#let p = plugin("/files/plugin-typed.wasm")
+
+// Error: 8-10 expected bytes, found integer
+#p.raw(12)
+
+---
+// Error: 9-39 plugin manifest is invalid (declares 2 parameters for `echo`, but the function takes 1)
+#plugin("/files/plugin-typed-bad.wasm")
@@ -0,0 +1,61 @@
+// Test WASI support and resource limits of WebAssembly plugins.
+// Ref: false
+
+---
+#let p = plugin("/files/plugin-wasi.wasm", wasi: true)
+
+// Warning: 7-16 plugin printed: Hello from WASI
+// Warning: 7-16 plugin printed: second line
+#test(p.print(), bytes("Hello"))
+
+---
+// The clock, randomness, and unsupported functions are deterministic.
+#let p = plugin("/files/plugin-wasi.wasm", wasi: true)
+#test(p.time(), bytes((0,) * 8))
+#test(array(p.random()), (220, 164, 133, 58, 22, 107, 225, 192, 124, 196, 67, 212))
+#test(array(p.unsupported()), (52,))
+
+---
+// The reactor initializer runs once on load and is not callable.
+#let p = plugin("/files/plugin-wasi.wasm", wasi: true)
+#test(p.initialized(), bytes((42,)))
+
+// Error: 2-17 plugin does not contain a function called _initialize
+#p._initialize()
+
+---
+#let p = plugin("/files/plugin-wasi.wasm", wasi: true)
+
+// Error: 2-10 plugin exited with code 3
+#p.exit()
+
+---
+// Error: 9-34 plugin imports WASI functions, but was not loaded with `wasi: true`
+#plugin("/files/plugin-wasi.wasm")
+
+---
+#let p = plugin("/files/plugin-wasi.wasm", wasi: true, fuel: 10000)
+
+// Error: 2-10 plugin exceeded its fuel limit of 10000
+#p.spin()
+
+---
+// The fuel limit applies to each call separately.
+#let p = plugin("/files/plugin-wasi.wasm", wasi: true, fuel: 100)
+#test(p.time(), bytes((0,) * 8))
+#test(p.initialized(), bytes((42,)))
+#test(p.unsupported(), bytes((52,)))
+
+---
+#let p = plugin("/files/plugin-wasi.wasm", wasi: true, memory: 65536 * 4)
+
+// Error: 2-10 plugin exceeded its memory limit of 262144 bytes
+#p.grow()
+
+---
+// Error: 9-34 plugin exceeded its memory limit of 1000 bytes
+#plugin("/files/plugin-wasi.wasm", wasi: true, memory: 1000)
+
+---
+// Error: 54-56 number must be at least zero
+#plugin("/files/plugin-wasi.wasm", wasi: true, fuel: -1)
@@ -30,6 +30,42 @@
 #test(data.at(2).Weight, "150kg")
 #test(data.at(1).Species, "Tiger")
 
+---
+// Test quoting, comments, and trimming.
+#let data = csv.decode(
+  "# Exported table\n name |'note'\n Debby |'big | heavy'\n",
+  delimiter: "|",
+  quote: "'",
+  comment: "#",
+  trim: true,
+  row-type: dictionary,
+)
+#test(data, ((name: "Debby", note: "big | heavy"),))
+
+---
+// Test type inference.
+#let data = csv.decode("1,-2.5,true,1e3,inf,,x", infer-types: true)
+#test(data, ((1, -2.5, true, 1000.0, "inf", "", "x"),))
+#test(csv.decode("1,true"), (("1", "true"),))
+
+---
+// Test type inference with dictionary rows.
+#let data = csv.decode("a,b\n3,false", row-type: dictionary, infer-types: true)
+#test(data, ((a: 3, b: false),))
+
+---
+// Error: 25-29 quote must be a single character
+#csv.decode("a", quote: "''")
+
+---
+// Error: 27-29 comment must not be empty
+#csv.decode("a", comment: "")
+
+---
+// Test error line numbers with comment lines.
+// Error: 13-26 failed to parse CSV (found 1 instead of 2 fields in line 3)
+#csv.decode("# a\nx,y\nz", comment: "#")
+
 ---
 // Error: 6-16 file not found (searched at typ/compute/nope.csv)
 #csv("nope.csv")
@@ -43,6 +79,39 @@
 // Error: 6-22 failed to parse CSV (found 3 instead of 2 fields in line 3)
 #csv("/files/bad.csv", row-type: dictionary)
 
+---
+// Test encoding CSV data.
+#let data = csv("/files/zoo.csv")
+#test(csv.decode(csv.encode(data)), data)
+#test(csv.encode((("a", 1, 2.5), (true, none, "b;c"))), "a,1,2.5\ntrue,,b;c\n")
+#test(csv.encode((("a", "b;c"),), delimiter: ";"), "a;\"b;c\"\n")
+
+---
+// Test encoding CSV data with dictionary rows.
+#let data = csv("/files/zoo.csv", row-type: dictionary)
+#test(csv.decode(csv.encode(data), row-type: dictionary), data)
+#test(csv.encode(((a: 1, b: 2), (b: 3))), "a,b\n1,2\n,3\n")
+
+---
+// Error: 13-41 row 2 has key "c" that is not in the header
+#csv.encode(((a: 1, b: 2), (b: 3, c: 4)))
+
+---
+// Error: 13-29 expected all rows to be arrays or all to be dictionaries, found dictionary in row 2
+#csv.encode((("a",), (a: 1)))
+
+---
+// Error: 13-33 failed to encode value as CSV (found 1 instead of 2 fields in a row)
+#csv.encode((("a", "b"), ("c",)))
+
+---
+// Error: 13-22 expected string, number, boolean, or none, found content
+#csv.encode((([a],),))
+
+---
+// Error: 13-29 cannot encode non-finite float inf as CSV
+#csv.encode(((1, calc.inf),))
+
 ---
 // Test reading JSON data.
 #let data = json("/files/zoo.json")
@@ -139,6 +208,32 @@
   ),
 ),))
 
+---
+// Test encoding XML data.
+#let data = xml("/files/data.xml")
+#test(xml.decode(xml.encode(data)), data)
+#test(
+  xml.encode((tag: "a", attrs: (title: "\"1 < 2\"\n"), children: ("x & y", (tag: "b")))),
+  "<a title=\"&quot;1 &lt; 2&quot;&#10;\">x &amp; y<b/></a>",
+)
+
+---
+// Test that escaped text round-trips.
+#let data = ((tag: "p", attrs: (x: "\ta\"b\"\n"), children: ("<&>\r\n",)),)
+#test(xml.decode(xml.encode(data)), data)
+
+---
+// Error: 13-25 "a b" is not a valid XML name
+#xml.encode((tag: "a b"))
+
+---
+// Error: 13-34 unexpected key "child", valid keys are "tag", "attrs", and "children"
+#xml.encode((tag: "a", child: ()))
+
+---
+// Error: 13-15 expected string, array, or dictionary, found integer
+#xml.encode(12)
+
 ---
 // Error: 6-22 failed to parse XML (found closing tag 'data' instead of 'hello' in line 3)
 #xml("/files/bad.xml")
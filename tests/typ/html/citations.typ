@@ -0,0 +1,9 @@
+// Test HTML export of citations and bibliographies.
+// HTML: true
+// Ref: false
+
+---
+As shown by @netwok and @arrgh.
+Also #cite(<netwok>, form: "prose").
+
+#bibliography("/files/works.bib")
@@ -0,0 +1,9 @@
+// Test HTML export of footnotes.
+// HTML: true
+// Ref: false
+
+---
+Hello#footnote[First note] world#footnote[Second *note*].
+
+---
+A#footnote[Shared] <fn> and B#footnote(<fn>).
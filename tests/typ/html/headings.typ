@@ -0,0 +1,18 @@
+// Test HTML export of headings.
+// HTML: true
+// Ref: false
+
+---
+= Introduction
+Some text.
+
+== Details <details>
+See #link(<details>)[the details].
+
+#heading(level: 3, outlined: false)[Deep]
+
+---
+#set heading(numbering: "1.a")
+= First
+== Second
+= Third
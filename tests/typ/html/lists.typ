@@ -0,0 +1,19 @@
+// Test HTML export of lists.
+// HTML: true
+// Ref: false
+
+---
+- One
+- Two
+  - Nested
+- Three
+
+---
++ First
++ Second
+
+#enum(start: 5)[Fifth][Sixth]
+
+---
+/ Term: Description
+/ Other: More
@@ -0,0 +1,31 @@
+// Test HTML export of math.
+// HTML: true
+// Ref: false
+
+---
+Inline $x^2 + y_1$ and block:
+$ sum_(i=0)^n i = (n(n+1)) / 2 $
+
+---
+// Set rules on math elements apply to MathML.
+#set math.mat(delim: "[")
+#set math.vec(delim: "[")
+#set math.cases(delim: "[")
+$ mat(1, 2; 3, 4) quad vec(a, b) quad cases(x "if" y, z) $
+
+---
+#set math.cancel(cross: true)
+$ cancel(x) $
+
+---
+#set math.op(limits: true)
+$ op("argmax", limits: #false)_x quad lim_x quad op("mx")_x $
+
+---
+// Show rules on math elements are realized.
+#show math.frac: it => $#it.num slash #it.denom$
+$ a / b $
+
+---
+#show "x": $y$
+$ x + 1 $
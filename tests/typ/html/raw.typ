@@ -0,0 +1,15 @@
+// Test HTML export of raw text.
+// HTML: true
+// Ref: false
+
+---
+Inline `let x = 1;` code.
+
+```rust
+fn main() {
+    println!("<Hello>");
+}
+```
+
+---
+#raw("a & b", block: true)
@@ -0,0 +1,18 @@
+// Test HTML export of tables.
+// HTML: true
+// Ref: false
+
+---
+#table(
+  columns: 2,
+  table.header[*Name*][*Value*],
+  [A], [1],
+  [B], [2],
+)
+
+---
+#table(
+  columns: 3,
+  table.cell(rowspan: 2)[A], [B], [C],
+  table.cell(colspan: 2)[D],
+)
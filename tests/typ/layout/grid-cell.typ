@@ -0,0 +1,153 @@
+// Test basic styling using the grid.cell element.
+
+---
+// Cell override
+#grid(
+  align: left,
+  fill: red,
+  stroke: blue,
+  inset: 5pt,
+  columns: 2,
+  [AAAAA], [BBBBB],
+  [A], [B],
+  grid.cell(align: right)[C], [D],
+  align(right)[E], [F],
+  align(horizon)[G], [A\ A\ A],
+  grid.cell(align: horizon)[G2], [A\ A\ A],
+  grid.cell(inset: 0pt)[I], [F],
+  [H], grid.cell(fill: blue)[J]
+)
+
+---
+// Cell show rule
+#show grid.cell: it => [Zz]
+
+#grid(
+  align: left,
+  fill: red,
+  stroke: blue,
+  inset: 5pt,
+  columns: 2,
+  [AAAAA], [BBBBB],
+  [A], [B],
+  grid.cell(align: right)[C], [D],
+  align(right)[E], [F],
+  align(horizon)[G], [A\ A\ A]
+)
+
+---
+#show grid.cell: it => (it.align, it.fill)
+#grid(
+  align: left,
+  row-gutter: 5pt,
+  [A],
+  grid.cell(align: right)[B],
+  grid.cell(fill: aqua)[B],
+)
+
+---
+// Cell set rules
+#set grid.cell(align: center)
+#show grid.cell: it => (it.align, it.fill, it.inset)
+#set grid.cell(inset: 20%)
+#grid(
+  align: left,
+  row-gutter: 5pt,
+  [A],
+  grid.cell(align: right)[B],
+  grid.cell(fill: aqua)[B],
+)
+
+---
+// Test folding per-cell properties (align and inset)
+#grid(
+  columns: (1fr, 1fr),
+  rows: (2.5em, auto),
+  align: right,
+  inset: 5pt,
+  fill: (x, y) => (green, aqua).at(calc.rem(x + y, 2)),
+  [Top], grid.cell(align: bottom)[Bot],
+  grid.cell(inset: (bottom: 0pt))[Bot], grid.cell(inset: (bottom: 0pt))[Bot]
+)
+
+---
+// Test per-cell strokes.
+#grid(
+  columns: 3,
+  inset: 5pt,
+  stroke: 1pt + black,
+  [A], grid.cell(stroke: 2pt + red)[B], [C],
+  grid.cell(stroke: (left: none, right: none))[D], [E], [F],
+  [G], grid.cell(stroke: (bottom: (paint: blue, dash: "dashed")))[H], [I],
+)
+
+---
+// Test cell positioning.
+#set page(width: auto)
+#show grid.cell: it => {
+  if it.x == 0 and it.y == 0 {
+    set text(red)
+    it
+  } else {
+    it
+  }
+}
+
+#grid(
+  columns: 3,
+  stroke: 0.5pt,
+  inset: 3pt,
+  grid.cell(x: 1, y: 1)[Center],
+  [A], [B], [C],
+  grid.cell(y: 2)[Row 2],
+  grid.cell(x: 2)[Col 2],
+  [D],
+)
+
+---
+// Test positioning with gutter and a custom x with automatic y.
+#table(
+  columns: 3,
+  gutter: 3pt,
+  table.cell(x: 2)[a],
+  table.cell(x: 2)[b],
+  [c], [d],
+  table.cell(y: 3)[e],
+)
+
+---
+// Error: 3:3-3:27 attempted to place a second cell at column 0, row 0
+// Hint: 3:3-3:27 try specifying your cells in a different order
+#grid(
+  [A],
+  grid.cell(x: 0, y: 0)[B],
+)
+
+---
+// Error: 3:3-3:21 cell could not be placed at invalid column 2
+#grid(
+  columns: 2,
+  grid.cell(x: 2)[A],
+)
+
+---
+// Error: 5:3-5:21 cell could not be placed in row 0 because it was full
+// Hint: 5:3-5:21 try specifying your cells in a different order
+#grid(
+  columns: 2,
+  grid.cell(y: 0)[a],
+  grid.cell(y: 0)[b],
+  grid.cell(y: 0)[c],
+)
+
+---
+// Error: 8:3-8:29 cannot use `table.cell` as a grid cell; use `grid.cell` instead
+#grid(
+  columns: 2,
+  [a],
+  [b],
+  [c],
+  [d],
+  [e],
+  table.cell(fill: green)[f],
+)
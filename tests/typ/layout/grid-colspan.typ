@@ -0,0 +1,83 @@
+// Test cells spanning multiple columns.
+
+---
+#grid(
+  columns: 4,
+  fill: (x, y) => if calc.odd(x + y) { blue.lighten(50%) } else { blue.lighten(10%) },
+  inset: 5pt,
+  align: center,
+  grid.cell(colspan: 4)[*Full Header*],
+  grid.cell(colspan: 2, fill: orange)[*Half*],
+  grid.cell(colspan: 2, fill: orange.darken(10%))[*Half*],
+  [*A*], [*B*], [*C*], [*D*],
+  [1], [2], [3], [4],
+  [5], grid.cell(colspan: 3, fill: orange.darken(10%))[6],
+  grid.cell(colspan: 2, fill: orange)[7], [8], [9],
+  [10], grid.cell(colspan: 2, fill: orange.darken(10%))[11], [12]
+)
+
+#table(
+  columns: 4,
+  fill: (x, y) => if calc.odd(x + y) { blue.lighten(50%) } else { blue.lighten(10%) },
+  inset: 5pt,
+  align: center,
+  table.cell(colspan: 4)[*Full Header*],
+  table.cell(colspan: 2, fill: orange)[*Half*],
+  table.cell(colspan: 2, fill: orange.darken(10%))[*Half*],
+  [*A*], [*B*], [*C*], [*D*],
+  [1], [2], [3], [4],
+  [5], table.cell(colspan: 3, fill: orange.darken(10%))[6],
+  table.cell(colspan: 2, fill: orange)[7], [8], [9],
+  [10], table.cell(colspan: 2, fill: orange.darken(10%))[11], [12]
+)
+
+---
+// Colspan with gutter.
+#table(
+  columns: 4,
+  gutter: 3pt,
+  inset: 5pt,
+  align: center,
+  table.cell(colspan: 4)[*Full Header*],
+  table.cell(colspan: 2, fill: orange)[*Half*],
+  table.cell(colspan: 2, fill: orange.darken(10%))[*Half*],
+  [*A*], [*B*], [*C*], [*D*],
+  [5], table.cell(colspan: 3, fill: orange.darken(10%))[6],
+)
+
+---
+// Colspan over auto and fractional columns.
+#set page(width: 300pt)
+#table(
+  columns: (auto, auto, 1fr),
+  [A], [B], [C],
+  table.cell(colspan: 2)[This cell spans two auto columns], [D],
+  table.cell(colspan: 3)[This cell spans all columns],
+)
+
+---
+// Colspan in RTL.
+#set text(dir: rtl)
+#table(
+  columns: 4,
+  fill: (x, y) => if calc.odd(x + y) { aqua } else { yellow },
+  [a], table.cell(colspan: 2)[b], [c],
+  table.cell(colspan: 3)[d], [e],
+)
+
+---
+// Error: 3:8-3:32 cell's colspan would cause it to exceed the available column(s)
+// Hint: 3:8-3:32 try placing the cell in another position or reducing its colspan
+#grid(
+  columns: 3,
+  [a], grid.cell(colspan: 3)[b]
+)
+
+---
+// Error: 4:3-4:27 attempted to place a second cell at column 1, row 0
+// Hint: 4:3-4:27 try specifying your cells in a different order
+#grid(
+  columns: 3,
+  grid.cell(x: 0, y: 0, colspan: 2)[a],
+  grid.cell(x: 1, y: 0)[b]
+)
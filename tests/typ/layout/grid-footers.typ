@@ -0,0 +1,82 @@
+// Test repeating grid and table footers.
+
+---
+#set page(width: auto, height: 15em)
+#set text(6pt)
+#set table(inset: 2pt, stroke: 0.5pt)
+#table(
+  columns: 5,
+  align: center + horizon,
+  table.header(
+    table.cell(colspan: 5)[*Cool Zone*],
+    table.cell(stroke: red)[*Name*], table.cell(stroke: aqua)[*Number*], [*Data 1*], [*Data 2*], [*Etc*],
+  ),
+  ..range(24).map(i => ([John \##i], table.cell(stroke: green)[123], table.cell(stroke: blue)[456], [789], [?])).flatten(),
+  table.footer(
+    table.cell(colspan: 4, fill: gray.lighten(60%))[*Total*], [*999*],
+  ),
+)
+
+---
+// Footer without a header, with gutter.
+#set page(height: 9em)
+#grid(
+  columns: 2,
+  gutter: 3pt,
+  ..range(10).map(i => ([#i], [#(i + 1)])).flatten(),
+  grid.footer(
+    [*Sum*], [*55*],
+  ),
+)
+
+---
+// Disable footer repetition.
+#set page(height: 8em)
+#table(
+  columns: 2,
+  ..range(8).map(str),
+  table.footer(
+    [*Left*], [*Right*],
+    repeat: false,
+  ),
+)
+
+---
+// A footer after a partially filled row starts at a new row.
+#table(
+  columns: 3,
+  [a], [b],
+  table.footer([c], [d], [e]),
+)
+
+---
+#table(
+  columns: 2,
+  // Error: 3-25 footer must end at the last row
+  table.footer([a], [b]),
+  [c], [d],
+)
+
+---
+#table(
+  columns: 2,
+  table.cell(rowspan: 2)[a], [b],
+  // Error: 3-42 footer would conflict with a cell placed before it at column 0 row 0
+  // Hint: 3-42 try reducing that cell's rowspan or moving the footer
+  table.footer(table.cell(x: 1, y: 1)[c]),
+)
+
+---
+#table(
+  table.footer([a]),
+  // Error: 3-20 cannot have more than one footer
+  table.footer([b]),
+)
+
+---
+// Error: 7-24 cannot use `table.footer` as a grid footer; use `grid.footer` instead
+#grid(table.footer([a]))
+
+---
+// Error: 21-38 cannot place a table footer within another footer or header
+#table(table.header(table.footer([a])))
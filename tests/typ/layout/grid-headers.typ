@@ -0,0 +1,92 @@
+// Test repeating grid and table headers.
+
+---
+#set page(width: auto, height: 12em)
+#table(
+  columns: 5,
+  align: center + horizon,
+  table.header(
+    table.cell(colspan: 5)[*Cool Zone*],
+    table.cell(fill: blue.lighten(60%))[*Name*], table.cell(fill: blue.lighten(60%))[*Number*], table.cell(fill: blue.lighten(60%))[*Data 1*], table.cell(fill: blue.lighten(60%))[*Data 2*], table.cell(fill: blue.lighten(60%))[*Etc*],
+  ),
+  ..range(24).map(i => ([John \##i], table.cell(stroke: green)[123], table.cell(stroke: blue)[456], [789], [?])).flatten(),
+  [Tom], [1], [2], [3], [4],
+)
+
+---
+// Header with gutter is repeated along with the gutter below it.
+#set page(height: 10em)
+#grid(
+  columns: 2,
+  gutter: 3pt,
+  grid.header(
+    [*Left*], [*Right*],
+  ),
+  ..range(10).map(i => ([#i], [#(i + 1)])).flatten()
+)
+
+---
+// Disable header repetition.
+#set page(height: 8em)
+#table(
+  columns: 2,
+  table.header(
+    [*A*], [*B*],
+    repeat: false,
+  ),
+  ..range(8).map(str)
+)
+
+---
+// Cells with explicit positions can extend the header.
+#table(
+  columns: 3,
+  table.header(
+    table.cell(rowspan: 2)[*A*], [*B*], [*C*],
+  ),
+  [a], [b],
+  [d], [e], [f],
+)
+
+---
+// Header with a rowspan below it repeats above the rowspan's continuation.
+#set page(height: 10em)
+#table(
+  columns: 2,
+  table.header([*H1*], [*H2*]),
+  table.cell(rowspan: 6)[Long],
+  ..range(6).map(i => [Row #i])
+)
+
+---
+// Empty header.
+#table(
+  columns: 2,
+  table.header(),
+  [a], [b],
+)
+
+---
+#table(
+  columns: 2,
+  [a], [b],
+  // Error: 3-29 header must start at the first row
+  // Hint: 3-29 remove any rows before the header
+  table.header([*A*], [*B*]),
+)
+
+---
+#table(
+  table.header([a]),
+  // Error: 3-20 cannot have more than one header
+  table.header([b]),
+  [c],
+)
+
+---
+// Error: 7-24 cannot use `table.header` as a grid header; use `grid.header` instead
+#grid(table.header([a]))
+
+---
+// Error: 19-35 cannot place a grid header within another header or footer
+#grid(grid.header(grid.header([a])))
@@ -0,0 +1,99 @@
+// Test cells spanning multiple rows.
+
+---
+#grid(
+  columns: 4,
+  fill: (x, y) => if calc.odd(x + y) { blue.lighten(50%) } else { blue.lighten(10%) },
+  inset: 5pt,
+  align: center,
+  grid.cell(rowspan: 2, fill: orange)[*Left*],
+  [Right A], [Right A], [Right A],
+  [Right B], grid.cell(colspan: 2, rowspan: 2, fill: orange.darken(10%))[B Wide],
+  [Left A], [Left A],
+  [Left B], [Left B], grid.cell(colspan: 2, rowspan: 3, fill: orange)[Wide and Long]
+)
+
+#table(
+  columns: 4,
+  fill: (x, y) => if calc.odd(x + y) { blue.lighten(50%) } else { blue.lighten(10%) },
+  inset: 5pt,
+  align: center,
+  table.cell(rowspan: 2, fill: orange)[*Left*],
+  [Right A], [Right A], [Right A],
+  [Right B], table.cell(colspan: 2, rowspan: 2, fill: orange.darken(10%))[B Wide],
+  [Left A], [Left A],
+  [Left B], [Left B], table.cell(colspan: 2, rowspan: 3, fill: orange)[Wide and Long]
+)
+
+---
+// Rowspan with gutter.
+#table(
+  columns: 4,
+  gutter: 3pt,
+  fill: (x, y) => if calc.odd(x + y) { blue.lighten(50%) } else { blue.lighten(10%) },
+  inset: 5pt,
+  align: center,
+  table.cell(rowspan: 2, fill: orange)[*Left*],
+  [Right A], [Right A], [Right A],
+  [Right B], table.cell(colspan: 2, rowspan: 2, fill: orange.darken(10%))[B Wide],
+  [Left A], [Left A],
+)
+
+---
+// Rowspan with explicit positions and in RTL.
+#set text(dir: rtl)
+#table(
+  columns: 3,
+  table.cell(x: 1, y: 0, rowspan: 3)[a \ b \ c],
+  [d], [e], [f], [g], [h], [i],
+)
+
+---
+// Rowspan growing the last spanned auto row.
+#table(
+  columns: 2,
+  rows: (10pt, auto),
+  table.cell(rowspan: 2)[a \ b \ c \ d],
+  [x], [y],
+)
+
+---
+// Breakable rowspan across pages.
+#set page(height: 10em)
+#table(
+  columns: 2,
+  table.cell(rowspan: 3)[#lorem(30)],
+  [a], [b], [c],
+)
+
+---
+// Unbreakable rowspan is moved to the next page.
+#set page(height: 10em)
+#v(5em)
+#grid(
+  columns: 2,
+  rows: 2em,
+  stroke: 1pt,
+  grid.cell(rowspan: 3, breakable: false)[a \ b \ c],
+  [d], [e], [f],
+)
+
+---
+// Rowspans across regions with gutter.
+#set page(height: 10em)
+#table(
+  columns: 2,
+  row-gutter: 8pt,
+  [a], table.cell(rowspan: 4)[#lorem(20)],
+  [b], [c], [d],
+)
+
+---
+// Error: 5:3-5:33 cell would span a previously placed cell at column 1, row 1
+// Hint: 5:3-5:33 try specifying your cells in a different order or reducing the cell's rowspan or colspan
+#grid(
+  columns: 2,
+  grid.cell(x: 1, y: 1)[a],
+  grid.cell(rowspan: 2)[b],
+  grid.cell(x: 1, rowspan: 2)[c],
+)
@@ -0,0 +1,112 @@
+// Test per-cell stroke functions and explicit grid and table lines.
+
+---
+// Stroke as a function of the cell's position.
+#table(
+  columns: 3,
+  stroke: (x, y) => if y == 0 { (bottom: 1pt) } else if x > 0 { (left: 0.5pt + gray) },
+  [*A*], [*B*], [*C*],
+  [a], [b], [c],
+  [d], [e], [f],
+)
+
+---
+// Stroke as a dictionary of sides, overridden by a cell.
+#grid(
+  columns: 2,
+  inset: 3pt,
+  stroke: (x: blue, y: 2pt + red),
+  [a], grid.cell(stroke: (top: none))[b],
+  [c], [d],
+)
+
+---
+// Booktabs-style table.
+#set page(width: auto)
+#table(
+  columns: 3,
+  stroke: none,
+  table.hline(stroke: 1pt),
+  table.header[*Metal*][*Density*][*Melting point*],
+  table.hline(stroke: 0.5pt),
+  [Aluminium], [2.70], [660 °C],
+  [Copper], [8.96], [1085 °C],
+  [Iron], [7.87], [1538 °C],
+  table.hline(stroke: 1pt),
+)
+
+---
+// Lines with start and end, and explicit positions.
+#table(
+  columns: 4,
+  stroke: none,
+  [a], [b], [c], [d],
+  table.hline(start: 1, end: 3, stroke: red),
+  [e], [f], [g], [h],
+  table.vline(x: 2, start: 1, stroke: blue),
+  table.hline(y: 0, stroke: green),
+  [i], [j], [k], [l],
+)
+
+---
+// Lines can override and remove cell strokes.
+#table(
+  columns: 3,
+  [a], [b], [c],
+  table.hline(stroke: none),
+  table.vline(x: 1, stroke: 2pt + orange),
+  [d], [e], [f],
+)
+
+---
+// Lines crossing gutter, placed at the bottom and end of a track.
+#grid(
+  columns: 3,
+  gutter: 4pt,
+  inset: 2pt,
+  [a], [b], [c],
+  grid.hline(y: 0, position: bottom),
+  grid.vline(x: 0, position: end),
+  grid.vline(x: 1, position: start, stroke: red),
+  [d], [e], [f],
+)
+
+---
+// Lines in right-to-left tables.
+#set text(dir: rtl)
+#table(
+  columns: 3,
+  stroke: none,
+  table.vline(x: 1, stroke: red),
+  table.vline(x: 2, position: left, stroke: blue),
+  [a], [b], [c],
+)
+
+---
+// Error: 8-37 line cannot end before it starts
+#table(table.hline(start: 2, end: 1), [a])
+
+---
+// Error: 8-25 cannot place horizontal line at invalid row 3
+#table(table.hline(y: 3), [a])
+
+---
+// Error: 8-43 cannot place horizontal line at the 'bottom' position of the bottom border (y = 1)
+// Hint: 8-43 set the line's position to 'top' or place it at a smaller 'y' index
+#table(table.hline(y: 1, position: bottom), [a])
+
+---
+// Error: 8-25 cannot place vertical line at invalid column 2
+#table(table.vline(x: 2), [a])
+
+---
+// Error: 7-20 cannot use `table.hline` as a grid line; use `grid.hline` instead
+#grid(table.hline(), [a])
+
+---
+// Error: 8-20 cannot use `grid.vline` as a table line; use `table.vline` instead
+#table(grid.vline(), [a])
+
+---
+// Error: 42-49 expected `top` or `bottom`
+#table(columns: 2, table.hline(position: horizon), [a], [b])
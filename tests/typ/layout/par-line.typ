@@ -0,0 +1,63 @@
+// Test line numbering.
+
+---
+#set page(width: 150pt, height: auto, margin: (left: 30pt, rest: 10pt))
+#set par.line(numbering: "1")
+
+Roses are red, violets are blue, and line numbers continue.
+
+Inline #box[boxed content] is not numbered on its own.
+
+#set par.line(number-align: left, number-clearance: 4pt)
+#set par.line(numbering: "(i)")
+Aligned to the left. \
+With a custom numbering and clearance.
+
+---
+// Test numbers in the right margin and restarting on each page.
+#set page(width: 120pt, height: 60pt, margin: (right: 30pt, rest: 10pt))
+#set par.line(numbering: "1", number-margin: right, numbering-scope: "page")
+#set page(footer: [Footer])
+
+First page. \
+Still first. \
+Second page. \
+Again.
+
+---
+// Test numbers of lines that sit side by side in two columns.
+#set page(width: 200pt, height: 110pt, margin: (x: 25pt, rest: 10pt), columns: 2)
+#set par.line(numbering: "1")
+#lorem(22)
+
+---
+// Test that numbers take the styles of their paragraph.
+#set page(width: 150pt, height: auto, margin: (left: 40pt, rest: 10pt))
+#set par.line(numbering: "1")
+
+Regular text.
+
+#[
+  #set text(fill: blue, size: 14pt)
+  #set par.line(number-clearance: 1em)
+  Large text with numbers to match.
+]
+
+---
+// Test referencing lines.
+#set page(width: 150pt, height: auto, margin: (left: 30pt, rest: 10pt))
+#set par.line(numbering: "1")
+
+The parties agree to the following
+terms. _All terms are final._ <line-final>
+
+As stated in @line-final, no changes are allowed.
+See also #ref(<line-final>, supplement: [l.]).
+
+---
+// Error: 10-26 can only be used in set rules
+#par.line(numbering: "1")
+
+---
+// Error: 30-36 expected `start`, `left`, `right`, or `end`, found center
+#set par.line(number-margin: center)
@@ -0,0 +1,54 @@
+// Test parent-scoped floats that span all columns.
+
+---
+#set page(height: 200pt, width: 300pt, columns: 2)
+#place(
+  top + center,
+  float: true,
+  scope: "parent",
+  clearance: 8pt,
+  text(14pt)[*A Wide Title*],
+)
+#lorem(30)
+#figure(
+  placement: bottom,
+  scope: "parent",
+  caption: [A wide rectangle],
+  rect(width: 100%, height: 20pt),
+)
+#lorem(40)
+
+---
+// A float from the second column moves to the next page.
+#set page(height: 160pt, width: 300pt, columns: 2)
+#lorem(50)
+#figure(
+  placement: top,
+  scope: "parent",
+  caption: [Deferred],
+  rect(width: 80%, height: 20pt),
+)
+#lorem(30)
+
+---
+// Right-to-left columns.
+#set page(height: 120pt, width: 300pt, columns: 2)
+#set text(dir: rtl)
+#place(top, float: true, scope: "parent", rect(width: 100%, height: 15pt))
+#lorem(30)
+
+---
+// Without columns, the parent scope behaves like the column scope.
+#set page(height: 100pt, width: 150pt)
+#place(bottom, float: true, scope: "parent", rect(width: 100%, height: 15pt))
+#lorem(10)
+
+---
+// Error: 2-48 parent-scoped positioning is only available for floating placement
+// Hint: 2-48 you can enable floating placement with `place(float: true, ..)`
+#place(top, scope: "parent", rect(width: 100%))
+
+---
+// Error: 2-35 parent-scoped placement is only available for floating figures
+// Hint: 2-35 you can enable floating placement with `figure(placement: auto, ..)`
+#figure(scope: "parent", rect[Hi])
@@ -20,6 +20,57 @@ What's up?
 #set document(author: (123,))
 What's up?
 
+---
+// The metadata is written into the info dictionary and the XMP metadata.
+// Ref: false
+// PDF: /Subject (Testing metadata) /Creator (Generator) /CreationDate
+// PDF: /department (Research) /revision-2 (4)
+// PDF: <dc:description><rdf:Alt><rdf:li xml:lang="x-default">Testing metadata</rdf:li></rdf:Alt></dc:description>
+// PDF: <xmp:CreatorTool>Generator</xmp:CreatorTool>
+// PDF: <pdfx:department>Research</pdfx:department><pdfx:revision-2>4</pdfx:revision-2>
+// PDF-Lacks: /Producer
+// PDF-Lacks: <pdf:Producer>
+// PDF-Lacks: pdfaExtension:schemas
+#set document(
+  subject: "Testing metadata",
+  creator: "Generator",
+  producer: none,
+  custom: (department: "Research", revision-2: "4"),
+)
+Hello
+
+---
+// PDF/A requires an extension schema for the custom properties.
+// Ref: false
+// PDF-Standard: a-2b
+// PDF: /Producer (Press)
+// PDF: <pdf:Producer>Press</pdf:Producer>
+// PDF: <pdfaSchema:namespaceURI>http://ns.adobe.com/pdfx/1.3/</pdfaSchema:namespaceURI><pdfaSchema:prefix>pdfx</pdfaSchema:prefix>
+// PDF: <pdfaProperty:name>department</pdfaProperty:name><pdfaProperty:valueType>Text</pdfaProperty:valueType><pdfaProperty:category>external</pdfaProperty:category>
+// PDF: <pdfaProperty:name>revision-2</pdfaProperty:name>
+#set document(producer: "Press", custom: (department: "Research", revision-2: "4"))
+Hello
+
+---
+// The producer defaults to Typst.
+// Ref: false
+// PDF: /Producer (Typst
+// PDF: <pdf:Producer>Typst
+#set document(title: [Hello])
+Hello
+
+---
+// Error: 23-36 expected string, found integer
+#set document(custom: (revision: 4))
+
+---
+// Error: 23-35 `2nd` is not a valid metadata key
+#set document(custom: ("2nd": "b"))
+
+---
+// Error: 23-39 `Title` is reserved for built-in metadata
+#set document(custom: (Title: "Other"))
+
 ---
 Hello
 
@@ -0,0 +1,54 @@
+// Test interactive form fields.
+
+---
+#set page(width: 180pt, height: auto)
+Name: #form.text-field("name", value: "Jane", width: 1fr)
+
+Comments:
+#form.text-field("comments", multiline: true, width: 100%)
+
+#form.checkbox("newsletter", checked: true) Newsletter \
+#form.checkbox("terms") Terms
+
+Size: #form.radio-group("size", ("S", "M", "L"), value: "M")
+
+Country: #form.dropdown("country", ("Germany", "France"), width: 4em)
+
+---
+// Error: 2-51 value must be one of the options
+#form.radio-group("size", ("S", "M"), value: "XL")
+
+---
+// Error: 2-48 value must be one of the options
+#form.dropdown("size", ("S", "M"), value: "XL")
+
+---
+// Error: 15-26 missing argument: options
+#form.dropdown("country")
+
+---
+// Widgets with the same name belong to the same field.
+// Ref: false
+// PDF: /T (size) /Kids [
+// PDF: /FT /Btn /Ff 49152 /V /M /DV /M
+// PDF: /AS /M /AP << /N << /M
+// PDF: /AS /Off /AP << /N << /S
+// PDF: /FT /Tx /V (Jane) /DV (Jane)
+// PDF: /NeedAppearances true
+#form.radio-group("size", ("S", "M"), value: "M")
+#form.text-field("name", value: "Jane")
+
+---
+// Ref: false
+// PDF-Lacks: /AcroForm
+#form.text-field("size")
+// Error: 2-23 field `size` is already a text field
+// Hint: 2-23 widgets with the same name belong to the same field
+#form.checkbox("size")
+
+---
+// Ref: false
+// PDF-Lacks: /AcroForm
+// Error: 2-42 radio button option cannot be named `Off`
+// Hint: 2-42 `Off` is the state of unselected buttons in PDF
+#form.radio-group("power", ("On", "Off"))
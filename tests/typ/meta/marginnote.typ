@@ -0,0 +1,37 @@
+// Test margin notes.
+
+---
+#set page(width: 220pt, height: 160pt, margin: (left: 15pt, right: 80pt, y: 15pt))
+#set text(9pt)
+Notes sit in the margin.#marginnote[A first note.]
+They are aligned with their line.
+#lorem(8)#marginnote[A second note in the same line that wraps.]#marginnote[
+  Overlapping notes move down.
+]
+
+#lorem(12)#marginnote(gap: 4pt, width: 40pt)[Narrow.]
+
+---
+// Two-sided documents use the outer margin.
+#set page(
+  width: 160pt,
+  height: 80pt,
+  margin: (inside: 15pt, outside: 60pt, y: 10pt),
+)
+#set text(8pt)
+Recto.#marginnote[Right.]
+#pagebreak()
+Verso.#marginnote[Left.]
+#pagebreak()
+#set page(binding: right)
+Bound right.#marginnote[Left.]
+
+---
+// Numbered notes share a counter.
+#set page(width: 200pt, height: auto, margin: (left: 10pt, right: 70pt, y: 10pt))
+#set text(8pt)
+#set marginnote(numbering: "1")
+Numbered#marginnote[One.] notes#marginnote(numbering: none)[Unnumbered.]
+count#marginnote[Two.] up.
+
+#locate(loc => counter(marginnote).at(loc))
@@ -0,0 +1,71 @@
+// Test embedding files into exported PDFs.
+// Ref: false
+
+---
+#pdf.embed(
+  "/files/data.csv",
+  relationship: "data",
+  mime-type: "text/csv",
+  description: "The data behind the chart",
+)
+
+// Embedded files produce no content, but can be queried.
+#locate(loc => {
+  let embeds = query(pdf.embed, loc)
+  test(embeds.len(), 1)
+  test(embeds.first().path, "/files/data.csv")
+  test(embeds.first().relationship, "data")
+  test(embeds.first().mime-type, "text/csv")
+})
+
+---
+// Embedded files are attached to the document by name and as associated
+// files.
+// PDF: /Names << /EmbeddedFiles << /Names [(data.csv)
+// PDF: (hello.txt)
+// PDF: /AF [
+// PDF: /Type /EmbeddedFile /Filter /FlateDecode /Subtype /text#2Fcsv /Params << /Size 31 >>
+// PDF: /Type /Filespec /F (data.csv) /UF (data.csv) /EF << /F
+// PDF: /Desc (The data behind the chart) /AFRelationship /Data
+// PDF: /Type /Filespec /F (hello.txt) /UF (hello.txt) /EF << /F
+// PDF: /AFRelationship /Unspecified
+#pdf.embed(
+  "/files/data.csv",
+  relationship: "data",
+  mime-type: "text/csv",
+  description: "The data behind the chart",
+)
+#pdf.embed("/files/hello.txt")
+
+---
+// Set rules apply to embedded files, also when checking a PDF/A standard.
+// PDF-Standard: a-3b
+// PDF: /Type /EmbeddedFile /Filter /FlateDecode /Subtype /text#2Fplain
+// PDF: /Desc (A greeting) /AFRelationship /Supplement
+#set pdf.embed(
+  relationship: "supplement",
+  mime-type: "text/plain",
+  description: "A greeting",
+)
+#pdf.embed("/files/hello.txt")
+
+---
+// Without embedded files, there is nothing to attach.
+// PDF-Lacks: /EmbeddedFiles
+// PDF-Lacks: /AF
+Hello
+
+---
+// File names must be unique.
+// PDF-Lacks: /EmbeddedFiles
+#pdf.embed("/files/data.csv")
+// Error: 2-30 a file named `data.csv` is already embedded
+#pdf.embed("/files/data.csv")
+
+---
+// Error: 12-32 file not found (searched at files/missing.xml)
+#pdf.embed("/files/missing.xml")
+
+---
+// Error: 45-52 expected "source", "data", "alternative", "supplement", or none
+#pdf.embed("/files/data.csv", relationship: "input")
@@ -0,0 +1,52 @@
+// Test exporting a selection of pages to PDF.
+// Ref: false
+
+---
+// Only the selected pages, their outline entries and their page labels are
+// written.
+// PDF-Pages: 1,3-
+// PDF: /Type /Pages /Count 3
+// PDF: <xmpTPg:NPages>3</xmpTPg:NPages>
+// PDF: /Title (One)
+// PDF: /Title (Three)
+// PDF: /Title (Four)
+// PDF-Lacks: /Title (Two)
+// PDF: /Type /PageLabel /S /r /St 1
+// PDF: /Type /PageLabel /S /D /St 1
+#set page(height: 100pt, numbering: "i")
+= One
+#pagebreak()
+= Two
+#pagebreak()
+#set page(numbering: "1")
+#counter(page).update(1)
+= Three
+#pagebreak()
+= Four
+
+---
+// Links to pages that are not exported are dropped.
+// PDF-Pages: 1,3
+// PDF-Lacks: /Subtype /Link
+#set page(height: 100pt)
+#link(<two>)[To two]
+#pagebreak()
+= Two <two>
+#pagebreak()
+Three
+
+---
+// Links to exported pages are kept.
+// PDF-Pages: 1,3
+// PDF: /Subtype /Link
+#set page(height: 100pt)
+#link(<three>)[To three]
+#pagebreak()
+Two
+#pagebreak()
+= Three <three>
+
+---
+// PDF-Pages: 3-
+// Error: page selection contains no pages of the document
+Hello
@@ -0,0 +1,74 @@
+// Test export in conformance with PDF/A standards.
+// Ref: false
+
+---
+// PDF-Standard: a-2b
+// PDF: /OutputIntents [<< /Type /OutputIntent /S /GTS_PDFA1 /OutputConditionIdentifier (sRGB) /Info (sRGB IEC61966-2.1) /DestOutputProfile
+// PDF: <pdfaid:part>2</pdfaid:part><pdfaid:conformance>B</pdfaid:conformance>
+Hello
+
+---
+// PDF-Standard: a-3b
+// PDF: <pdfaid:part>3</pdfaid:part><pdfaid:conformance>B</pdfaid:conformance>
+// PDF-Lacks: /DeviceCMYK
+Hello
+
+---
+// Without a standard, there is no output intent.
+// PDF-Lacks: /OutputIntents
+// PDF-Lacks: pdfaid
+Hello
+
+---
+// PDF-Standard: a-2b
+// Error: 45-49 PDF/A-2b: the license of the font Noto Serif Hebrew Restricted does not allow embedding it
+// Hint: 45-49 use a different font for this text
+#text(font: "Noto Serif Hebrew Restricted")[שלום]
+
+---
+// PDF-Standard: a-2b
+#set text(fallback: false)
+// Error: 1-3 PDF/A-2b: no font could render the character '中'
+// Hint: 1-3 try a font that contains this character
+A中
+
+---
+// PDF-Standard: a-2b
+// Error: 2-36 PDF/A-2b: CMYK colors cannot be used with an sRGB output intent
+// Hint: 2-36 convert the color to RGB with `rgb(..)`
+#rect(fill: cmyk(0%, 50%, 50%, 0%))
+// Error: 36-38 PDF/A-2b: CMYK colors cannot be used with an sRGB output intent
+// Hint: 36-38 convert the color to RGB with `rgb(..)`
+#text(fill: cmyk(0%, 0%, 0%, 80%))[Hi]
+
+---
+// Pages that are not exported are not checked.
+// PDF-Standard: a-2b
+// PDF-Pages: 1
+// PDF: /Type /Pages /Count 1
+RGB
+#pagebreak()
+#rect(fill: cmyk(0%, 50%, 50%, 0%))
+
+---
+// PDF-Standard: a-3b
+// Error: 2-34 PDF/A-3b: the CMYK color profile of this image does not match its RGB pixels
+// Hint: 2-34 convert the image to sRGB and remove its color profile
+#image("/files/cmyk-profile.jpg")
+
+---
+// Without a standard, the same image exports fine.
+// PDF: /Subtype /Image
+#image("/files/cmyk-profile.jpg")
+
+---
+// PDF-Standard: a-2b
+// Error: 2-30 PDF/A-2b: embedded files are not supported
+// Hint: 2-30 export with PDF/A-3b to embed arbitrary files
+#pdf.embed("/files/data.csv")
+
+---
+// PDF-Standard: a-3b
+// Error: 2-30 PDF/A-3b: embedded files must have a MIME type
+// Hint: 2-30 specify the file's type with the `mime-type` argument
+#pdf.embed("/files/data.csv")
@@ -0,0 +1,107 @@
+// Test the structure tree of tagged PDFs.
+// Ref: false
+
+---
+// PDF: /StructTreeRoot
+// PDF: /MarkInfo << /Marked true >>
+// PDF: /Type /StructTreeRoot
+// PDF: /ParentTree << /Nums [0 [
+// PDF: /StructParents 0 /Tabs /S
+// PDF: /Type /StructElem /S /Document
+// PDF: /Type /StructElem /S /H1
+// PDF: /Type /StructElem /S /P
+// PDF: /Span << /MCID 0 >> BDC
+// PDF: /Type /MCR /MCID 0
+#set document(tagged: true)
+= Introduction
+Hello
+
+---
+// Page headers and footers are artifacts.
+// PDF: /Artifact << /Type /Pagination >> BDC
+#set document(tagged: true)
+#set page(header: [Header])
+Body
+
+---
+// Links refer to their annotations.
+// PDF: /Type /StructElem /S /Link
+// PDF: /Type /OBJR
+// PDF: /Subtype /Link
+// PDF: /StructParent 1
+#set document(tagged: true)
+See #link("https://typst.app")[Typst].
+
+---
+// Footnotes are references to notes.
+// PDF: /Type /StructElem /S /Reference
+// PDF: /Type /StructElem /S /Note
+#set document(tagged: true)
+Text#footnote[Note]
+
+---
+// PDF: /Type /StructElem /S /L
+// PDF: /A [<< /O /List /ListNumbering /Disc >>]
+// PDF: /A [<< /O /List /ListNumbering /Decimal >>]
+// PDF: /Type /StructElem /S /LI
+#set document(tagged: true)
+- One
+- Two
+
++ Three
+
+---
+// PDF: /Type /StructElem /S /Table
+// PDF: /Type /StructElem /S /TR
+// PDF: /Type /StructElem /S /TD
+// PDF: /A [<< /O /Table /RowSpan 2 >>]
+// PDF: /A [<< /O /Table /ColSpan 2 >>]
+// Table lines are artifacts.
+// PDF: /Artifact BMC
+#set document(tagged: true)
+#table(
+  columns: 3,
+  table.cell(rowspan: 2)[A], [B], [C],
+  table.cell(colspan: 2)[D],
+)
+
+---
+// Cells in the header are header cells.
+// PDF: /Type /StructElem /S /TH
+// PDF: /Type /StructElem /S /TD
+#set document(tagged: true)
+#table(
+  columns: 2,
+  table.header[Name][Value],
+  [A], [1],
+)
+
+---
+// Set rules for cells apply to the structure tree.
+// PDF: /A [<< /O /Table /ColSpan 2 >>]
+#set document(tagged: true)
+#set table.cell(colspan: 2)
+#table(columns: 2, [A], [B])
+
+---
+// Images in figures provide the figure's alternate text.
+// PDF: /Type /StructElem /S /Figure
+// PDF: /Alt (A tiger)
+#set document(tagged: true)
+#figure(
+  image("/files/tiger.jpg", width: 40pt, alt: "A tiger"),
+  caption: [A tiger],
+)
+
+---
+// Without tagging, there is no structure.
+// PDF-Lacks: /StructTreeRoot
+// PDF-Lacks: /MarkInfo
+// PDF-Lacks: /StructParent
+// PDF-Lacks: /MCID
+// PDF-Lacks: /Artifact
+#set page(header: [Header])
+= Introduction
+Hello #link("https://typst.app")[Typst]
+
+- One
@@ -0,0 +1,89 @@
+// Test PDF viewer settings.
+// Ref: false
+
+---
+#pdf.viewer(
+  page-layout: "two-page-right",
+  page-mode: "outline",
+  open-to: 2,
+  display-title: false,
+)
+
+// Viewer settings produce no content, but can be queried.
+#locate(loc => {
+  let viewers = query(pdf.viewer, loc)
+  test(viewers.len(), 1)
+  test(viewers.first().page-layout, "two-page-right")
+  test(viewers.first().page-mode, "outline")
+  test(viewers.first().open-to, 2)
+})
+
+---
+// The settings are written into the document catalog.
+// PDF: /PageLayout /TwoPageRight /PageMode /UseAttachments /OpenAction [
+// PDF: /XYZ 0 100 0]
+// PDF-Lacks: /DisplayDocTitle
+#set page(height: 100pt)
+#set document(title: [Report])
+#pdf.viewer(
+  page-layout: "two-page-right",
+  page-mode: "attachments",
+  open-to: 2,
+  display-title: false,
+)
+A
+#pagebreak()
+B
+
+---
+// Set rules apply to the viewer settings.
+// PDF: /PageLayout /OneColumn /PageMode /UseThumbs /OpenAction [
+// PDF-Lacks: /DisplayDocTitle
+#set page(height: 100pt)
+#set document(title: [Report])
+#set pdf.viewer(page-layout: "one-column", page-mode: "thumbnails")
+#set pdf.viewer(open-to: 2, display-title: false)
+#pdf.viewer()
+A
+#pagebreak()
+B
+
+---
+// Viewers show the title of documents that have one.
+// PDF: /ViewerPreferences << /Direction /L2R /DisplayDocTitle true >>
+// PDF-Lacks: /PageLayout
+// PDF-Lacks: /PageMode
+// PDF-Lacks: /OpenAction
+#set document(title: [Report])
+Hello
+
+---
+// PDF-Lacks: /DisplayDocTitle
+Hello
+
+---
+// Pages that are not exported cannot be opened to.
+// PDF-Pages: 1
+// PDF: /PageLayout /SinglePage
+// PDF-Lacks: /OpenAction
+#pdf.viewer(page-layout: "single-page", open-to: 2)
+A
+#pagebreak()
+B
+
+---
+// Error: 2-24 cannot open to page 3
+// Hint: 2-24 the document ends at page 2
+// PDF-Lacks: /OpenAction
+#pdf.viewer(open-to: 3)
+A
+#pagebreak()
+B
+
+---
+// Error: 26-34 expected "single-page", "one-column", "two-column-left", "two-column-right", "two-page-left", "two-page-right", or none
+#pdf.viewer(page-layout: "double")
+
+---
+// Error: 22-23 number must be positive
+#pdf.viewer(open-to: 0)
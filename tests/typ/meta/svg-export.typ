@@ -0,0 +1,23 @@
+// Test links and the text layer in SVG export.
+// Ref: false
+
+---
+// Links to URLs are clickable.
+// SVG: <a xlink:href="https://typst.app"> <rect class="typst-link"
+#link("https://typst.app")[Typst]
+
+---
+// Links within the document point to an anchor at their target, which the
+// test checks to exist.
+// SVG: <a xlink:href="#a
+// SVG: class="typst-anchor" x="15" y="80"
+#set page(height: 60pt)
+#link(<target>)[To the target]
+#pagebreak()
+= Target <target>
+
+---
+// The text layer is escaped and stretched to the width of the glyphs.
+// SVG: font-family="Linux Libertine" font-size="10" textLength="43.359375" lengthAdjust="spacingAndGlyphs"
+// SVG: xml:space="preserve">a &lt; b &amp;&amp; c</text>
+#"a < b && c"
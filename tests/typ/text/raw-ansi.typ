@@ -0,0 +1,33 @@
+// Test ANSI escape sequences in raw text.
+
+---
+#set page(width: 200pt)
+#let esc = "\u{1b}"
+
+#raw(
+  block: true,
+  lang: "ansi",
+  esc + "[1mBold" + esc + "[22m, " + esc + "[3mitalic" + esc + "[0m and "
+    + esc + "[4munderlined" + esc + "[24m.\n"
+    + esc + "[31mRed " + esc + "[92mbright green " + esc + "[39mdefault\n"
+    + esc + "[38;5;208mPalette " + esc + "[38;5;27mcube " + esc + "[38;5;244mgray\n"
+    + esc + "[38;2;200;0;120mTrue color " + esc + "[48;2;255;230;150mon a background\n"
+    + "Carried over" + esc + "[m, " + esc + "[2Kcleared " + esc + "]0;title" + "\u{7}" + "done.",
+)
+
+// Inline, too.
+#raw(lang: "ansi", esc + "[1;34mok" + esc + "[0m")
+
+---
+// The plain text of the lines has no escape sequences.
+#show raw: it => {
+  test(it.lines.map(line => line.text), ("red", "bold"))
+}
+
+#raw(lang: "ansi", "\u{1b}[31mred\n\u{1b}[1mbold\u{1b}[0m")
+
+---
+// Invalid parameters are skipped instead of resetting the style, and parameters
+// beyond a byte are not truncated.
+#let esc = "\u{1b}"
+#raw(lang: "ansi", esc + "[1;70000;31mbold red " + esc + "[300;4munderlined " + esc + "[38;5;300;3mitalic")
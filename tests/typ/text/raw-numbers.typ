@@ -0,0 +1,62 @@
+// Test line numbers, line ranges, and highlighted lines in raw blocks.
+
+---
+#set page(width: 180pt)
+#set raw(numbers: true)
+
+```rust
+fn main() {
+    println!("Hello World!");
+}
+```
+
+// Inline raw text is not numbered.
+Call `main()` to start.
+
+---
+// Test the start line and highlighted lines.
+#set page(width: 180pt)
+#set raw(highlight-lines: (11, 12))
+
+#raw(
+  block: true,
+  lang: "typ",
+  numbers: true,
+  start-line: 10,
+  "= Heading\n#let x = 1\n#let y = 2\n*Strong*",
+)
+
+// Highlighting works without numbers, too.
+```py
+def f(x):
+    return x ** 2
+```
+
+---
+// Test slicing with a line range, which keeps the original numbers.
+#set page(width: 180pt)
+#let code = "fn main() {\n    let x = 1;\n    let y = 2;\n    println!(\"{}\", x + y);\n}"
+#raw(code, block: true, lang: "rust", numbers: true, line-range: (2, 4), highlight-lines: (3,))
+#raw(code, block: true, lang: "rust", line-range: (4, 10))
+
+---
+// Test that lines are still accessible.
+#show raw: it => {
+  test(it.lines.len(), 2)
+  test(it.lines.first().text, "b")
+  test(it.lines.first().number, 1)
+}
+
+#raw("a\nb\nc\nd", line-range: (2, 3), start-line: 0)
+
+---
+// Error: 23-29 line range must not end before it starts
+#raw("a", line-range: (3, 2))
+
+---
+// Error: 23-27 line range must contain exactly two line numbers
+#raw("a", line-range: (1,))
+
+---
+// Error: 2-51 line range starts at line 50, but the text only has 3 lines
+#raw("a\nb\nc", block: true, line-range: (50, 60))